@@ -0,0 +1,342 @@
+//! The snake's body, stored as straight runs rather than one entry per cell.
+//!
+//! A run is a head-end cell, the heading it was laid down with and a length,
+//! so a thousand-cell snake on a giant board takes a few dozen runs, and a
+//! tick only touches the runs at either end. Collision checks go run by run.
+//! Cells stacked on one spot, as the tail is by growing in place, make a run
+//! without a heading that unfolds as the snake moves on. Saves still list
+//! every cell, so they load as before.
+
+use crate::{Cell, Direction};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone)]
+struct Run {
+    start: Cell,            // the cell nearest the head
+    dir: Option<Direction>, // the heading it was laid down with, `None` for copies of `start`
+    len: u16,
+}
+
+impl Run {
+    /// the `i`th cell back from the start
+    fn cell(&self, i: u16) -> Cell {
+        match self.dir {
+            Some(dir) => self.start.clone_with_pos_shift(dir.opposite(), i),
+            None => self.start.clone(),
+        }
+    }
+
+    /// how far back from the start `pos` first lies, if the run covers it
+    fn offset(&self, (x, y): (u16, u16)) -> Option<u16> {
+        let (sx, sy) = self.start.pos;
+        let back = match self.dir {
+            Some(Direction::Up) if x == sx => y.checked_sub(sy),
+            Some(Direction::Down) if x == sx => sy.checked_sub(y),
+            Some(Direction::Left) if y == sy => x.checked_sub(sx),
+            Some(Direction::Right) if y == sy => sx.checked_sub(x),
+            None if (x, y) == (sx, sy) => Some(0),
+            _ => None,
+        }?;
+        (back < self.len).then_some(back)
+    }
+}
+
+/// the heading of a single step from `from` to `to`, `Some(None)` for no
+/// step at all and `None` if they aren't neighbours
+fn step((fx, fy): (u16, u16), (tx, ty): (u16, u16)) -> Option<Option<Direction>> {
+    match (i32::from(tx) - i32::from(fx), i32::from(ty) - i32::from(fy)) {
+        (0, 0) => Some(None),
+        (0, -1) => Some(Some(Direction::Up)),
+        (0, 1) => Some(Some(Direction::Down)),
+        (-1, 0) => Some(Some(Direction::Left)),
+        (1, 0) => Some(Some(Direction::Right)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<Cell>", into = "Vec<Cell>")]
+pub struct Body {
+    runs: VecDeque<Run>, // head first
+    len: usize,
+}
+
+impl Body {
+    /// a straight body of `len` cells with its head at `head`, heading `dir`
+    pub fn new(head: Cell, dir: Direction, len: u16) -> Self {
+        let mut body = Self::default();
+        if len > 0 {
+            body.runs.push_back(Run {
+                start: head,
+                dir: Some(dir),
+                len,
+            });
+            body.len = usize::from(len);
+        }
+        body
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn front(&self) -> Option<&Cell> {
+        self.runs.front().map(|run| &run.start)
+    }
+
+    pub fn back(&self) -> Option<Cell> {
+        self.runs.back().map(|run| run.cell(run.len - 1))
+    }
+
+    /// every cell from head to tail
+    pub fn iter(&self) -> impl Iterator<Item = Cell> + '_ {
+        self.runs
+            .iter()
+            .flat_map(|run| (0..run.len).map(|i| run.cell(i)))
+    }
+
+    pub fn contains(&self, cell: &Cell) -> bool {
+        self.runs.iter().any(|run| run.offset(cell.pos).is_some())
+    }
+
+    /// whether any cell but the head itself lies on the head
+    pub fn overlaps_head(&self) -> bool {
+        let head = match self.front() {
+            Some(head) => head.pos,
+            None => return false,
+        };
+        // a run without a heading covers its start more than once
+        self.runs.iter().enumerate().any(|(i, run)| {
+            run.offset(head)
+                .is_some_and(|offset| i > 0 || offset > 0 || (run.dir.is_none() && run.len > 1))
+        })
+    }
+
+    /// add a new head, extending the first run if it carries on the same way
+    /// and still has room
+    pub fn push_front(&mut self, cell: Cell) {
+        let dir = self.front().and_then(|head| step(head.pos, cell.pos));
+        match (self.runs.front_mut(), dir) {
+            (Some(run), Some(dir)) if run.len < u16::MAX && (run.dir == dir || run.len == 1) => {
+                run.start = cell;
+                run.dir = dir;
+                run.len += 1;
+            }
+            _ => self.runs.push_front(Run {
+                start: cell,
+                dir: None,
+                len: 1,
+            }),
+        }
+        self.len += 1;
+    }
+
+    /// add a cell behind the tail, extending the last run if it carries on
+    /// the same way and still has room
+    pub fn push_back(&mut self, cell: Cell) {
+        let dir = self.back().and_then(|tail| step(cell.pos, tail.pos));
+        match (self.runs.back_mut(), dir) {
+            (Some(run), Some(dir)) if run.len < u16::MAX && (run.dir == dir || run.len == 1) => {
+                run.dir = dir;
+                run.len += 1;
+            }
+            _ => self.runs.push_back(Run {
+                start: cell,
+                dir: None,
+                len: 1,
+            }),
+        }
+        self.len += 1;
+    }
+
+    /// stack `n` more copies of the tail cell
+    pub fn stack_tail(&mut self, n: usize) {
+        if let Some(tail) = self.back() {
+            for _ in 0..n {
+                self.push_back(tail.clone());
+            }
+        }
+    }
+
+    /// move the head to `cell`, as when it passes through a wall
+    pub fn set_head(&mut self, cell: Cell) {
+        if let Some(run) = self.runs.front_mut() {
+            if run.len == 1 {
+                self.runs.pop_front();
+            } else {
+                run.start = run.cell(1);
+                run.len -= 1;
+            }
+            self.len -= 1;
+            self.push_front(cell);
+        }
+    }
+
+    pub fn pop_back(&mut self) {
+        if let Some(run) = self.runs.back_mut() {
+            run.len -= 1;
+            if run.len == 0 {
+                self.runs.pop_back();
+            }
+            self.len -= 1;
+        }
+    }
+}
+
+impl From<Vec<Cell>> for Body {
+    fn from(cells: Vec<Cell>) -> Self {
+        let mut body = Self::default();
+        for cell in cells {
+            body.push_back(cell);
+        }
+        body
+    }
+}
+
+impl From<Body> for Vec<Cell> {
+    fn from(body: Body) -> Self {
+        body.iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rng;
+    use alloc::vec;
+
+    const SIDE: u16 = 8; // cells run from 1 to SIDE on both axes
+
+    /// check every query of `body` against the plain list of cells
+    fn check(body: &Body, model: &VecDeque<Cell>) {
+        let cells: Vec<Cell> = body.iter().collect();
+        assert_eq!(cells, model.iter().cloned().collect::<Vec<_>>());
+        assert_eq!(body.len(), model.len());
+        assert_eq!(body.front(), model.front());
+        assert_eq!(body.back().as_ref(), model.back());
+        for x in 0..=SIDE + 1 {
+            for y in 0..=SIDE + 1 {
+                let cell = Cell::new(x, y);
+                assert_eq!(body.contains(&cell), model.contains(&cell), "{:?}", cell);
+            }
+        }
+        let overlaps = model
+            .front()
+            .is_some_and(|head| model.iter().skip(1).any(|cell| cell == head));
+        assert_eq!(body.overlaps_head(), overlaps, "{:?}", model);
+    }
+
+    fn body_of(cells: &[(u16, u16)]) -> (Body, VecDeque<Cell>) {
+        let cells: Vec<Cell> = cells.iter().map(|&(x, y)| Cell::new(x, y)).collect();
+        (Body::from(cells.clone()), cells.into())
+    }
+
+    #[test]
+    fn set_head_leaves_stacked_copies_on_the_tail() {
+        let mut body = Body::new(Cell::new(1, 1), Direction::Right, 1);
+        body.stack_tail(2);
+        body.set_head(Cell::new(SIDE, 1));
+        let model = [(SIDE, 1), (1, 1), (1, 1)].map(|(x, y)| Cell::new(x, y));
+        check(&body, &model.into_iter().collect());
+    }
+
+    #[test]
+    fn from_cells_keeps_what_follows_a_duplicate() {
+        let (body, model) = body_of(&[(1, 1), (2, 1), (2, 1), (3, 1), (3, 2), (3, 2)]);
+        check(&body, &model);
+        let (body, model) = body_of(&[(2, 2), (2, 2), (2, 3), (1, 3), (1, 2), (2, 2)]);
+        check(&body, &model);
+    }
+
+    #[test]
+    fn serde_round_trip_lists_every_cell() {
+        let (mut body, mut model) = body_of(&[(3, 3), (3, 4), (3, 5), (4, 5)]);
+        body.stack_tail(2);
+        model.extend([Cell::new(4, 5), Cell::new(4, 5)]);
+        let json = serde_json::to_string(&body).unwrap();
+        assert_eq!(json, serde_json::to_string(&model).unwrap());
+        check(&serde_json::from_str(&json).unwrap(), &model);
+    }
+
+    #[test]
+    fn full_runs_start_new_ones() {
+        let mut body = Body::new(Cell::new(3, 1), Direction::Right, 3);
+        body.stack_tail(usize::from(u16::MAX));
+        body.stack_tail(1);
+        let mut model: VecDeque<Cell> = [(3, 1), (2, 1), (1, 1)]
+            .map(|(x, y)| Cell::new(x, y))
+            .into_iter()
+            .collect();
+        model.extend(core::iter::repeat_n(
+            Cell::new(1, 1),
+            usize::from(u16::MAX) + 1,
+        ));
+        check(&body, &model);
+        for _ in 0..=u16::MAX {
+            body.push_front(Cell::new(3, 1));
+            model.push_front(Cell::new(3, 1));
+        }
+        check(&body, &model);
+    }
+
+    #[test]
+    fn random_moves_match_a_list_of_cells() {
+        let mut rng = Rng::new(7);
+        for _ in 0..200 {
+            let head = Cell::new(rng.gen_range(1, SIDE + 1), rng.gen_range(1, SIDE + 1));
+            let mut body = Body::new(head.clone(), Direction::Right, 1);
+            let mut model = VecDeque::from(vec![head]);
+            for _ in 0..100 {
+                match rng.gen_range(0, 10) {
+                    // step on, wrapping around the edges like the wrap mode
+                    0..=3 => {
+                        let (x, y) = body.front().unwrap().pos;
+                        let next = match rng.gen_range(0, 5) {
+                            0 => (x, y),
+                            1 => (x, if y == 1 { SIDE } else { y - 1 }),
+                            2 => (x, if y == SIDE { 1 } else { y + 1 }),
+                            3 => (if x == 1 { SIDE } else { x - 1 }, y),
+                            _ => (if x == SIDE { 1 } else { x + 1 }, y),
+                        };
+                        let next = Cell::new(next.0, next.1);
+                        if next.pos.0.abs_diff(x) + next.pos.1.abs_diff(y) > 1 {
+                            body.push_front(body.front().unwrap().clone());
+                            body.set_head(next.clone());
+                        } else {
+                            body.push_front(next.clone());
+                        }
+                        model.push_front(next);
+                    }
+                    4..=5 if model.len() > 1 => {
+                        body.pop_back();
+                        model.pop_back();
+                    }
+                    6 => {
+                        let n = usize::from(rng.gen_range(0, 3));
+                        body.stack_tail(n);
+                        let tail = model.back().unwrap().clone();
+                        model.extend(core::iter::repeat_n(tail, n));
+                    }
+                    // jump through a portal
+                    7 => {
+                        let cell =
+                            Cell::new(rng.gen_range(1, SIDE + 1), rng.gen_range(1, SIDE + 1));
+                        body.set_head(cell.clone());
+                        model[0] = cell;
+                    }
+                    _ => {
+                        let json = serde_json::to_string(&body).unwrap();
+                        body = serde_json::from_str(&json).unwrap();
+                    }
+                }
+                check(&body, &model);
+            }
+        }
+    }
+}
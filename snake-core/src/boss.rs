@@ -0,0 +1,154 @@
+//! The boss of a boss level: a large square creature roaming the board.
+//!
+//! It appears through the `spawn_boss` level event, moves one cell every few
+//! ticks and now and then heads for the food to sit on it. Touching it is
+//! deadly. The level is cleared by outlasting it, or at once by trapping it
+//! between walls and the snake's body so it can't move.
+
+use crate::{Cell, Direction, GameState};
+use serde::{Deserialize, Serialize};
+
+const SPAWN_TRIES: usize = 100; // random spots tried when the boss appears
+const SPAWN_DISTANCE: u16 = 4; // minimum distance from the head, on top of its size
+const HUNT_CHANCE: u64 = 4; // one move in this many heads for the food
+
+/// what a level's boss is like
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BossSpec {
+    pub size: u16,    // side of the square, in cells
+    pub every: u64,   // ticks between moves
+    pub survive: u64, // ticks to outlast it for
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Boss {
+    pub pos: (u16, u16), // top left cell
+    pub size: u16,
+    pub dir: Direction,
+    every: u64,
+    /// ticks until the boss is outlasted
+    pub left: u64,
+    pub trapped: bool,
+}
+
+impl Boss {
+    pub fn contains(&self, (x, y): (u16, u16)) -> bool {
+        (self.pos.0..self.pos.0 + self.size).contains(&x)
+            && (self.pos.1..self.pos.1 + self.size).contains(&y)
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        (0..self.size)
+            .flat_map(move |dy| (0..self.size).map(move |dx| (dx, dy)))
+            .map(|(dx, dy)| Cell::new(self.pos.0 + dx, self.pos.1 + dy))
+    }
+
+    pub fn is_defeated(&self) -> bool {
+        self.trapped || self.left == 0
+    }
+}
+
+/// whether a boss of `size` fits with its top left corner at `pos`
+fn fits(state: &GameState, pos: (u16, u16), size: u16) -> bool {
+    let (width, height) = state.size;
+    pos.0 >= 1
+        && pos.1 >= 1
+        && pos.0 + size < width
+        && pos.1 + size < height
+        && (0..size).all(|dy| {
+            (0..size).all(|dx| {
+                let cell = Cell::new(pos.0 + dx, pos.1 + dy);
+                !state.wall.cells.contains(&cell)
+                    && !state.breakables.contains(&cell)
+                    && !state.is_closed_gate(&cell)
+                    && !state.snake.body.contains(&cell)
+                    && !state.hazards.contains(&cell)
+            })
+        })
+}
+
+/// put the boss of `spec` on a free spot away from the head, if there is one
+pub fn spawn(state: &mut GameState, spec: BossSpec) {
+    let (width, height) = state.size;
+    if spec.size == 0 || spec.size + 2 > width || spec.size + 2 > height {
+        return;
+    }
+    let (hx, hy) = state.snake.head().pos;
+    for _ in 0..SPAWN_TRIES {
+        let pos = (
+            state.rng.gen_range(1, width - spec.size),
+            state.rng.gen_range(1, height - spec.size),
+        );
+        let (cx, cy) = (pos.0 + spec.size / 2, pos.1 + spec.size / 2);
+        let far = cx.abs_diff(hx) + cy.abs_diff(hy) >= spec.size + SPAWN_DISTANCE;
+        if far && fits(state, pos, spec.size) {
+            let dir = state.snake.dir.opposite();
+            state.boss = Some(Boss {
+                pos,
+                size: spec.size,
+                dir,
+                every: spec.every.max(1),
+                left: spec.survive,
+                trapped: false,
+            });
+            return;
+        }
+    }
+}
+
+/// the way from the boss's middle towards the food, along the longer axis
+fn toward_food(boss: &Boss, food: (u16, u16)) -> Direction {
+    let (cx, cy) = (boss.pos.0 + boss.size / 2, boss.pos.1 + boss.size / 2);
+    if cx.abs_diff(food.0) >= cy.abs_diff(food.1) {
+        if food.0 < cx {
+            Direction::Left
+        } else {
+            Direction::Right
+        }
+    } else if food.1 < cy {
+        Direction::Up
+    } else {
+        Direction::Down
+    }
+}
+
+/// count down the boss and move it when it is due
+pub fn update(state: &mut GameState) {
+    let mut boss = match state.boss.take() {
+        Some(boss) => boss,
+        None => return,
+    };
+    boss.left = boss.left.saturating_sub(1);
+    if state.tick.is_multiple_of(boss.every) && !boss.is_defeated() {
+        let hunt = state.rng.next_u64().is_multiple_of(HUNT_CHANCE);
+        let first = match state.food.first() {
+            Some(food) if hunt => toward_food(&boss, food.pos),
+            _ => boss.dir,
+        };
+        let turn = if state.rng.next_u64().is_multiple_of(2) {
+            first.turn_left()
+        } else {
+            first.turn_right()
+        };
+        let moves = [first, turn, turn.opposite(), first.opposite()];
+        let next = moves.into_iter().find_map(|dir| {
+            let (x, y) = boss.pos;
+            let pos = match dir {
+                Direction::Up => (x, y.checked_sub(1)?),
+                Direction::Down => (x, y + 1),
+                Direction::Left => (x.checked_sub(1)?, y),
+                Direction::Right => (x + 1, y),
+            };
+            let clear = fits(state, pos, boss.size);
+            clear.then_some((pos, dir))
+        });
+        match next {
+            Some((pos, dir)) => {
+                boss.pos = pos;
+                boss.dir = dir;
+            }
+            None => boss.trapped = true,
+        }
+    }
+    state.boss = Some(boss);
+}
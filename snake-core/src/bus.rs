@@ -0,0 +1,37 @@
+//! Notifications of what happened in the game.
+//!
+//! The rules push a [`Notice`] onto [`GameState::bus`](crate::GameState)
+//! whenever something noteworthy happens; frontends drain the bus after each
+//! tick to keep stats, play sounds and so on. The bus is not saved.
+
+use crate::Direction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crash {
+    Wall,
+    Body,
+    Hazard,
+    Boss,
+    Poison,
+    /// the other snake's body, see `rival`
+    Rival,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notice {
+    /// the snake ate a food of the given kind: "food", "bonus" or one added by the frontend
+    Ate(&'static str),
+    /// the snake changed heading
+    Turned(Direction),
+    /// the snake moved one cell
+    Moved,
+    /// the snake picked up a power-up of the given kind
+    PowerUp(&'static str),
+    /// a ramming snake broke a wall cell
+    Smashed,
+    /// the snake ate a poison pellet and lost length and a point
+    Poisoned,
+    /// a battle snake crashed into the snake's body, for points
+    Eliminated,
+    Crashed(Crash),
+}
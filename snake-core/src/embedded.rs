@@ -0,0 +1,73 @@
+//! Renderer for `embedded-graphics` draw targets.
+//!
+//! Each board cell becomes a square block of pixels, so a 32x32 board fits a
+//! 64x64 LED matrix with `cell_px = 2` or a 128x128 OLED with `cell_px = 4`.
+//! Input comes from any [`InputSource`](crate::InputSource), e.g. buttons
+//! wired to GPIO pins.
+
+use crate::{Cell, GameState};
+use embedded_graphics::pixelcolor::{BinaryColor, PixelColor};
+use embedded_graphics::prelude::{DrawTarget, Point, Size};
+use embedded_graphics::primitives::Rectangle;
+
+/// colors used for each kind of board entity
+#[derive(Debug, Clone, Copy)]
+pub struct Palette<C> {
+    pub snake: C,
+    pub food: C,
+    pub wall: C,
+    pub background: C,
+}
+
+impl Palette<BinaryColor> {
+    /// everything lit on a dark background, for single-color displays
+    pub fn monochrome() -> Self {
+        Self {
+            snake: BinaryColor::On,
+            food: BinaryColor::On,
+            wall: BinaryColor::On,
+            background: BinaryColor::Off,
+        }
+    }
+}
+
+pub struct EgRenderer<C> {
+    cell_px: u32,
+    palette: Palette<C>,
+}
+
+impl<C: PixelColor> EgRenderer<C> {
+    pub fn new(cell_px: u32, palette: Palette<C>) -> Self {
+        Self { cell_px, palette }
+    }
+
+    fn draw_cell<D>(&self, target: &mut D, cell: &Cell, color: C) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let origin = Point::new(
+            i32::from(cell.pos.0) * self.cell_px as i32,
+            i32::from(cell.pos.1) * self.cell_px as i32,
+        );
+        let area = Rectangle::new(origin, Size::new(self.cell_px, self.cell_px));
+        target.fill_solid(&area, color)
+    }
+
+    /// draw a full frame of `state` onto `target`
+    pub fn draw<D>(&self, state: &GameState, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        target.clear(self.palette.background)?;
+        for cell in state.wall.cells.iter().chain(&state.breakables) {
+            self.draw_cell(target, cell, self.palette.wall)?;
+        }
+        for cell in state.snake.body.iter() {
+            self.draw_cell(target, &cell, self.palette.snake)?;
+        }
+        for cell in &state.food {
+            self.draw_cell(target, cell, self.palette.food)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+//! Scheduled level events.
+//!
+//! Levels and modes attach a list of [`Event`]s to the game; each tick the
+//! ones whose trigger matches fire their action on the game state. Timing is
+//! in ticks so runs stay reproducible from their seed.
+
+use crate::boss::{self, BossSpec};
+use crate::{Cell, GameState};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Trigger {
+    /// once, at the given tick
+    At(u64),
+    /// at every multiple of the given number of ticks
+    Every(u64),
+    /// each tick with the given probability, in parts per million
+    Chance(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// remove a short run of interior wall; the outer wall always stays closed
+    OpenGap,
+    /// drop this many extra food pellets on free cells
+    RainFood(u16),
+    /// place a deadly hazard on a free cell away from the snake's head
+    SpawnHazard,
+    /// drop a ram power-up on a free cell, see `GameState::breakables`
+    SpawnRam,
+    /// bring in the level's boss, see `boss`; levels declare it in a section of its own
+    #[serde(skip_deserializing)]
+    SpawnBoss(BossSpec),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    pub trigger: Trigger,
+    pub action: Action,
+}
+
+const GAP_LEN: usize = 3; // wall cells removed by `OpenGap`
+const HAZARD_DISTANCE: u16 = 3; // minimum distance of a new hazard from the head
+const HAZARD_TRIES: usize = 50; // free cells tried for a hazard before giving up
+
+impl Trigger {
+    fn fires(&self, tick: u64, state: &mut GameState) -> bool {
+        match *self {
+            Trigger::At(at) => tick == at,
+            Trigger::Every(every) => every > 0 && tick > 0 && tick.is_multiple_of(every),
+            Trigger::Chance(ppm) => state.rng.next_u64() % 1_000_000 < u64::from(ppm),
+        }
+    }
+}
+
+impl Action {
+    fn apply(&self, state: &mut GameState) {
+        match *self {
+            Action::OpenGap => open_gap(state),
+            Action::RainFood(count) => {
+                for _ in 0..count {
+                    if let Some(cell) = state.random_free_cell() {
+                        state.extra_food.push(cell);
+                    }
+                }
+            }
+            Action::SpawnHazard => {
+                // no hazard if no free cell far enough from the head turns up
+                for _ in 0..HAZARD_TRIES {
+                    let cell = match state.random_free_cell() {
+                        Some(cell) => cell,
+                        None => break,
+                    };
+                    let (hx, hy) = state.snake.head().pos;
+                    if cell.pos.0.abs_diff(hx) + cell.pos.1.abs_diff(hy) >= HAZARD_DISTANCE {
+                        state.hazards.push(cell);
+                        break;
+                    }
+                }
+            }
+            Action::SpawnRam => {
+                if let Some(cell) = state.random_free_cell() {
+                    state.rams.push(cell);
+                }
+            }
+            Action::SpawnBoss(spec) => boss::spawn(state, spec),
+        }
+    }
+}
+
+/// remove a random interior wall cell and up to `GAP_LEN - 1` of its neighbours in a row
+fn open_gap(state: &mut GameState) {
+    let (width, height) = state.size;
+    let interior =
+        |c: &Cell| c.pos.0 > 0 && c.pos.1 > 0 && c.pos.0 < width - 1 && c.pos.1 < height - 1;
+    let count = state.wall.cells.iter().filter(|c| interior(c)).count();
+    if count == 0 {
+        return;
+    }
+    let pick = (state.rng.next_u64() % count as u64) as usize;
+    let start = state
+        .wall
+        .cells
+        .iter()
+        .filter(|c| interior(c))
+        .nth(pick)
+        .unwrap()
+        .clone();
+    // extend along whichever axis the wall runs
+    let horizontal = state
+        .wall
+        .cells
+        .iter()
+        .any(|c| c.pos == (start.pos.0 + 1, start.pos.1));
+    for i in 0..GAP_LEN as u16 {
+        let pos = if horizontal {
+            (start.pos.0 + i, start.pos.1)
+        } else {
+            (start.pos.0, start.pos.1 + i)
+        };
+        let cell = Cell::new(pos.0, pos.1);
+        if !interior(&cell) {
+            break;
+        }
+        state.wall.cells.retain(|c| *c != cell);
+    }
+}
+
+/// fire every event due at the current tick
+pub fn fire(state: &mut GameState) {
+    let events = state.events.clone();
+    for event in &events {
+        if event.trigger.fires(state.tick, state) {
+            event.action.apply(state);
+        }
+    }
+}
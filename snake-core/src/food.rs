@@ -0,0 +1,65 @@
+//! Food behaviors: what food does while it waits to be eaten.
+//!
+//! [`GameState::step`](crate::GameState::step) runs the behavior named in
+//! the state at the end of every tick, after the mode. Like modes, behaviors
+//! are stateless. New ones are added by implementing [`FoodBehavior`] and
+//! listing them in [`BEHAVIORS`].
+
+use crate::GameState;
+
+const WANDER_TICKS: u64 = 5; // ticks between two steps of wandering food
+
+pub trait FoodBehavior: Sync {
+    /// unique name, stored in saves
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+
+    /// called at the end of every tick
+    fn on_tick(&self, _state: &mut GameState) {}
+}
+
+/// food stays where it appeared
+pub struct Still;
+
+impl FoodBehavior for Still {
+    fn name(&self) -> &'static str {
+        "still"
+    }
+
+    fn description(&self) -> &'static str {
+        "food stays where it appears"
+    }
+}
+
+/// every few ticks each food steps to a free neighbouring cell, away from the snake when it can
+pub struct Wander;
+
+impl FoodBehavior for Wander {
+    fn name(&self) -> &'static str {
+        "wander"
+    }
+
+    fn description(&self) -> &'static str {
+        "food takes a step every few ticks, away from the snake when it can"
+    }
+
+    fn on_tick(&self, state: &mut GameState) {
+        if state.tick.is_multiple_of(WANDER_TICKS) {
+            for i in 0..state.food.len() {
+                state.nudge_food(i);
+            }
+        }
+    }
+}
+
+/// every behavior, the first one is the default
+pub static BEHAVIORS: &[&dyn FoodBehavior] = &[&Still, &Wander];
+
+/// the behavior called `name`, still food for unknown names
+pub fn find(name: &str) -> &'static dyn FoodBehavior {
+    BEHAVIORS
+        .iter()
+        .copied()
+        .find(|behavior| behavior.name() == name)
+        .unwrap_or(BEHAVIORS[0])
+}
@@ -0,0 +1,48 @@
+//! Gates: wall segments that open and close on a fixed timer.
+//!
+//! Each gate is closed for `closed` ticks, then open for `open` ticks, over
+//! and over, starting `offset` ticks into its cycle. A closed gate is a wall
+//! to the head; one closing on the body doesn't hurt it.
+
+use crate::Cell;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Gate {
+    pub id: char, // the map character of its cells
+    pub cells: Vec<Cell>,
+    pub open: u64,
+    pub closed: u64,
+    pub offset: u64,
+}
+
+impl Gate {
+    pub fn new(id: char) -> Self {
+        Self {
+            id,
+            cells: Vec::new(),
+            open: 0,
+            closed: 0,
+            offset: 0,
+        }
+    }
+
+    fn phase(&self, tick: u64) -> u64 {
+        (tick + self.offset) % (self.open + self.closed).max(1)
+    }
+
+    pub fn is_closed(&self, tick: u64) -> bool {
+        self.phase(tick) < self.closed
+    }
+
+    /// ticks until the gate next opens or closes
+    pub fn ticks_to_change(&self, tick: u64) -> u64 {
+        let phase = self.phase(tick);
+        if phase < self.closed {
+            self.closed - phase
+        } else {
+            self.open + self.closed - phase
+        }
+    }
+}
@@ -0,0 +1,441 @@
+//! Board layouts parsed from ASCII maps.
+//!
+//! One character per cell: `#` is a wall, `.` or a space is empty, and
+//! `^`, `v`, `<`, `>` mark a spawn point with the snake heading that way.
+//! `+` is a doorway: an empty cell that splits the open space around it
+//! into separate rooms, which frontends show one at a time. The digits `1`
+//! to `9` are cells of the gate with that number, see `gates`. `%` is a
+//! breakable wall that a snake with a ram power-up smashes through. `S` is
+//! a spawn point heading the way with the most room, and `F` an empty cell
+//! of the food zone: when a map has one, food only appears there. The
+//! lowercase letters `a` to `u` are portals, each letter used exactly twice
+//! for the two linked ends, see `portals`. Rows
+//! shorter than the widest one are padded with empty cells, and the
+//! outermost cells must all be walls so the snake can't leave the board,
+//! and a map must be at least `MIN_BOARD` cells each way.
+
+use crate::events::Event;
+use crate::gates::Gate;
+use crate::portals::Portal;
+use crate::{Cell, Direction, Rng, Spawn, Wall};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// smallest board the snake and the first food fit on
+pub const MIN_BOARD: u16 = 8;
+
+/// how spawn points are handed out when a game starts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpawnOrder {
+    /// every snake starts at a random spawn point
+    #[default]
+    Random,
+    /// player `n` starts at the `n`-th spawn point of the map
+    Assigned,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpawnPoint {
+    pub pos: (u16, u16),
+    pub dir: Direction,
+}
+
+/// a part of the board between doorways, with the walls around it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Room {
+    pub pos: (u16, u16),  // top left cell
+    pub size: (u16, u16), // (width, height) in cells
+}
+
+impl Room {
+    pub fn contains(&self, (x, y): (u16, u16)) -> bool {
+        (self.pos.0..self.pos.0 + self.size.0).contains(&x)
+            && (self.pos.1..self.pos.1 + self.size.1).contains(&y)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Level {
+    pub size: (u16, u16), // (width, height) in cells
+    pub wall: Wall,
+    pub spawns: Vec<SpawnPoint>,
+    pub spawn_order: SpawnOrder,
+    pub events: Vec<Event>,
+    pub rooms: Vec<Room>, // empty unless the map has doorways
+    pub gates: Vec<Gate>, // in order of first appearance, timed by the level file
+    pub breakables: Vec<Cell>,
+    pub food_zone: Vec<Cell>, // empty when food may appear anywhere
+    pub portals: Vec<Portal>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapError {
+    Empty,
+    TooLarge,
+    TooSmall,
+    OpenBorder,
+    UnknownTile { line: usize, col: usize, tile: char },
+    UnpairedPortal(char),
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapError::Empty => write!(f, "the map is empty"),
+            MapError::TooLarge => write!(f, "the map is too large"),
+            MapError::TooSmall => write!(
+                f,
+                "the map must be at least {} by {} cells",
+                MIN_BOARD, MIN_BOARD
+            ),
+            MapError::OpenBorder => write!(f, "the map must be surrounded by walls"),
+            MapError::UnpairedPortal(tile) => {
+                write!(f, "portal '{}' must appear exactly twice", tile)
+            }
+            MapError::UnknownTile { line, col, tile } => {
+                write!(
+                    f,
+                    "unknown tile '{}' at line {}, column {}",
+                    tile, line, col
+                )
+            }
+        }
+    }
+}
+
+impl Level {
+    /// empty board surrounded by a wall, without spawn points
+    pub fn bordered((width, height): (u16, u16)) -> Self {
+        Self {
+            size: (width, height),
+            wall: Wall::new(width, height),
+            spawns: Vec::new(),
+            spawn_order: SpawnOrder::Random,
+            events: Vec::new(),
+            rooms: Vec::new(),
+            gates: Vec::new(),
+            breakables: Vec::new(),
+            food_zone: Vec::new(),
+            portals: Vec::new(),
+        }
+    }
+
+    pub fn parse(map: &str) -> Result<Self, MapError> {
+        let rows: Vec<&str> = map.lines().filter(|row| !row.trim().is_empty()).collect();
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        if width == 0 {
+            return Err(MapError::Empty);
+        }
+        if width > usize::from(u16::MAX) || rows.len() > usize::from(u16::MAX) {
+            return Err(MapError::TooLarge);
+        }
+        if width < usize::from(MIN_BOARD) || rows.len() < usize::from(MIN_BOARD) {
+            return Err(MapError::TooSmall);
+        }
+        let mut level = Self {
+            size: (width as u16, rows.len() as u16),
+            wall: Wall { cells: Vec::new() },
+            spawns: Vec::new(),
+            spawn_order: SpawnOrder::Random,
+            events: Vec::new(),
+            rooms: Vec::new(),
+            gates: Vec::new(),
+            breakables: Vec::new(),
+            food_zone: Vec::new(),
+            portals: Vec::new(),
+        };
+        let mut doors = Vec::new();
+        let mut starts = Vec::new();
+        let mut ends: Vec<(char, Cell)> = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, tile) in row.chars().enumerate() {
+                let pos = (x as u16, y as u16);
+                let dir = match tile {
+                    '#' => {
+                        level.wall.cells.push(Cell::new(pos.0, pos.1));
+                        continue;
+                    }
+                    '%' => {
+                        level.breakables.push(Cell::new(pos.0, pos.1));
+                        continue;
+                    }
+                    '.' | ' ' => continue,
+                    'F' => {
+                        level.food_zone.push(Cell::new(pos.0, pos.1));
+                        continue;
+                    }
+                    'S' => {
+                        starts.push(pos);
+                        continue;
+                    }
+                    '+' => {
+                        doors.push(pos);
+                        continue;
+                    }
+                    '1'..='9' => {
+                        let index = match level.gates.iter().position(|g| g.id == tile) {
+                            Some(index) => index,
+                            None => {
+                                level.gates.push(Gate::new(tile));
+                                level.gates.len() - 1
+                            }
+                        };
+                        level.gates[index].cells.push(Cell::new(pos.0, pos.1));
+                        continue;
+                    }
+                    'a'..='u' => {
+                        ends.push((tile, Cell::new(pos.0, pos.1)));
+                        continue;
+                    }
+                    '^' => Direction::Up,
+                    'v' => Direction::Down,
+                    '<' => Direction::Left,
+                    '>' => Direction::Right,
+                    _ => {
+                        return Err(MapError::UnknownTile {
+                            line: y + 1,
+                            col: x + 1,
+                            tile,
+                        })
+                    }
+                };
+                level.spawns.push(SpawnPoint { pos, dir });
+            }
+        }
+        let (w, h) = level.size;
+        let closed = (0..w).all(|x| level.is_wall(x, 0) && level.is_wall(x, h - 1))
+            && (0..h).all(|y| level.is_wall(0, y) && level.is_wall(w - 1, y));
+        if !closed {
+            return Err(MapError::OpenBorder);
+        }
+        for (id, _) in &ends {
+            if level.portals.iter().any(|portal| portal.id == *id) {
+                continue;
+            }
+            let pair: Vec<_> = ends.iter().filter(|(other, _)| other == id).collect();
+            match pair[..] {
+                [(_, a), (_, b)] => level.portals.push(Portal {
+                    id: *id,
+                    ends: [a.clone(), b.clone()],
+                }),
+                _ => return Err(MapError::UnpairedPortal(*id)),
+            }
+        }
+        for pos in starts {
+            let dir = level.roomiest_heading(pos);
+            level.spawns.push(SpawnPoint { pos, dir });
+        }
+        if !doors.is_empty() {
+            level.rooms = level.find_rooms(&doors);
+        }
+        Ok(level)
+    }
+
+    /// the heading from `pos` with the most open cells straight ahead,
+    /// preferring those with room on both sides for a default-length snake
+    fn roomiest_heading(&self, (x, y): (u16, u16)) -> Direction {
+        let open = |dir: Direction| {
+            (1..)
+                .map(|i| Cell::new(x, y).clone_with_pos_shift(dir, i).pos)
+                .take_while(|&(x, y)| !self.is_wall(x, y))
+                .count()
+        };
+        [
+            Direction::Right,
+            Direction::Left,
+            Direction::Down,
+            Direction::Up,
+        ]
+        .into_iter()
+        // the last of equals wins, so ties go to the first listed
+        .rev()
+        .max_by_key(|&dir| (open(dir) >= 2 && open(dir.opposite()) >= 2, open(dir)))
+        .unwrap_or(Direction::Right)
+    }
+
+    /// the open areas separated by walls and `doors`
+    fn find_rooms(&self, doors: &[(u16, u16)]) -> Vec<Room> {
+        let (w, h) = (usize::from(self.size.0), usize::from(self.size.1));
+        let mut seen = vec![false; w * h];
+        for cell in &self.wall.cells {
+            seen[usize::from(cell.pos.1) * w + usize::from(cell.pos.0)] = true;
+        }
+        for &(x, y) in doors {
+            seen[usize::from(y) * w + usize::from(x)] = true;
+        }
+        let mut rooms = Vec::new();
+        for start in 0..w * h {
+            if seen[start] {
+                continue;
+            }
+            // flood fill the room, tracking its bounds
+            seen[start] = true;
+            let mut stack = vec![start];
+            let (mut min, mut max) = ((w, h), (0, 0));
+            while let Some(i) = stack.pop() {
+                let (x, y) = (i % w, i / w);
+                min = (min.0.min(x), min.1.min(y));
+                max = (max.0.max(x), max.1.max(y));
+                // the border is all walls, so neighbours stay on the board
+                for next in [i - 1, i + 1, i - w, i + w] {
+                    if !seen[next] {
+                        seen[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+            // include the surrounding walls
+            rooms.push(Room {
+                pos: (min.0 as u16 - 1, min.1 as u16 - 1),
+                size: ((max.0 - min.0 + 3) as u16, (max.1 - min.1 + 3) as u16),
+            });
+        }
+        rooms
+    }
+
+    fn is_wall(&self, x: u16, y: u16) -> bool {
+        self.wall.cells.contains(&Cell::new(x, y))
+    }
+
+    /// check that a snake started by `spawn` lies on the board and off the walls
+    pub fn fits(&self, spawn: &Spawn) -> bool {
+        let head = Cell::new(spawn.pos.0, spawn.pos.1);
+        let (x, y) = (i32::from(spawn.pos.0), i32::from(spawn.pos.1));
+        let back = i32::from(spawn.len) - 1;
+        let (tx, ty) = match spawn.dir {
+            Direction::Up => (x, y + back),
+            Direction::Down => (x, y - back),
+            Direction::Left => (x + back, y),
+            Direction::Right => (x - back, y),
+        };
+        let on_board = |x: i32, y: i32| {
+            x >= 0 && y >= 0 && x < i32::from(self.size.0) && y < i32::from(self.size.1)
+        };
+        spawn.len > 0
+            && on_board(x, y)
+            && on_board(tx, ty)
+            && (0..spawn.len).all(|i| {
+                let cell = head.clone_with_pos_shift(spawn.dir.opposite(), i);
+                !self.wall.cells.contains(&cell) && !self.breakables.contains(&cell)
+            })
+    }
+
+    /// spawn for `player` with a body of `len` cells, `None` if the map has no spawn points
+    pub fn pick_spawn(&self, player: usize, len: u16, rng: &mut Rng) -> Option<Spawn> {
+        if self.spawns.is_empty() {
+            return None;
+        }
+        let index = match self.spawn_order {
+            SpawnOrder::Random => (rng.next_u64() % self.spawns.len() as u64) as usize,
+            SpawnOrder::Assigned => player % self.spawns.len(),
+        };
+        let point = self.spawns[index];
+        Some(Spawn {
+            pos: point.pos,
+            dir: point.dir,
+            len,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    const OPEN: &str = "#......#"; // a row of the inside of an 8 wide map
+
+    /// an 8 wide map with `inside` between a top and a bottom wall
+    fn map(inside: &[&str]) -> String {
+        let mut rows = vec!["########"];
+        rows.extend(inside);
+        rows.push("########");
+        rows.join("\n")
+    }
+
+    #[test]
+    fn parses_walls_and_spawn_points() {
+        let level = Level::parse(&map(&[OPEN, "#.>....#", OPEN, "#..##.S#", OPEN, OPEN])).unwrap();
+        assert_eq!(level.size, (8, 8));
+        assert_eq!(level.wall.cells.len(), 7 * 4 + 2);
+        assert!(level.is_wall(3, 4) && level.is_wall(4, 4));
+        let spawns = [
+            SpawnPoint {
+                pos: (2, 2),
+                dir: Direction::Right,
+            },
+            // the head of an `S` goes where there is the most room
+            SpawnPoint {
+                pos: (6, 4),
+                dir: Direction::Up,
+            },
+        ];
+        assert_eq!(level.spawns, spawns);
+    }
+
+    #[test]
+    fn blank_lines_and_spaces_are_skipped_or_empty() {
+        let text = map(&[OPEN, "#      #", "", OPEN, OPEN, OPEN, OPEN]);
+        let level = Level::parse(&text).unwrap();
+        assert_eq!(level.size, (8, 8));
+        assert!(!level.is_wall(1, 2));
+    }
+
+    #[test]
+    fn short_rows_are_padded_so_the_border_must_still_close() {
+        let level = Level::parse(&map(&[OPEN, OPEN, OPEN, OPEN, OPEN, "#########"]));
+        assert_eq!(level.unwrap_err(), MapError::OpenBorder);
+        let level = Level::parse(&map(&[OPEN, OPEN, "#.....", OPEN, OPEN, OPEN]));
+        assert_eq!(level.unwrap_err(), MapError::OpenBorder);
+    }
+
+    #[test]
+    fn a_map_without_spawn_points_leaves_the_start_to_the_config() {
+        let level = Level::parse(&map(&[OPEN; 6])).unwrap();
+        assert!(level.spawns.is_empty());
+        assert!(level.pick_spawn(0, 3, &mut Rng::new(1)).is_none());
+    }
+
+    #[test]
+    fn maps_smaller_than_the_smallest_board_are_rejected() {
+        assert_eq!(Level::parse("").unwrap_err(), MapError::Empty);
+        assert_eq!(Level::parse("\n  \n").unwrap_err(), MapError::Empty);
+        assert_eq!(Level::parse("#").unwrap_err(), MapError::TooSmall);
+        assert_eq!(
+            Level::parse("###\n#>#\n###").unwrap_err(),
+            MapError::TooSmall
+        );
+        // one row short
+        assert_eq!(
+            Level::parse(&map(&[OPEN; 5])).unwrap_err(),
+            MapError::TooSmall
+        );
+        // one column short
+        let mut narrow = ["#######"; 8];
+        narrow[1..7].fill("#.....#");
+        assert_eq!(
+            Level::parse(&narrow.join("\n")).unwrap_err(),
+            MapError::TooSmall
+        );
+    }
+
+    #[test]
+    fn unknown_tiles_and_lone_portals_are_rejected() {
+        let level = Level::parse(&map(&[OPEN, "#..?...#", OPEN, OPEN, OPEN, OPEN]));
+        let unknown = MapError::UnknownTile {
+            line: 3,
+            col: 4,
+            tile: '?',
+        };
+        assert_eq!(level.unwrap_err(), unknown);
+        let level = Level::parse(&map(&[OPEN, "#.a....#", OPEN, OPEN, OPEN, OPEN]));
+        assert_eq!(level.unwrap_err(), MapError::UnpairedPortal('a'));
+    }
+}
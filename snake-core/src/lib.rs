@@ -0,0 +1,980 @@
+//! Game rules of rust-snake: snake movement, collision, scoring and RNG.
+//!
+//! The crate is `no_std` and only needs `alloc`, so the same engine can be
+//! driven by the terminal frontend, embedded targets or wasm. Positions are
+//! board cells; mapping them to pixels or characters is up to the renderer.
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod body;
+pub mod boss;
+pub mod bus;
+#[cfg(feature = "embedded-graphics")]
+pub mod embedded;
+pub mod events;
+pub mod food;
+pub mod gates;
+pub mod level;
+pub mod maze;
+pub mod modes;
+pub mod portals;
+pub mod powerup;
+pub mod rival;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use body::Body;
+use boss::Boss;
+use bus::{Crash, Notice};
+use events::Event;
+use food::FoodBehavior;
+use gates::Gate;
+use level::{Level, Room};
+use modes::{GameMode, Hit};
+use portals::Portal;
+use powerup::{Kind, PowerUps};
+use rival::Rival;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Cell {
+    pub pos: (u16, u16), // (horizontal coord, vertical coord)
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    /// the heading after a 90° turn counterclockwise
+    pub fn turn_left(self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    /// the heading after a 90° turn clockwise
+    pub fn turn_right(self) -> Self {
+        self.turn_left().opposite()
+    }
+}
+
+impl Cell {
+    pub fn new(x: u16, y: u16) -> Self {
+        Self { pos: (x, y) }
+    }
+
+    pub fn clone_with_pos_shift(&self, dir: Direction, steps: u16) -> Self {
+        let (mut x, mut y) = self.pos;
+        match dir {
+            Direction::Up => y -= steps,
+            Direction::Down => y += steps,
+            Direction::Left => x -= steps,
+            Direction::Right => x += steps,
+        }
+        Self::new(x, y)
+    }
+}
+
+/// source of player input for frontends driving the engine, such as
+/// keyboards, GPIO buttons or scripted moves
+pub trait InputSource {
+    /// the turn requested since the last tick, if any
+    fn poll(&mut self) -> Option<Direction>;
+}
+
+const RNG_MIX: u64 = 0x9E37_79B9_7F4A_7C15; // spreads small seeds over the state
+
+/// small deterministic PRNG (xorshift64*), so runs can be reproduced from a seed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift must not start from zero, which the one seed equal to the
+        // mixing constant would give
+        let state = seed ^ RNG_MIX;
+        Self {
+            state: if state == 0 { RNG_MIX } else { state },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// uniform value in `lo..hi`
+    pub fn gen_range(&mut self, lo: u16, hi: u16) -> u16 {
+        lo + (self.next_u64() % u64::from(hi - lo)) as u16
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snake {
+    pub body: Body,
+    pub dir: Direction,
+}
+
+impl Snake {
+    pub fn new((x, y): (u16, u16), dir: Direction, len: u16) -> Self {
+        let body = Body::new(Cell::new(x, y), dir, len);
+        Self { body, dir }
+    }
+
+    pub fn head(&self) -> &Cell {
+        self.body.front().unwrap()
+    }
+
+    /// grow snake body when eating food
+    pub fn grow_body(&mut self) {
+        self.body
+            .push_front(self.head().clone_with_pos_shift(self.dir, 1));
+    }
+
+    pub fn move_body(&mut self) {
+        self.body
+            .push_front(self.head().clone_with_pos_shift(self.dir, 1));
+        self.body.pop_back();
+    }
+
+    pub fn check_bite_body(&self) -> bool {
+        self.body.overlaps_head()
+    }
+
+    pub fn check_bite_food(&self, food: &Cell) -> bool {
+        self.head() == food
+    }
+
+    /// check if the snake body overlaps with food when generating food
+    pub fn check_overlap_food(&self, food: &Cell) -> bool {
+        self.body.contains(food)
+    }
+
+    pub fn check_collide_wall(&self, wall: &Wall) -> bool {
+        wall.cells.iter().any(|c| c == self.head())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Wall {
+    pub cells: Vec<Cell>,
+}
+
+impl Wall {
+    /// wall around the border of a `width` x `height` board
+    pub fn new(width: u16, height: u16) -> Self {
+        let top_wall = (0..width).map(|x| (x, 0));
+        let btm_wall = (0..width).map(|x| (x, height - 1));
+        let lft_wall = (1..height - 1).map(|y| (0, y));
+        let rht_wall = (1..height - 1).map(|y| (width - 1, y));
+        Self {
+            cells: top_wall
+                .chain(lft_wall)
+                .chain(rht_wall)
+                .chain(btm_wall)
+                .map(|(x, y)| Cell::new(x, y))
+                .collect(),
+        }
+    }
+}
+
+/// whether the head may move into the cell the tail is leaving in the same tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TailRule {
+    /// allowed, as in most modern snake games
+    #[default]
+    Lenient,
+    /// a bite, as in the classic arcade game
+    Strict,
+}
+
+/// where and how the snake starts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Spawn {
+    pub pos: (u16, u16), // head position
+    pub dir: Direction,
+    pub len: u16,
+}
+
+impl Spawn {
+    /// the classic start: length 3 in the middle of the board, heading right
+    pub fn centered((width, height): (u16, u16)) -> Self {
+        Self {
+            pos: (width / 2, (height / 2).saturating_sub(1)),
+            dir: Direction::Right,
+            len: 3,
+        }
+    }
+
+    /// the same start turned half way round a `width` x `height` board
+    pub fn mirrored(&self, (width, height): (u16, u16)) -> Self {
+        Self {
+            pos: (width - 1 - self.pos.0, height - 1 - self.pos.1),
+            dir: self.dir.opposite(),
+            len: self.len,
+        }
+    }
+
+    /// check that the whole starting body lies on `level`, clear of its walls
+    pub fn validate(&self, level: &Level) -> Result<(), &'static str> {
+        if self.len == 0 {
+            Err("the starting length must be at least 1")
+        } else if !level.fits(self) {
+            Err("the starting snake doesn't fit inside the walls")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+const BUS_LEN: usize = 64; // notices kept for a frontend that doesn't drain the bus
+const RAM_TICKS: u64 = 60; // ticks a ram power-up lasts
+const POISON_CUT: usize = 2; // cells a poison pellet takes off the tail
+const ROCK_TRIES: usize = 20; // free cells tried per rock before giving up
+const FREE_CELL_TRIES: usize = 32; // random cells tried before listing the free ones
+const ROCK_CLEARANCE: u16 = 4; // cells ahead of the starting head kept clear
+const ELIMINATION_POINTS: u16 = 5; // for each battle snake that crashes into the snake
+
+/// complete state of a game, advanced one tick at a time by `step`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameState {
+    pub size: (u16, u16), // board (width, height) in cells, walls included
+    pub wall: Wall,
+    pub snake: Snake,
+    #[serde(deserialize_with = "one_or_many")]
+    pub food: Vec<Cell>, // pellets that move elsewhere when eaten
+    pub score: u16,
+    pub is_over: bool,
+    #[serde(default)]
+    pub seed: u64, // seed the run started from
+    pub rng: Rng,
+    #[serde(default)]
+    pub tick: u64, // ticks played so far
+    #[serde(default)]
+    pub events: Vec<Event>,
+    #[serde(default)]
+    pub extra_food: Vec<Cell>, // pellets dropped by events, not respawned when eaten
+    #[serde(default)]
+    pub hazards: Vec<Cell>, // deadly cells placed by events
+    #[serde(skip)]
+    pub bus: Vec<Notice>, // notices not yet taken by the frontend
+    #[serde(default)]
+    pub mode: String, // name of the game mode, see `modes`
+    #[serde(default)]
+    pub won: bool,
+    #[serde(default)]
+    pub tail_rule: TailRule,
+    #[serde(default)]
+    pub rooms: Vec<Room>, // see `level::Room`
+    #[serde(default)]
+    pub boss: Option<Boss>,
+    #[serde(default)]
+    pub gates: Vec<Gate>,
+    #[serde(default)]
+    pub breakables: Vec<Cell>, // wall cells a ramming snake smashes through
+    #[serde(default)]
+    pub rams: Vec<Cell>, // ram power-ups lying on the board
+    #[serde(default)]
+    pub ram: u64, // ticks of ramming left
+    #[serde(default = "one")]
+    pub multiplier: u16, // points for each food are multiplied by this
+    #[serde(default)]
+    pub poison: Vec<Cell>, // pellets that cost length and a point, moving elsewhere when eaten
+    #[serde(default)]
+    pub poison_kills: bool, // eating poison ends the run instead
+    #[serde(default)]
+    pub food_zone: Vec<Cell>, // see `level::Level::food_zone`
+    #[serde(default)]
+    pub rival: Option<Rival>,
+    #[serde(default)]
+    pub closing: Vec<Cell>, // cells turning into wall as soon as no snake is on them
+    #[serde(default)]
+    pub portals: Vec<Portal>,
+    #[serde(default)]
+    pub food_behavior: String, // name of what food does between meals, see `food`
+    #[serde(default)]
+    pub bots: Vec<Rival>, // battle snakes steered by the computer, see `rival`
+    #[serde(default)]
+    pub power_ups: Option<PowerUps>, // `None` when power-ups are off, see `powerup`
+    #[serde(skip)]
+    pub pickups: Vec<Cell>, // frontend plugins' pickups, put back by the plugins every tick
+}
+
+fn one() -> u16 {
+    1
+}
+
+/// saves from before several foods hold a single cell
+fn one_or_many<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Vec<Cell>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Food {
+        One(Cell),
+        Many(Vec<Cell>),
+    }
+    Ok(match Food::deserialize(d)? {
+        Food::One(cell) => alloc::vec![cell],
+        Food::Many(cells) => cells,
+    })
+}
+
+impl GameState {
+    pub fn new(size: (u16, u16), seed: u64) -> Self {
+        Self::with_spawn(size, seed, &Spawn::centered(size))
+    }
+
+    /// new game on an empty bordered board with the snake placed by `spawn`
+    pub fn with_spawn(size: (u16, u16), seed: u64, spawn: &Spawn) -> Self {
+        Self::with_level(&Level::bordered(size), seed, spawn)
+    }
+
+    /// new game on `level` with the snake placed by `spawn`, which must fit the level
+    pub fn with_level(level: &Level, seed: u64, spawn: &Spawn) -> Self {
+        let (width, height) = level.size;
+        let mut state = Self {
+            size: level.size,
+            wall: level.wall.clone(),
+            snake: Snake::new(spawn.pos, spawn.dir, spawn.len),
+            food: alloc::vec![Cell::new(
+                (width / 2).saturating_sub(1),
+                height.saturating_sub(3),
+            )],
+            score: 0,
+            is_over: false,
+            seed,
+            rng: Rng::new(seed),
+            tick: 0,
+            events: level.events.clone(),
+            extra_food: Vec::new(),
+            hazards: Vec::new(),
+            bus: Vec::new(),
+            mode: String::from(modes::MODES[0].name()),
+            won: false,
+            tail_rule: TailRule::Lenient,
+            rooms: level.rooms.clone(),
+            boss: None,
+            gates: level.gates.clone(),
+            breakables: level.breakables.clone(),
+            rams: Vec::new(),
+            ram: 0,
+            multiplier: 1,
+            poison: Vec::new(),
+            poison_kills: false,
+            food_zone: level.food_zone.clone(),
+            rival: None,
+            closing: Vec::new(),
+            portals: level.portals.clone(),
+            food_behavior: String::from(food::BEHAVIORS[0].name()),
+            bots: Vec::new(),
+            power_ups: None,
+            pickups: Vec::new(),
+        };
+        if state.is_occupied(&state.food[0]) || !state.in_food_zone(&state.food[0]) {
+            state.move_food(0);
+        }
+        state
+    }
+
+    pub fn rules(&self) -> &'static dyn GameMode {
+        modes::find(&self.mode)
+    }
+
+    pub fn food_behavior(&self) -> &'static dyn FoodBehavior {
+        food::find(&self.food_behavior)
+    }
+
+    /// post a notice on the bus, dropping the oldest ones if nobody takes them
+    pub fn notify(&mut self, notice: Notice) {
+        if self.bus.len() >= BUS_LEN {
+            self.bus.remove(0);
+        }
+        self.bus.push(notice);
+    }
+
+    /// change the heading, ignoring attempts to reverse into the body
+    pub fn turn(&mut self, dir: Direction) {
+        if dir != self.snake.dir.opposite() && dir != self.snake.dir {
+            self.snake.dir = dir;
+            self.notify(Notice::Turned(dir));
+        }
+    }
+
+    /// change the rival's heading, ignoring attempts to reverse into its body
+    pub fn turn_rival(&mut self, dir: Direction) {
+        if let Some(rival) = &mut self.rival {
+            if dir != rival.snake.dir.opposite() {
+                rival.snake.dir = dir;
+            }
+        }
+    }
+
+    /// crash the rival where it is, as when its player leaves; a duel ends there
+    pub fn forfeit_rival(&mut self) {
+        if let Some(rival) = &mut self.rival {
+            if !rival.crashed && !self.is_over {
+                rival.crashed = true;
+                self.is_over = rival.duel;
+            }
+        }
+    }
+
+    /// change battle snake `i`'s heading, ignoring attempts to reverse into its body
+    pub fn turn_bot(&mut self, i: usize, dir: Direction) {
+        if let Some(bot) = self.bots.get_mut(i) {
+            if dir != bot.snake.dir.opposite() {
+                bot.snake.dir = dir;
+            }
+        }
+    }
+
+    /// the state with the rival in the snake's place, for steering the rival
+    /// the way the snake is steered
+    pub fn rival_view(&self) -> Option<Self> {
+        let mut view = self.clone();
+        view.swap_rival().then_some(view)
+    }
+
+    /// the state with battle snake `i` in the snake's place, while it is still on the move
+    pub fn bot_view(&self, i: usize) -> Option<Self> {
+        let mut view = self.clone();
+        view.swap_bot(i).then_some(view)
+    }
+
+    /// trade places between the snake and a rival still on the move, scores
+    /// included, so the rules can be applied to the rival as to the snake
+    fn swap_rival(&mut self) -> bool {
+        match &mut self.rival {
+            Some(rival) if !rival.crashed => {
+                core::mem::swap(&mut self.snake, &mut rival.snake);
+                core::mem::swap(&mut self.score, &mut rival.score);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// trade places between the snake and battle snake `i`, like `swap_rival`
+    fn swap_bot(&mut self, i: usize) -> bool {
+        match self.bots.get_mut(i) {
+            Some(bot) if !bot.crashed => {
+                core::mem::swap(&mut self.snake, &mut bot.snake);
+                core::mem::swap(&mut self.score, &mut bot.score);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// whether a battle snake still on the move lies on `cell`
+    fn is_bot(&self, cell: &Cell) -> bool {
+        self.bots
+            .iter()
+            .any(|bot| !bot.crashed && bot.snake.body.contains(cell))
+    }
+
+    /// whether anything but the main food already sits on `cell`
+    fn is_occupied(&self, cell: &Cell) -> bool {
+        self.snake.check_overlap_food(cell)
+            || self.wall.cells.contains(cell)
+            || self.extra_food.contains(cell)
+            || self.hazards.contains(cell)
+            || self
+                .boss
+                .as_ref()
+                .is_some_and(|boss| boss.contains(cell.pos))
+            || self.gates.iter().any(|gate| gate.cells.contains(cell))
+            || self.breakables.contains(cell)
+            || self.rams.contains(cell)
+            || self.poison.contains(cell)
+            || self.closing.contains(cell)
+            || self.portals.iter().any(|portal| portal.contains(cell))
+            || self
+                .rival
+                .as_ref()
+                .is_some_and(|rival| rival.snake.body.contains(cell))
+            || self.is_bot(cell)
+            || self.pickups.contains(cell)
+    }
+
+    /// wall in ring `ring` of the board, the outer wall being ring 0; cells
+    /// a snake is on close once it has moved off them
+    pub fn close_ring(&mut self, ring: u16) {
+        let (width, height) = self.size;
+        if 2 * ring >= width.min(height) {
+            return;
+        }
+        let (right, bottom) = (width - 1 - ring, height - 1 - ring);
+        for y in ring..=bottom {
+            for x in ring..=right {
+                let cell = Cell::new(x, y);
+                let edge = x == ring || y == ring || x == right || y == bottom;
+                if edge && !self.wall.cells.contains(&cell) && !self.closing.contains(&cell) {
+                    self.closing.push(cell);
+                }
+            }
+        }
+        self.fill_closing();
+    }
+
+    /// turn the closing cells no snake is on into wall; whatever else lay
+    /// there is moved elsewhere or dropped
+    pub fn fill_closing(&mut self) {
+        let on_snake = |state: &Self, cell: &Cell| {
+            state.snake.body.contains(cell)
+                || state
+                    .rival
+                    .as_ref()
+                    .is_some_and(|r| r.snake.body.contains(cell))
+                || state.is_bot(cell)
+        };
+        let (free, taken): (Vec<_>, Vec<_>) = core::mem::take(&mut self.closing)
+            .into_iter()
+            .partition(|cell| !on_snake(self, cell));
+        self.closing = taken;
+        for cell in free {
+            self.extra_food.retain(|c| *c != cell);
+            self.hazards.retain(|c| *c != cell);
+            self.rams.retain(|c| *c != cell);
+            self.breakables.retain(|c| *c != cell);
+            self.portals.retain(|portal| !portal.contains(&cell));
+            self.wall.cells.push(cell.clone());
+            for i in 0..self.food.len() {
+                if self.food[i] == cell {
+                    self.move_food(i);
+                }
+            }
+            for i in (0..self.poison.len()).rev() {
+                if self.poison[i] == cell {
+                    self.move_poison(i);
+                }
+            }
+        }
+    }
+
+    /// points for each food right now, doubled while a double-points power-up runs
+    pub fn points_multiplier(&self) -> u16 {
+        let double = self
+            .power_ups
+            .as_ref()
+            .is_some_and(|power_ups| power_ups.is_active(Kind::Double));
+        if double {
+            self.multiplier.saturating_mul(2)
+        } else {
+            self.multiplier
+        }
+    }
+
+    /// whether `cell` is part of a gate that is closed right now
+    pub fn is_closed_gate(&self, cell: &Cell) -> bool {
+        self.gates
+            .iter()
+            .any(|gate| gate.is_closed(self.tick) && gate.cells.contains(cell))
+    }
+
+    /// where a head moving onto `pos` comes out, if `pos` is a portal
+    pub fn portal_exit(&self, pos: (u16, u16)) -> Option<(u16, u16)> {
+        let cell = Cell::new(pos.0, pos.1);
+        self.portals
+            .iter()
+            .find_map(|portal| portal.exit(&cell))
+            .map(|exit| exit.pos)
+    }
+
+    /// a random cell inside the border that nothing occupies, `None` if the board is full
+    pub fn random_free_cell(&mut self) -> Option<Cell> {
+        let free =
+            |state: &Self, cell: &Cell| !state.is_occupied(cell) && !state.food.contains(cell);
+        // guessing finds one quickly unless the board is nearly full
+        for _ in 0..FREE_CELL_TRIES {
+            let x = self.rng.gen_range(1, self.size.0 - 1);
+            let y = self.rng.gen_range(1, self.size.1 - 1);
+            let cell = Cell::new(x, y);
+            if free(self, &cell) {
+                return Some(cell);
+            }
+        }
+        let (width, height) = self.size;
+        let cells: Vec<Cell> = (1..height.saturating_sub(1))
+            .flat_map(|y| (1..width.saturating_sub(1)).map(move |x| Cell::new(x, y)))
+            .filter(|cell| free(self, cell))
+            .collect();
+        if cells.is_empty() {
+            return None;
+        }
+        let i = self.rng.next_u64() % cells.len() as u64;
+        Some(cells[i as usize].clone())
+    }
+
+    fn in_food_zone(&self, cell: &Cell) -> bool {
+        self.food_zone.is_empty() || self.food_zone.contains(cell)
+    }
+
+    /// a random free cell of the food zone, or of the board if the zone is
+    /// full; `None` if the board is full too
+    fn random_food_cell(&mut self) -> Option<Cell> {
+        let free: Vec<Cell> = self
+            .food_zone
+            .iter()
+            .filter(|c| !self.is_occupied(c) && !self.food.contains(c))
+            .cloned()
+            .collect();
+        if free.is_empty() {
+            return self.random_free_cell();
+        }
+        let i = self.rng.next_u64() % free.len() as u64;
+        Some(free[i as usize].clone())
+    }
+
+    /// move the `i`th food to a random free cell; it stays where it is if there is none
+    pub fn move_food(&mut self, i: usize) {
+        if let Some(cell) = self.random_food_cell() {
+            self.food[i] = cell;
+        }
+    }
+
+    /// move the `i`th poison pellet to a random free cell, or take it away if there is none
+    fn move_poison(&mut self, i: usize) {
+        match self.random_free_cell() {
+            Some(cell) => self.poison[i] = cell,
+            None => {
+                self.poison.remove(i);
+            }
+        }
+    }
+
+    /// step the `i`th food to a random free neighbouring cell of the food
+    /// zone, one no closer to the snake's head if there is such a cell
+    pub fn nudge_food(&mut self, i: usize) {
+        let (x, y) = self.food[i].pos;
+        let head = self.snake.head().pos;
+        let away = |(cx, cy): (u16, u16)| cx.abs_diff(head.0) + cy.abs_diff(head.1);
+        let free: Vec<Cell> = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+            .iter()
+            .map(|&(dx, dy)| Cell::new(x.saturating_add_signed(dx), y.saturating_add_signed(dy)))
+            .filter(|c| c.pos.0 < self.size.0 && c.pos.1 < self.size.1)
+            .filter(|c| !self.is_occupied(c) && !self.food.contains(c) && self.in_food_zone(c))
+            .collect();
+        let (farther, closer): (Vec<Cell>, Vec<Cell>) =
+            free.into_iter().partition(|c| away(c.pos) >= away((x, y)));
+        let choices = if farther.is_empty() { closer } else { farther };
+        if !choices.is_empty() {
+            let pick = self.rng.next_u64() % choices.len() as u64;
+            self.food[i] = choices[pick as usize].clone();
+        }
+    }
+
+    /// put up to `n` single-cell rocks on free cells; none touches another wall
+    /// cell, so the board stays connected, and none lies in the snake's way
+    /// for its first few steps
+    pub fn scatter_rocks(&mut self, n: usize) {
+        let head = self.snake.head().clone();
+        let mut placed = 0;
+        for _ in 0..n * ROCK_TRIES {
+            if placed == n {
+                break;
+            }
+            let cell = match self.random_free_cell() {
+                Some(cell) => cell,
+                None => break,
+            };
+            let (x, y) = cell.pos;
+            let touches = |c: &Cell| c.pos.0.abs_diff(x) <= 1 && c.pos.1.abs_diff(y) <= 1;
+            let crowded = self.wall.cells.iter().any(touches)
+                || self.breakables.iter().any(touches)
+                || self.gates.iter().any(|gate| gate.cells.iter().any(touches));
+            let ahead =
+                (0..=ROCK_CLEARANCE).any(|i| head.clone_with_pos_shift(self.snake.dir, i) == cell);
+            if !crowded && !ahead {
+                self.wall.cells.push(cell);
+                placed += 1;
+            }
+        }
+    }
+
+    /// put up to `n` pairs of portals on free cells away from walls and the
+    /// snake's first few steps, so a snake coming out of one has room to turn
+    pub fn scatter_portals(&mut self, n: usize) {
+        let head = self.snake.head().clone();
+        let mut ends = Vec::new();
+        for _ in 0..2 * n * ROCK_TRIES {
+            if ends.len() == 2 * n {
+                break;
+            }
+            let cell = match self.random_free_cell() {
+                Some(cell) => cell,
+                None => break,
+            };
+            let (x, y) = cell.pos;
+            let touches = |c: &Cell| c.pos.0.abs_diff(x) <= 1 && c.pos.1.abs_diff(y) <= 1;
+            let crowded = self.wall.cells.iter().any(touches)
+                || self.breakables.iter().any(touches)
+                || self.gates.iter().any(|gate| gate.cells.iter().any(touches))
+                || ends.iter().any(touches);
+            let ahead =
+                (0..=ROCK_CLEARANCE).any(|i| head.clone_with_pos_shift(self.snake.dir, i) == cell);
+            if !crowded && !ahead {
+                ends.push(cell);
+            }
+        }
+        // ids go on from the level's own portals
+        let taken: Vec<char> = self.portals.iter().map(|portal| portal.id).collect();
+        let mut ids = ('a'..='u').filter(|id| !taken.contains(id));
+        let mut ends = ends.into_iter();
+        while let (Some(a), Some(b), Some(id)) = (ends.next(), ends.next(), ids.next()) {
+            self.portals.push(Portal { id, ends: [a, b] });
+        }
+    }
+
+    /// add or take away poison pellets until `n` are on the board
+    pub fn set_poison_count(&mut self, n: usize) {
+        self.poison.truncate(n);
+        while self.poison.len() < n {
+            match self.random_free_cell() {
+                Some(cell) => self.poison.push(cell),
+                None => break,
+            }
+        }
+    }
+
+    /// add or take away foods until `n` are on the board, keeping at least one
+    pub fn set_food_count(&mut self, n: usize) {
+        self.food.truncate(n.max(1));
+        while self.food.len() < n {
+            match self.random_food_cell() {
+                Some(cell) => self.food.push(cell),
+                None => break,
+            }
+        }
+    }
+
+    /// start ramming if the head is on a ram power-up
+    fn pick_up_ram(&mut self) {
+        let head = self.snake.head();
+        if let Some(i) = self.rams.iter().position(|c| c == head) {
+            self.rams.remove(i);
+            self.ram = RAM_TICKS;
+            self.notify(Notice::PowerUp("ram"));
+        }
+    }
+
+    /// while ramming, break the wall the head is on at the cost of a tail cell;
+    /// a snake too short to pay crashes into it on the next tick
+    fn smash(&mut self) {
+        let head = self.snake.head();
+        let i = match self.breakables.iter().position(|c| c == head) {
+            Some(i) if self.ram > 0 && self.snake.body.len() > 1 => i,
+            _ => return,
+        };
+        self.breakables.remove(i);
+        self.snake.body.pop_back();
+        self.notify(Notice::Smashed);
+    }
+
+    /// advance the game by one tick using the next turn from `input`
+    pub fn step_with<I: InputSource>(&mut self, input: &mut I) {
+        self.step(input.poll());
+    }
+
+    /// what the snake ran into on its last move, unless the rules let it survive
+    fn crash(&mut self, rules: &dyn GameMode) -> Option<Crash> {
+        let mut crash = None;
+        if self.snake.check_bite_body() && rules.on_self_bite(self) == Hit::Die {
+            crash = Some(Crash::Body);
+        }
+        // checked even after a survived bite, the head must not be left on the wall
+        let on_wall = self.snake.check_collide_wall(&self.wall)
+            || self.is_closed_gate(self.snake.head())
+            || self.breakables.contains(self.snake.head());
+        if crash.is_none() && on_wall && rules.on_wall_hit(self) == Hit::Die {
+            crash = Some(Crash::Wall);
+        }
+        if crash.is_none() && self.hazards.contains(self.snake.head()) {
+            crash = Some(Crash::Hazard);
+        }
+        let head = self.snake.head().pos;
+        if crash.is_none() && self.boss.as_ref().is_some_and(|boss| boss.contains(head)) {
+            crash = Some(Crash::Boss);
+        }
+        let head = self.snake.head();
+        if crash.is_none()
+            && self
+                .rival
+                .as_ref()
+                .is_some_and(|r| r.snake.body.contains(head))
+        {
+            crash = Some(Crash::Rival);
+        }
+        if crash.is_none() && self.is_bot(self.snake.head()) {
+            crash = Some(Crash::Rival);
+        }
+        if crash.is_none() && self.poison_kills && self.poison.contains(self.snake.head()) {
+            crash = Some(Crash::Poison);
+        }
+        if crash.is_none() && self.tail_rule == TailRule::Strict && self.snake.body.len() > 1 {
+            let next = self.snake.head().clone_with_pos_shift(self.snake.dir, 1);
+            let next = match self.portal_exit(next.pos) {
+                Some((x, y)) => Cell::new(x, y),
+                None => next,
+            };
+            if self.snake.body.back() == Some(next) && rules.on_self_bite(self) == Hit::Die {
+                crash = Some(Crash::Body);
+            }
+        }
+        crash
+    }
+
+    /// eat whatever lies under the head, then move on, growing after a food
+    fn eat_and_move(&mut self, rules: &dyn GameMode) {
+        if let Some(i) = self
+            .poison
+            .iter()
+            .position(|c| self.snake.check_bite_food(c))
+        {
+            for _ in 0..POISON_CUT.min(self.snake.body.len() - 1) {
+                self.snake.body.pop_back();
+            }
+            self.score = self.score.saturating_sub(1);
+            self.move_poison(i);
+            self.notify(Notice::Poisoned);
+        }
+        let food = self.food.iter().position(|c| self.snake.check_bite_food(c));
+        let extra = self
+            .extra_food
+            .iter()
+            .position(|c| self.snake.check_bite_food(c));
+        if let Some(i) = food {
+            self.score = self.score.saturating_add(
+                rules
+                    .on_food_eaten(self)
+                    .saturating_mul(self.points_multiplier()),
+            );
+            self.snake.grow_body();
+            // a won board may have no room left for the food
+            if !rules.is_won(self) {
+                self.move_food(i);
+            }
+            self.notify(Notice::Ate("food"));
+        } else if let Some(i) = extra {
+            self.extra_food.remove(i);
+            self.score = self.score.saturating_add(
+                rules
+                    .on_food_eaten(self)
+                    .saturating_mul(self.points_multiplier()),
+            );
+            self.snake.grow_body();
+            self.notify(Notice::Ate("bonus"));
+        } else if rules.keeps_tail() {
+            self.snake.grow_body();
+        } else {
+            self.snake.move_body();
+        }
+        if let Some((x, y)) = self.portal_exit(self.snake.head().pos) {
+            self.snake.body.set_head(Cell::new(x, y));
+        }
+    }
+
+    /// advance the game by one tick, applying `turn` first if given
+    pub fn step(&mut self, turn: Option<Direction>) {
+        if let Some(dir) = turn {
+            self.turn(dir);
+        }
+        let rules = self.rules();
+        let crash = self.crash(rules);
+        let rival_crash = if self.swap_rival() {
+            let crash = self.crash(rules);
+            self.swap_rival();
+            crash
+        } else {
+            None
+        };
+        if let (Some(rival), Some(_)) = (&mut self.rival, rival_crash) {
+            rival.crashed = true;
+        }
+        // every battle snake is checked before any is taken off, so heads that meet both crash
+        let bot_crashes: Vec<usize> = (0..self.bots.len())
+            .filter(|&i| {
+                if !self.swap_bot(i) {
+                    return false;
+                }
+                let crash = self.crash(rules);
+                self.swap_bot(i);
+                crash.is_some()
+            })
+            .collect();
+        for i in bot_crashes {
+            self.bots[i].crashed = true;
+            if self.snake.body.contains(self.bots[i].snake.head()) {
+                let points = ELIMINATION_POINTS.saturating_mul(self.points_multiplier());
+                self.score = self.score.saturating_add(points);
+                self.notify(Notice::Eliminated);
+            }
+        }
+        if let Some(crash) = crash {
+            // a crashed snake stays where it hit
+            self.is_over = true;
+            self.notify(Notice::Crashed(crash));
+            return;
+        }
+        if rival_crash.is_some() && self.rival.as_ref().is_some_and(|r| r.duel) {
+            self.is_over = true;
+            return;
+        }
+        self.eat_and_move(rules);
+        // what the rival and battle snakes eat is not the snake's news
+        let posted = self.bus.len();
+        if self.swap_rival() {
+            self.eat_and_move(rules);
+            self.swap_rival();
+        }
+        for i in 0..self.bots.len() {
+            if self.swap_bot(i) {
+                self.eat_and_move(rules);
+                self.swap_bot(i);
+            }
+        }
+        self.bus.truncate(posted);
+        self.notify(Notice::Moved);
+        self.ram = self.ram.saturating_sub(1);
+        self.pick_up_ram();
+        self.smash();
+        self.tick += 1;
+        events::fire(self);
+        boss::update(self);
+        rules.on_tick(self);
+        self.food_behavior().on_tick(self);
+        powerup::update(self);
+        let boss_defeated = self.boss.as_ref().is_some_and(|boss| boss.is_defeated());
+        let last_standing = !self.bots.is_empty() && self.bots.iter().all(|bot| bot.crashed);
+        if rules.is_won(self) || boss_defeated || last_standing {
+            self.won = true;
+            self.is_over = true;
+        } else if rules.is_lost(self) {
+            self.is_over = true;
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Random mazes: corridors one cell wide filling the whole board, carved by
+//! a recursive backtracker.
+//!
+//! A backtracker leaves exactly one path between any two cells and plenty of
+//! dead ends, which a snake can't turn around in. Every dead end is opened
+//! into a neighbouring corridor, so the maze is full of loops instead, and a
+//! clearing in the middle holds the snake at the start. All open cells stay
+//! connected, so the snake can reach the food wherever it appears.
+
+use crate::level::{Level, SpawnPoint};
+use crate::{Cell, Direction, Rng, Wall};
+use alloc::vec;
+use alloc::vec::Vec;
+
+const STEPS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// a maze filling a board of `size`, with room to start a snake `len` cells long
+pub fn generate(size: (u16, u16), len: u16, rng: &mut Rng) -> Level {
+    let (width, height) = size;
+    // corridor crossings sit on odd columns and rows, the cells between them are knocked through
+    let across = (
+        i32::from(width.saturating_sub(1) / 2),
+        i32::from(height.saturating_sub(1) / 2),
+    );
+    let mut open = vec![false; usize::from(width) * usize::from(height)];
+    let index = |(x, y): (i32, i32)| y as usize * usize::from(width) + x as usize;
+    let cell = |(i, j): (i32, i32)| (2 * i + 1, 2 * j + 1);
+    let inside = |(i, j): (i32, i32)| (0..across.0).contains(&i) && (0..across.1).contains(&j);
+    let between = |(i, j): (i32, i32), (di, dj): (i32, i32)| (2 * i + 1 + di, 2 * j + 1 + dj);
+
+    if across.0 > 0 && across.1 > 0 {
+        let mut stack = vec![(0, 0)];
+        open[index(cell((0, 0)))] = true;
+        while let Some(&at) = stack.last() {
+            let fresh: Vec<_> = STEPS
+                .iter()
+                .map(|&(di, dj)| ((at.0 + di, at.1 + dj), (di, dj)))
+                .filter(|&(next, _)| inside(next) && !open[index(cell(next))])
+                .collect();
+            if fresh.is_empty() {
+                stack.pop();
+                continue;
+            }
+            let (next, step) = fresh[(rng.next_u64() % fresh.len() as u64) as usize];
+            open[index(between(at, step))] = true;
+            open[index(cell(next))] = true;
+            stack.push(next);
+        }
+        // open every dead end into a neighbouring corridor
+        for j in 0..across.1 {
+            for i in 0..across.0 {
+                let ways: Vec<_> = STEPS
+                    .iter()
+                    .copied()
+                    .filter(|&(di, dj)| inside((i + di, j + dj)))
+                    .collect();
+                let walled: Vec<_> = ways
+                    .iter()
+                    .copied()
+                    .filter(|&step| !open[index(between((i, j), step))])
+                    .collect();
+                if ways.len() - walled.len() == 1 && !walled.is_empty() {
+                    let step = walled[(rng.next_u64() % walled.len() as u64) as usize];
+                    open[index(between((i, j), step))] = true;
+                }
+            }
+        }
+    }
+
+    // a clearing three rows high for the snake to start in, heading right
+    let head = (i32::from(width / 2), i32::from(height / 2));
+    let left = (head.0 - i32::from(len)).max(1);
+    let right = (head.0 + 2).min(i32::from(width) - 2);
+    for y in (head.1 - 1).max(1)..=(head.1 + 1).min(i32::from(height) - 2) {
+        for x in left..=right {
+            open[index((x, y))] = true;
+        }
+    }
+
+    let mut wall = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            if !open[index((i32::from(x), i32::from(y)))] {
+                wall.push(Cell::new(x, y));
+            }
+        }
+    }
+    Level {
+        wall: Wall { cells: wall },
+        spawns: vec![SpawnPoint {
+            pos: (head.0 as u16, head.1 as u16),
+            dir: Direction::Right,
+        }],
+        ..Level::bordered(size)
+    }
+}
@@ -0,0 +1,244 @@
+//! Game modes: the rules that differ between ways to play.
+//!
+//! [`GameState::step`](crate::GameState::step) consults the mode named in
+//! the state at each point where modes differ. Modes are stateless; anything
+//! they need to remember lives in the game state. New modes are added by
+//! implementing [`GameMode`] and listing them in [`MODES`].
+
+use crate::{Cell, Direction, GameState};
+
+/// what happens after a crash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hit {
+    Die,
+    Survive,
+}
+
+pub trait GameMode: Sync {
+    /// unique name, stored in saves
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+
+    /// the head ran into a wall cell; the mode may move it before surviving
+    fn on_wall_hit(&self, _state: &mut GameState) -> Hit {
+        Hit::Die
+    }
+
+    /// the head ran into the body
+    fn on_self_bite(&self, _state: &mut GameState) -> Hit {
+        Hit::Die
+    }
+
+    /// points scored for a food
+    fn on_food_eaten(&self, _state: &mut GameState) -> u16 {
+        1
+    }
+
+    /// whether the tail stays put as the snake moves, leaving a trail
+    fn keeps_tail(&self) -> bool {
+        false
+    }
+
+    /// called at the end of every tick
+    fn on_tick(&self, _state: &mut GameState) {}
+
+    /// the run is won, by default once the snake fills the board
+    fn is_won(&self, state: &GameState) -> bool {
+        let cells = usize::from(state.size.0) * usize::from(state.size.1);
+        state.snake.body.len() >= cells - state.wall.cells.len()
+    }
+
+    fn is_lost(&self, _state: &GameState) -> bool {
+        false
+    }
+
+    /// ticks left before the run ends on time, for modes with a time limit
+    fn ticks_left(&self, _state: &GameState) -> Option<u64> {
+        None
+    }
+
+    /// ticks left before the walls close in, for modes with a shrinking arena
+    fn ticks_to_shrink(&self, _state: &GameState) -> Option<u64> {
+        None
+    }
+}
+
+/// the head steps through the outer wall and comes back in on the opposite side
+fn wrap_around(state: &mut GameState) -> bool {
+    let (width, height) = state.size;
+    let dir = state.snake.dir;
+    let (x, y) = match state.snake.body.front() {
+        Some(head) => head.pos,
+        None => return false,
+    };
+    // leaving along the heading lands on the far border, the next move brings it inside
+    let wrap = |v: u16, max: u16, towards_low: bool, along: bool| match (v, along) {
+        (0, true) if towards_low => Some(max - 1),
+        (0, _) => Some(max - 2),
+        (v, true) if v == max - 1 && !towards_low => Some(0),
+        (v, _) if v == max - 1 => Some(1),
+        _ => None,
+    };
+    let horizontal = matches!(dir, Direction::Left | Direction::Right);
+    let new_x = wrap(x, width, dir == Direction::Left, horizontal);
+    let new_y = wrap(y, height, dir == Direction::Up, !horizontal);
+    if new_x.is_none() && new_y.is_none() {
+        return false;
+    }
+    let (x, y) = (new_x.unwrap_or(x), new_y.unwrap_or(y));
+    state.snake.body.set_head(Cell::new(x, y));
+    true
+}
+
+pub struct Classic;
+
+impl GameMode for Classic {
+    fn name(&self) -> &'static str {
+        "classic"
+    }
+
+    fn description(&self) -> &'static str {
+        "walls and your own body are deadly"
+    }
+}
+
+pub struct Wrap;
+
+impl GameMode for Wrap {
+    fn name(&self) -> &'static str {
+        "wrap"
+    }
+
+    fn description(&self) -> &'static str {
+        "leave through the outer wall to come back on the other side"
+    }
+
+    fn on_wall_hit(&self, state: &mut GameState) -> Hit {
+        if wrap_around(state) {
+            Hit::Survive
+        } else {
+            Hit::Die
+        }
+    }
+}
+
+const TIME_ATTACK_TICKS: u64 = 400;
+const TIME_ATTACK_POINTS: u16 = 2;
+
+pub struct TimeAttack;
+
+impl GameMode for TimeAttack {
+    fn name(&self) -> &'static str {
+        "time-attack"
+    }
+
+    fn description(&self) -> &'static str {
+        "score as much as you can before the time runs out, foods count double"
+    }
+
+    fn on_food_eaten(&self, _state: &mut GameState) -> u16 {
+        TIME_ATTACK_POINTS
+    }
+
+    fn is_lost(&self, state: &GameState) -> bool {
+        state.tick >= TIME_ATTACK_TICKS
+    }
+
+    fn ticks_left(&self, state: &GameState) -> Option<u64> {
+        Some(TIME_ATTACK_TICKS.saturating_sub(state.tick))
+    }
+}
+
+pub struct Zen;
+
+impl GameMode for Zen {
+    fn name(&self) -> &'static str {
+        "zen"
+    }
+
+    fn description(&self) -> &'static str {
+        "no walls, no bites, just eating; only hazards end the run"
+    }
+
+    fn on_wall_hit(&self, state: &mut GameState) -> Hit {
+        // inner walls are passed straight through
+        wrap_around(state);
+        Hit::Survive
+    }
+
+    fn on_self_bite(&self, _state: &mut GameState) -> Hit {
+        Hit::Survive
+    }
+}
+
+const TRON_TICKS_PER_POINT: u64 = 10;
+
+pub struct Tron;
+
+impl GameMode for Tron {
+    fn name(&self) -> &'static str {
+        "tron"
+    }
+
+    fn description(&self) -> &'static str {
+        "your trail never ends; survive as long as you can"
+    }
+
+    fn keeps_tail(&self) -> bool {
+        true
+    }
+
+    fn on_tick(&self, state: &mut GameState) {
+        if state.tick.is_multiple_of(TRON_TICKS_PER_POINT) && !state.is_over {
+            state.score = state.score.saturating_add(1);
+        }
+    }
+}
+
+const SHRINK_TICKS: u64 = 200; // 30 seconds at the normal 150 ms tick, sooner once it speeds up
+const MIN_ARENA: u16 = 6; // cells across the arena at least, it stops shrinking there
+
+pub struct Survival;
+
+impl Survival {
+    /// whether walling in `ring` still leaves the arena wide enough
+    fn may_close(state: &GameState, ring: u64) -> bool {
+        let across = u64::from(state.size.0.min(state.size.1));
+        across >= 2 * (ring + 1) + u64::from(MIN_ARENA)
+    }
+}
+
+impl GameMode for Survival {
+    fn name(&self) -> &'static str {
+        "survival"
+    }
+
+    fn description(&self) -> &'static str {
+        "the walls close in by one ring every 200 ticks"
+    }
+
+    fn on_tick(&self, state: &mut GameState) {
+        state.fill_closing();
+        let ring = state.tick / SHRINK_TICKS;
+        if state.tick.is_multiple_of(SHRINK_TICKS) && Survival::may_close(state, ring) {
+            state.close_ring(ring as u16);
+        }
+    }
+
+    fn ticks_to_shrink(&self, state: &GameState) -> Option<u64> {
+        let next = state.tick / SHRINK_TICKS + 1;
+        Survival::may_close(state, next).then(|| SHRINK_TICKS - state.tick % SHRINK_TICKS)
+    }
+}
+
+/// every mode, the first one is the default
+pub static MODES: &[&dyn GameMode] = &[&Classic, &Wrap, &TimeAttack, &Zen, &Tron, &Survival];
+
+/// the mode called `name`, classic for unknown names
+pub fn find(name: &str) -> &'static dyn GameMode {
+    MODES
+        .iter()
+        .copied()
+        .find(|mode| mode.name() == name)
+        .unwrap_or(MODES[0])
+}
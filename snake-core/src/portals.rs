@@ -0,0 +1,29 @@
+//! Portals: pairs of linked cells. A head moving onto one end comes out of
+//! the other, still heading the same way, and the body follows it through.
+//!
+//! Nothing else lies on a portal, so food never appears on one, and a snake
+//! only crashes through a portal into what waits at the far end.
+
+use crate::Cell;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Portal {
+    pub id: char, // the map character of both ends
+    pub ends: [Cell; 2],
+}
+
+impl Portal {
+    pub fn contains(&self, cell: &Cell) -> bool {
+        self.ends.contains(cell)
+    }
+
+    /// the end linked to `cell`, if `cell` is one of the ends
+    pub fn exit(&self, cell: &Cell) -> Option<&Cell> {
+        match &self.ends {
+            [a, b] if a == cell => Some(b),
+            [a, b] if b == cell => Some(a),
+            _ => None,
+        }
+    }
+}
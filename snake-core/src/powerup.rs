@@ -0,0 +1,130 @@
+//! Timed power-ups dropped on the board now and then.
+//!
+//! One lies on a free cell for a while; eating it speeds the snake up, slows
+//! it down, doubles the points for food or cuts the snake in half. Speed and
+//! points last a number of ticks. They are kept in the state, so a rewind,
+//! a save or a replay brings them back as they were. How long a tick lasts
+//! is up to the frontend, which asks [`PowerUps::time_factor`].
+
+use crate::bus::Notice;
+use crate::{Cell, GameState};
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+const EVERY: u64 = 120; // ticks between drops
+const LYING_TICKS: u64 = 60; // ticks a power-up stays on the board
+const EFFECT_TICKS: u64 = 50; // ticks a speed or points effect lasts
+const MIN_LEN: usize = 3; // shrinking stops here
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Fast,
+    Slow,
+    Double,
+    Shrink,
+}
+
+const KINDS: [Kind; 4] = [Kind::Fast, Kind::Slow, Kind::Double, Kind::Shrink];
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Fast => "fast",
+            Kind::Slow => "slow",
+            Kind::Double => "double",
+            Kind::Shrink => "shrink",
+        }
+    }
+
+    /// how much longer a tick lasts while it is running
+    fn time_factor(self) -> f64 {
+        match self {
+            Kind::Fast => 0.6,
+            Kind::Slow => 1.6,
+            Kind::Double | Kind::Shrink => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerUps {
+    lying: Option<(Cell, Kind, u64)>, // cell, kind and the tick it disappears
+    active: Vec<(Kind, u64)>,         // running effects and the tick they wear off
+}
+
+impl PowerUps {
+    /// how much longer a tick lasts under the effects running now
+    pub fn time_factor(&self) -> f64 {
+        self.active
+            .iter()
+            .map(|&(kind, _)| kind.time_factor())
+            .product()
+    }
+
+    pub fn is_active(&self, kind: Kind) -> bool {
+        self.active.iter().any(|&(k, _)| k == kind)
+    }
+
+    /// the power-up on the board, if any
+    pub fn lying(&self) -> Option<(&Cell, Kind)> {
+        self.lying.as_ref().map(|(cell, kind, _)| (cell, *kind))
+    }
+
+    /// lay a `kind` power-up on `cell` until `tick`, for a scripted board
+    pub fn place(&mut self, cell: Cell, kind: Kind, tick: u64) {
+        self.lying = Some((cell, kind, tick));
+    }
+
+    /// running effects and the ticks they have left at `tick`
+    pub fn active(&self, tick: u64) -> impl Iterator<Item = (Kind, u64)> + '_ {
+        self.active
+            .iter()
+            .map(move |&(kind, until)| (kind, until.saturating_sub(tick)))
+    }
+
+    fn apply(&mut self, kind: Kind, state: &mut GameState) {
+        let until = state.tick + EFFECT_TICKS;
+        match kind {
+            Kind::Shrink => {
+                let len = (state.snake.body.len() / 2).max(MIN_LEN);
+                while state.snake.body.len() > len {
+                    state.snake.body.pop_back();
+                }
+            }
+            // eating the same kind again only restarts its timer
+            _ => match self.active.iter_mut().find(|(k, _)| *k == kind) {
+                Some((_, left)) => *left = until,
+                None => self.active.push((kind, until)),
+            },
+        }
+    }
+}
+
+/// pick up, wear off and drop power-ups after a tick, if they are on
+pub fn update(state: &mut GameState) {
+    let mut power_ups = match state.power_ups.take() {
+        Some(power_ups) => power_ups,
+        None => return,
+    };
+    let tick = state.tick;
+    power_ups.active.retain(|&(_, until)| until > tick);
+    match power_ups.lying {
+        Some((ref cell, kind, _)) if state.snake.head() == cell => {
+            power_ups.lying = None;
+            power_ups.apply(kind, state);
+            state.notify(Notice::PowerUp(kind.name()));
+        }
+        Some((_, _, until)) if tick >= until => power_ups.lying = None,
+        Some(_) => (),
+        None if tick > 0 && tick.is_multiple_of(EVERY) && !state.is_over => {
+            let kind = KINDS[(state.rng.next_u64() % KINDS.len() as u64) as usize];
+            power_ups.lying = state
+                .random_free_cell()
+                .map(|cell| (cell, kind, tick + LYING_TICKS));
+        }
+        None => (),
+    }
+    state.power_ups = Some(power_ups);
+}
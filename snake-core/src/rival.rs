@@ -0,0 +1,36 @@
+//! A second snake sharing the board, for two players on one keyboard or a
+//! computer opponent.
+//!
+//! The rival moves every tick along with the snake, plays by the same rules
+//! and eats the same food, keeping a score of its own. Either snake running
+//! into the other's body crashes; when both heads meet, both crash and the
+//! run is a draw. In a duel the run ends as soon as one of them crashes;
+//! otherwise a crashed rival stays where it hit, in the snake's way.
+//!
+//! Battle snakes are rivals too, two or three of them steered by the
+//! computer. They collide with every other snake like the rival does, but a
+//! crashed one is taken off the board, and one that crashed into the snake
+//! scores it points. The snake wins by being the last one left.
+
+use crate::{Snake, Spawn};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rival {
+    pub snake: Snake,
+    pub score: u16,
+    pub crashed: bool,
+    #[serde(default)]
+    pub duel: bool, // the run ends when the rival crashes
+}
+
+impl Rival {
+    pub fn new(spawn: &Spawn, duel: bool) -> Self {
+        Self {
+            snake: Snake::new(spawn.pos, spawn.dir, spawn.len),
+            score: 0,
+            crashed: false,
+            duel,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Short animations played on the board, such as the snake's death.
+//!
+//! An animation is a timeline: from the time it has been playing it tells
+//! how each cell of the snake looks in the frame being drawn. The game draws
+//! frames until it is done; a key press skips what is left of it.
+
+use std::time::{Duration, Instant};
+
+const FLASH: Duration = Duration::from_millis(600); // the whole snake blinks this long
+const BLINK: Duration = Duration::from_millis(100); // one blink, on or off
+const CRUMBLE: Duration = Duration::from_millis(1000); // at most, for any length
+const CELL: Duration = Duration::from_millis(60); // at most, between two cells crumbling
+const DEBRIS: Duration = Duration::from_millis(150); // a crumbled cell leaves debris this long
+
+/// how a snake cell looks in a frame
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Look {
+    Whole,
+    Flash,
+    Debris,
+    Gone,
+}
+
+/// the snake flashes, then crumbles head to tail, one cell after another
+pub struct Death {
+    started: Instant,
+    per_cell: Duration, // between two cells crumbling
+    cells: u32,
+}
+
+impl Death {
+    pub fn new(len: usize) -> Self {
+        let cells = u32::try_from(len.max(1)).unwrap_or(u32::MAX);
+        Self {
+            started: Instant::now(),
+            per_cell: CELL.min(CRUMBLE / cells),
+            cells,
+        }
+    }
+
+    /// how cell `i` of the snake, counted from the head, looks now
+    pub fn look(&self, i: usize) -> Look {
+        let elapsed = self.started.elapsed();
+        if elapsed < FLASH {
+            let blinks = elapsed.as_millis() / BLINK.as_millis();
+            return if blinks.is_multiple_of(2) {
+                Look::Flash
+            } else {
+                Look::Whole
+            };
+        }
+        let crumbles = FLASH + self.per_cell * u32::try_from(i).unwrap_or(u32::MAX);
+        match elapsed.checked_sub(crumbles) {
+            None => Look::Whole,
+            Some(since) if since < DEBRIS => Look::Debris,
+            Some(_) => Look::Gone,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.started.elapsed() >= FLASH + self.per_cell * self.cells + DEBRIS
+    }
+}
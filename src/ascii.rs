@@ -0,0 +1,92 @@
+//! Drawing with ASCII only, for terminals and fonts without block and
+//! arrow characters.
+//!
+//! `--ascii` or `[display] ascii = true` turn it on; otherwise it is on when
+//! the locale isn't UTF-8. The board then uses the built-in `ascii` glyph set
+//! (unless the configured one is ASCII already), and an [`AsciiWriter`]
+//! around the terminal output swaps every other character for an ASCII one of
+//! the same width, so the layout stays the same.
+
+use std::env;
+use std::io::{self, Write};
+
+/// whether the locale asks for ASCII, i.e. names a character set other than UTF-8
+pub fn detect() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty());
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_lowercase();
+            !locale.contains("utf-8") && !locale.contains("utf8")
+        }
+        // no locale at all, as on Windows: assume the terminal copes
+        None => false,
+    }
+}
+
+/// the ASCII stand-in for `c`
+fn ascii(c: char) -> char {
+    match c {
+        _ if c.is_ascii() => c,
+        '█' | '▓' | '▒' | '░' => '#',
+        '·' | '•' => '.',
+        '◀' => '<',
+        '▶' => '>',
+        '▲' => '^',
+        '▼' => 'v',
+        '◤' | '◢' => '\\',
+        '◥' | '◣' => '/',
+        '─' => '-',
+        '│' => '|',
+        _ => '?',
+    }
+}
+
+/// terminal output with every character that isn't ASCII replaced, if `enabled`
+pub struct AsciiWriter<W: Write> {
+    out: W,
+    enabled: bool,
+    partial: Vec<u8>, // the start of a character cut off at the end of a write
+}
+
+impl<W: Write> AsciiWriter<W> {
+    pub fn new(out: W, enabled: bool) -> Self {
+        Self {
+            out,
+            enabled,
+            partial: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> Write for AsciiWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.enabled || (self.partial.is_empty() && buf.is_ascii()) {
+            return self.out.write(buf);
+        }
+        self.partial.extend_from_slice(buf);
+        let bytes = std::mem::take(&mut self.partial);
+        let (text, rest) = match std::str::from_utf8(&bytes) {
+            Ok(text) => (text, &[][..]),
+            Err(e) if e.error_len().is_none() => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                (std::str::from_utf8(valid).unwrap_or_default(), rest)
+            }
+            // not UTF-8 at all: pass it on as it is
+            Err(_) => {
+                self.out.write_all(&bytes)?;
+                return Ok(buf.len());
+            }
+        };
+        let text: String = text.chars().map(ascii).collect();
+        self.out.write_all(text.as_bytes())?;
+        self.partial = rest.to_vec();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
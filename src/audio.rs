@@ -0,0 +1,43 @@
+//! Sound effects, with the `audio` feature: a beep when the snake eats,
+//! turns or crashes, as `[audio]` in the config asks.
+//!
+//! The beep is the terminal bell, the one sound every terminal can make, so
+//! no audio library is needed. Sounds heard during a tick are rung with the
+//! next frame; a bell rings once however many events happened in a tick.
+
+use crate::config::AudioConfig;
+use snake_core::bus::Notice;
+use std::io::{self, Write};
+
+const BELL: &[u8] = b"\x07";
+
+pub struct Sounds {
+    config: AudioConfig,
+    due: bool, // something to ring with the next frame
+}
+
+impl Sounds {
+    /// the sounds `config` asks for, `None` when they are switched off
+    pub fn new(config: AudioConfig) -> Option<Self> {
+        config.enabled.then_some(Self { config, due: false })
+    }
+
+    /// note what happened in a tick
+    pub fn hear(&mut self, notices: &[Notice]) {
+        let config = &self.config;
+        self.due |= notices.iter().any(|notice| match notice {
+            Notice::Ate(_) => config.eat,
+            Notice::Turned(_) => config.turn,
+            Notice::Crashed(_) => config.crash,
+            _ => false,
+        });
+    }
+
+    /// ring the bell on `out` if anything was heard since the last frame
+    pub fn play<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        if std::mem::take(&mut self.due) {
+            out.write_all(BELL)?;
+        }
+        Ok(())
+    }
+}
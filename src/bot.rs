@@ -0,0 +1,207 @@
+//! Autopilot for `--autopilot`: a bot steering the snake to the food.
+//!
+//! The path search runs on a worker thread, so large boards never hold up
+//! the game loop. After every tick the loop hands the worker a copy of the
+//! state, and the worker searches until its time budget runs out, settling
+//! for any safe move if the food wasn't reached by then. An answer that
+//! isn't back by the next tick is dropped, and the snake keeps its previous
+//! heading.
+
+use snake_core::{Direction, GameState, Rng};
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const CHECK_EVERY: usize = 256; // cells searched between looks at the clock
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+/// bots for `simulate`, with what they do
+pub const STRATEGIES: [(&str, &str); 2] = [
+    ("greedy", "the autopilot: a shortest path to the food"),
+    ("random", "any move that doesn't crash at once"),
+];
+
+pub struct Bot {
+    states: Sender<GameState>,
+    // planned moves, tagged with the tick of the state they were planned from
+    answers: Receiver<(u64, Direction)>,
+}
+
+impl Bot {
+    /// start the worker, giving it `budget` to plan each move
+    pub fn spawn(budget: Duration) -> Self {
+        let (states, inbox) = mpsc::channel::<GameState>();
+        let (outbox, answers) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(mut state) = inbox.recv() {
+                // skip what piled up while the last search ran
+                while let Ok(newer) = inbox.try_recv() {
+                    state = newer;
+                }
+                let dir = plan(&state, Instant::now() + budget);
+                if outbox.send((state.tick, dir)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { states, answers }
+    }
+
+    /// have the worker plan the move following `state`
+    pub fn ask(&self, state: &GameState) {
+        let _ = self.states.send(state.clone());
+    }
+
+    /// the move planned from the state at `tick`, if the worker is done with it
+    pub fn answer(&self, tick: u64) -> Option<Direction> {
+        self.answers
+            .try_iter()
+            .filter(|&(planned, _)| planned == tick)
+            .map(|(_, dir)| dir)
+            .last()
+    }
+}
+
+/// the cell next to `pos` towards `dir`, if it is on a `size` board
+fn neighbour((x, y): (u16, u16), dir: Direction, size: (u16, u16)) -> Option<(u16, u16)> {
+    let pos = match dir {
+        Direction::Up => (x, y.checked_sub(1)?),
+        Direction::Down => (x, y + 1),
+        Direction::Left => (x.checked_sub(1)?, y),
+        Direction::Right => (x + 1, y),
+    };
+    (pos.0 < size.0 && pos.1 < size.1).then_some(pos)
+}
+
+/// where the head ends up moving from `pos` towards `dir`, through a portal
+/// if it steps onto one
+fn step(state: &GameState, pos: (u16, u16), dir: Direction) -> Option<(u16, u16)> {
+    let next = neighbour(pos, dir, state.size)?;
+    Some(state.portal_exit(next).unwrap_or(next))
+}
+
+/// which cells the head must not move into; the tail is free as it moves on
+fn blocked(state: &GameState) -> Vec<bool> {
+    let (width, height) = state.size;
+    let mut blocked = vec![false; usize::from(width) * usize::from(height)];
+    let mut block = |(x, y): (u16, u16)| {
+        if x < width && y < height {
+            blocked[usize::from(y) * usize::from(width) + usize::from(x)] = true;
+        }
+    };
+    let body = &state.snake.body;
+    for cell in body.iter().take(body.len().saturating_sub(1)) {
+        block(cell.pos);
+    }
+    for cell in state
+        .wall
+        .cells
+        .iter()
+        .chain(&state.hazards)
+        .chain(&state.poison)
+    {
+        block(cell.pos);
+    }
+    // a ramming snake goes straight through breakable walls
+    if state.ram == 0 {
+        for cell in &state.breakables {
+            block(cell.pos);
+        }
+    }
+    for gate in state.gates.iter().filter(|gate| gate.is_closed(state.tick)) {
+        for cell in &gate.cells {
+            block(cell.pos);
+        }
+    }
+    if let Some(boss) = &state.boss {
+        for cell in boss.cells() {
+            block(cell.pos);
+        }
+    }
+    // keep clear of the other snakes' heads too, they may move anywhere next
+    let bots = state.bots.iter().filter(|bot| !bot.crashed);
+    for rival in state.rival.iter().chain(bots) {
+        let size = state.size;
+        let head = rival.snake.head().pos;
+        let around = DIRECTIONS
+            .iter()
+            .filter_map(|&dir| neighbour(head, dir, size));
+        for pos in rival.snake.body.iter().map(|c| c.pos).chain(around) {
+            block(pos);
+        }
+    }
+    blocked
+}
+
+/// the moves from `state` that don't crash at once, straight on first
+fn safe_moves(state: &GameState, blocked: &[bool]) -> Vec<Direction> {
+    let size = state.size;
+    let index = |(x, y): (u16, u16)| usize::from(y) * usize::from(size.0) + usize::from(x);
+    let heading = state.snake.dir;
+    let head = state.snake.head().pos;
+    let mut moves = vec![heading, heading.turn_left(), heading.turn_right()];
+    moves.retain(|&dir| step(state, head, dir).is_some_and(|pos| !blocked[index(pos)]));
+    moves
+}
+
+/// the move the `strategy` bot makes from `state`, planned on the spot
+pub fn decide(strategy: &str, state: &GameState, rng: &mut Rng) -> Direction {
+    match strategy {
+        "random" => {
+            let moves = safe_moves(state, &blocked(state));
+            match moves.len() {
+                0 => state.snake.dir,
+                n => moves[(rng.next_u64() % n as u64) as usize],
+            }
+        }
+        // no rush without a frame to draw
+        _ => plan(state, Instant::now() + Duration::from_secs(60)),
+    }
+}
+
+/// the first move of a shortest path to the food, found breadth first before
+/// `deadline`; otherwise a move that doesn't crash at once, straight on if possible
+fn plan(state: &GameState, deadline: Instant) -> Direction {
+    let size = state.size;
+    let index = |(x, y): (u16, u16)| usize::from(y) * usize::from(size.0) + usize::from(x);
+    let mut blocked = blocked(state);
+    let heading = state.snake.dir;
+    let head = state.snake.head().pos;
+    let moves = safe_moves(state, &blocked);
+    let safe = match moves.first() {
+        Some(&dir) => dir,
+        None => return heading,
+    };
+    // cells to search from, with the first move taken to reach them
+    let mut queue = VecDeque::new();
+    for &dir in &moves {
+        let pos = step(state, head, dir).unwrap_or(head);
+        blocked[index(pos)] = true;
+        queue.push_back((pos, dir));
+    }
+    let mut searched: usize = 0;
+    while let Some((pos, first)) = queue.pop_front() {
+        if state.food.iter().any(|food| food.pos == pos) {
+            return first;
+        }
+        searched += 1;
+        if searched.is_multiple_of(CHECK_EVERY) && Instant::now() > deadline {
+            break;
+        }
+        for dir in DIRECTIONS {
+            if let Some(next) = step(state, pos, dir) {
+                if !blocked[index(next)] {
+                    blocked[index(next)] = true;
+                    queue.push_back((next, first));
+                }
+            }
+        }
+    }
+    safe
+}
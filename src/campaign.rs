@@ -0,0 +1,176 @@
+//! Campaign levels: levels with medal targets, played from a level-select screen.
+//!
+//! A level joins the campaign by giving targets for each medal in its level
+//! file. A medal is earned by eating its number of foods within its time, in
+//! seconds of game time; the run is complete once the gold target's food
+//! count is reached.
+//!
+//! ```toml
+//! [medals]
+//! bronze = { food = 5, time = 60 }
+//! silver = { food = 10, time = 90 }
+//! gold = { food = 15, time = 100 }
+//! ```
+
+use crate::menu::select;
+use crate::speedrun::format_time;
+use crossterm::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+impl Medal {
+    pub fn name(self) -> &'static str {
+        match self {
+            Medal::Bronze => "bronze",
+            Medal::Silver => "silver",
+            Medal::Gold => "gold",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Target {
+    pub food: u32,
+    pub time: f64, // seconds
+}
+
+impl Target {
+    fn time(&self) -> Duration {
+        Duration::from_secs_f64(self.time.max(0.0))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Medals {
+    pub bronze: Target,
+    pub silver: Target,
+    pub gold: Target,
+}
+
+impl Medals {
+    fn targets(&self) -> [(Medal, Target); 3] {
+        [
+            (Medal::Bronze, self.bronze),
+            (Medal::Silver, self.silver),
+            (Medal::Gold, self.gold),
+        ]
+    }
+
+    fn describe(&self) -> String {
+        let parts: Vec<_> = self
+            .targets()
+            .iter()
+            .map(|(medal, t)| format!("{} {} in {}", medal.name(), t.food, format_time(t.time())))
+            .collect();
+        parts.join("  ")
+    }
+}
+
+/// a run on a campaign level, timing when each food count was reached
+pub struct Attempt {
+    pub level: String,
+    medals: Medals,
+    reached: Vec<Duration>, // reached[n - 1]: when the n-th food was eaten
+}
+
+impl Attempt {
+    pub fn new(level: &str, medals: Medals) -> Self {
+        Self {
+            level: level.to_string(),
+            medals,
+            reached: Vec::new(),
+        }
+    }
+
+    /// note the food count after a tick at game time `time`
+    pub fn update(&mut self, food: u32, time: Duration) {
+        while (self.reached.len() as u32) < food {
+            self.reached.push(time);
+        }
+    }
+
+    /// the best medal earned so far
+    pub fn medal(&self) -> Option<Medal> {
+        self.medals
+            .targets()
+            .iter()
+            .filter(|(_, t)| {
+                let reached = t.food.checked_sub(1).map(|i| self.reached.get(i as usize));
+                match reached {
+                    Some(Some(&at)) => at <= t.time(),
+                    Some(None) => false,
+                    None => true, // no food needed
+                }
+            })
+            .map(|(medal, _)| *medal)
+            .max()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.reached.len() as u32 >= self.medals.gold.food
+    }
+
+    /// progress towards the next medal that can still be earned
+    pub fn hud(&self, time: Duration) -> String {
+        let food = self.reached.len() as u32;
+        let next = self
+            .medals
+            .targets()
+            .into_iter()
+            .filter(|(medal, t)| Some(*medal) > self.medal() && time <= t.time())
+            .find(|(_, t)| t.food > food);
+        let goal = match next {
+            Some((medal, t)) => format!(
+                "next: {} {} in {}",
+                medal.name(),
+                t.food,
+                format_time(t.time())
+            ),
+            None => "no more medals in reach".to_string(),
+        };
+        format!(
+            "{}  food {}  {}  {}",
+            self.level,
+            food,
+            format_time(time),
+            goal
+        )
+    }
+}
+
+/// a level the player can pick on the campaign screen
+pub struct CampaignLevel {
+    pub name: String,
+    pub medals: Medals,
+}
+
+/// let the player pick a level; `None` if they backed out
+pub fn level_select<T: Write>(
+    buffer: &mut T,
+    levels: &[&CampaignLevel],
+    earned: &BTreeMap<String, Medal>,
+) -> Result<Option<usize>> {
+    let entries: Vec<_> = levels
+        .iter()
+        .map(|level| {
+            let medal = earned.get(&level.name).map_or("-", |m| m.name());
+            format!(
+                "{:<16} {:<7} {}",
+                level.name,
+                medal,
+                level.medals.describe()
+            )
+        })
+        .collect();
+    select(buffer, "Campaign", &entries)
+}
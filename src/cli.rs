@@ -0,0 +1,337 @@
+use crate::bot::STRATEGIES;
+use crate::config::{BoardConfig, SpeedConfig};
+use crate::difficulty::DIFFICULTIES;
+use clap::builder::{PossibleValue, PossibleValuesParser};
+use clap::{error::ErrorKind, ArgAction, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use snake_core::level::MIN_BOARD;
+use snake_core::modes::MODES;
+use snake_core::{Direction, Spawn};
+use std::io;
+use std::path::PathBuf;
+
+/// A snake game in the terminal
+#[derive(Parser)]
+#[command(version, disable_version_flag = true)]
+pub struct Cli {
+    /// Print version
+    #[arg(short = 'V', long, action = ArgAction::SetTrue)]
+    pub version: bool,
+    /// With --version, also list the cargo features this binary was built with
+    #[arg(long, requires = "version")]
+    pub features: bool,
+    /// Rules to play by
+    #[arg(long, global = true, value_name = "MODE", value_parser = mode_parser())]
+    pub mode: Option<String>,
+    /// Difficulty preset: tick rate, starting length and points per food
+    #[arg(long, global = true, value_name = "LEVEL", value_parser = difficulty_parser())]
+    pub difficulty: Option<String>,
+    /// Practice mode: rewind the last few ticks with `z`; runs are unranked
+    #[arg(long, global = true)]
+    pub practice: bool,
+    /// Speedrun mode: race to lengths 10, 25 and 50, or with `score` to 10, 25 and 50 points,
+    /// with live splits against your best
+    #[arg(
+        long,
+        global = true,
+        value_name = "GOAL",
+        num_args = 0..=1,
+        default_missing_value = "length",
+        value_parser = ["length", "score"],
+        conflicts_with = "practice"
+    )]
+    pub speedrun: Option<String>,
+    /// Pick a campaign level and play it for bronze, silver and gold medals
+    #[arg(long, global = true, conflicts_with_all = ["practice", "speedrun", "level"])]
+    pub campaign: bool,
+    /// Play through the built-in stages, moving on to the next layout at each target score
+    #[arg(long, global = true, conflicts_with_all = ["campaign", "level", "width", "height"])]
+    pub stages: bool,
+    /// Maze mode: play through corridors generated from the seed, a new maze every run
+    #[arg(long, global = true, conflicts_with_all = ["campaign", "stages", "level", "map"])]
+    pub maze: bool,
+    /// Play a session of N rounds, with a scoreboard between rounds
+    #[arg(long, global = true, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub best_of: Option<u16>,
+    /// After every run, write the results of all runs so far to FILE as JSON
+    #[arg(long, global = true, value_name = "FILE")]
+    pub results_json: Option<PathBuf>,
+    /// After every run, write the results of all runs so far to FILE as CSV
+    #[arg(long, global = true, value_name = "FILE")]
+    pub results_csv: Option<PathBuf>,
+    /// Write one CSV row per tick to FILE: head, length, score, ticks since food, render time
+    #[arg(long, global = true, value_name = "FILE")]
+    pub tick_csv: Option<PathBuf>,
+    /// Start paused in frame-step mode: space advances one tick, `f` toggles
+    #[arg(long, global = true)]
+    pub frame_step: bool,
+    /// Play the tutorial again (it starts automatically on a fresh profile)
+    #[arg(long, global = true)]
+    pub tutorial: bool,
+    /// Play on a level: a level file, or the name of a built-in or mod level
+    #[arg(long, global = true, value_name = "FILE")]
+    pub level: Option<PathBuf>,
+    /// Play on a plain-text map: `#` wall, `.` empty, `S` start, `F` food zone
+    #[arg(long, global = true, value_name = "FILE", conflicts_with_all = ["level", "campaign", "stages", "width", "height"])]
+    pub map: Option<PathBuf>,
+    /// Starting length of the snake
+    #[arg(long, global = true, value_name = "N")]
+    pub length: Option<u16>,
+    /// Starting head position in board cells, instead of the level's spawn points
+    #[arg(long, global = true, value_name = "X,Y", value_parser = parse_position)]
+    pub spawn: Option<(u16, u16)>,
+    /// Starting direction: up, down, left or right
+    #[arg(long, global = true, value_name = "DIR", value_parser = parse_direction)]
+    pub direction: Option<Direction>,
+    /// Board width in cells, walls included; fits the terminal by default
+    #[arg(long, global = true, value_name = "N", value_parser = board_side(), conflicts_with_all = ["level", "campaign"])]
+    pub width: Option<u16>,
+    /// Board height in cells, walls included; fits the terminal by default
+    #[arg(long, global = true, value_name = "N", value_parser = board_side(), conflicts_with_all = ["level", "campaign"])]
+    pub height: Option<u16>,
+    /// Draw each board cell as a WxH block of characters, e.g. 4x2 for large screens
+    #[arg(long, global = true, value_name = "WxH", value_parser = parse_scale)]
+    pub scale: Option<(u16, u16)>,
+    /// Color theme: classic, solarized, monochrome, neon, sunset, colorblind or one from a mod
+    #[arg(long, global = true, value_name = "NAME")]
+    pub theme: Option<String>,
+    /// High contrast: everything in bold white on black, with a shape of its own for every tile
+    #[arg(long, global = true, conflicts_with = "colorblind")]
+    pub no_color: bool,
+    /// Colorblind-friendly drawing: the colorblind theme, and a shape of its own for every tile
+    #[arg(long, global = true, conflicts_with = "theme")]
+    pub colorblind: bool,
+    /// Draw with ASCII characters only, for terminals or fonts without block characters
+    #[arg(long, global = true)]
+    pub ascii: bool,
+    /// Language of the title, score and messages: en, zh or one from the lang folder;
+    /// taken from the locale by default
+    #[arg(long, global = true, value_name = "CODE")]
+    pub lang: Option<String>,
+    /// Load custom rules from a Rhai script
+    #[cfg(feature = "scripting")]
+    #[arg(long, global = true, value_name = "FILE")]
+    pub script: Option<PathBuf>,
+    /// Steer by the timed moves in FILE (`-` for stdin) instead of the arrow keys
+    #[arg(long, global = true, value_name = "FILE")]
+    pub input_script: Option<PathBuf>,
+    /// Start the first run from SEED, to play the same food and board again or share it
+    #[arg(long, global = true, value_name = "SEED")]
+    pub seed: Option<u64>,
+    /// Starting speed in ticks per second, instead of the difficulty's or the configured one
+    #[arg(long, global = true, value_name = "TICKS", value_parser = parse_speed)]
+    pub speed: Option<f64>,
+    /// Let a bot steer the snake to the food; runs are unranked
+    #[arg(long, global = true, conflicts_with_all = ["input_script", "campaign"])]
+    pub autopilot: bool,
+    /// Two players on one keyboard: a second snake steered with WASD; runs are unranked
+    #[arg(long, global = true, conflicts_with_all = ["autopilot", "input_script", "campaign", "speedrun"])]
+    pub two_player: bool,
+    /// Race a computer-controlled snake for the food
+    #[arg(long, global = true, conflicts_with = "two_player")]
+    pub opponent: bool,
+    /// Battle arena: fight N (2 or 3) computer snakes, scoring for each one that crashes into you
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        value_parser = clap::value_parser!(u8).range(2..=3),
+        conflicts_with_all = ["two_player", "opponent", "campaign", "speedrun", "maze"]
+    )]
+    pub battle: Option<u8>,
+    /// Host a LAN game for two on PORT (7878 by default) and wait for a player to --join
+    #[arg(
+        long,
+        global = true,
+        value_name = "PORT",
+        num_args = 0..=1,
+        default_missing_value = "7878",
+        conflicts_with_all = ["two_player", "opponent", "battle", "autopilot", "input_script", "campaign", "speedrun"]
+    )]
+    pub host: Option<u16>,
+    /// Join the LAN game hosted at ADDR (HOST:PORT) and steer its second snake
+    #[arg(long, global = true, value_name = "ADDR", conflicts_with = "host")]
+    pub join: Option<String>,
+    /// Also write the replay file of each finished run to FILE
+    #[arg(long, global = true, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+    /// Record every key pressed to FILE, for replaying with --replay-keys
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        hide = true,
+        conflicts_with = "replay_keys"
+    )]
+    pub record_keys: Option<PathBuf>,
+    /// Drive the game with the keys recorded in FILE instead of the keyboard
+    #[arg(long, global = true, value_name = "FILE", hide = true)]
+    pub replay_keys: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Play, as without a command
+    Play,
+    /// Print the personal bests, coins, campaign medals and high scores
+    Stats,
+    /// Download and install the latest release binary
+    #[cfg(feature = "net")]
+    Update,
+    /// Print a shell completion script to stdout
+    Completions { shell: Shell },
+    /// Print the man page to stdout
+    Manpage,
+    /// List installed mods and the themes, glyphs and levels available
+    Mods,
+    /// Print the path of the config file
+    Config {
+        /// Write a commented config file with every setting at its default, if there is none yet
+        #[arg(long)]
+        init: bool,
+    },
+    /// Print the board as plain text after TICKS ticks of a run started with --seed (0 if unset)
+    Snapshot {
+        #[arg(long, default_value_t = 0)]
+        ticks: u64,
+    },
+    /// Watch the replay of a run saved with --record (or last.replay in the data directory)
+    Replay { file: PathBuf },
+    /// Let a bot play GAMES games without drawing them and print the totals
+    Simulate {
+        /// Which bot plays
+        #[arg(long, default_value = "greedy", value_parser = bot_parser())]
+        bot: String,
+        #[arg(long, default_value_t = 100)]
+        games: u32,
+    },
+    /// Edit a plain-text map (see --map), creating it --width by --height (30 by 20) if missing
+    Edit { file: PathBuf },
+}
+
+fn mode_parser() -> PossibleValuesParser {
+    PossibleValuesParser::new(
+        MODES
+            .iter()
+            .map(|mode| PossibleValue::new(mode.name()).help(mode.description())),
+    )
+}
+
+fn bot_parser() -> PossibleValuesParser {
+    PossibleValuesParser::new(
+        STRATEGIES
+            .iter()
+            .map(|&(name, help)| PossibleValue::new(name).help(help)),
+    )
+}
+
+fn difficulty_parser() -> PossibleValuesParser {
+    PossibleValuesParser::new(
+        DIFFICULTIES
+            .iter()
+            .map(|d| PossibleValue::new(d.name()).help(d.description())),
+    )
+}
+
+fn board_side() -> clap::builder::RangedI64ValueParser<u16> {
+    clap::value_parser!(u16).range(i64::from(MIN_BOARD)..)
+}
+
+fn parse_position(arg: &str) -> Result<(u16, u16), String> {
+    let (x, y) = arg.split_once(',').ok_or("expected X,Y")?;
+    let coord = |v: &str| v.trim().parse::<u16>().map_err(|e| e.to_string());
+    Ok((coord(x)?, coord(y)?))
+}
+
+fn parse_scale(arg: &str) -> Result<(u16, u16), String> {
+    let (w, h) = arg.split_once('x').ok_or("expected WxH")?;
+    let size = |v: &str| v.trim().parse::<u16>().map_err(|e| e.to_string());
+    Ok((size(w)?, size(h)?))
+}
+
+fn parse_speed(arg: &str) -> Result<f64, String> {
+    match arg.parse::<f64>().map_err(|e| e.to_string())? {
+        speed if (0.1..=1000.0).contains(&speed) => Ok(speed),
+        _ => Err("expected between 0.1 and 1000 ticks per second".to_string()),
+    }
+}
+
+fn parse_direction(arg: &str) -> Result<Direction, String> {
+    match arg.to_ascii_lowercase().as_str() {
+        "up" => Ok(Direction::Up),
+        "down" => Ok(Direction::Down),
+        "left" => Ok(Direction::Left),
+        "right" => Ok(Direction::Right),
+        _ => Err("expected up, down, left or right".to_string()),
+    }
+}
+
+impl Cli {
+    /// override the configured board size with the flags given on the command line
+    pub fn apply_board(&self, board: &mut BoardConfig) {
+        if let Some(width) = self.width {
+            board.width = Some(width);
+        }
+        if let Some(height) = self.height {
+            board.height = Some(height);
+        }
+    }
+
+    /// override the configured starting speed with `--speed`
+    pub fn apply_speed(&self, speed: &mut SpeedConfig) {
+        if let Some(ticks) = self.speed {
+            speed.start_ms = Some((1000.0 / ticks).round().max(1.0) as u64);
+        }
+    }
+
+    /// override the configured spawn with the flags given on the command line
+    pub fn apply_spawn(&self, spawn: &mut Spawn) {
+        if let Some(len) = self.length {
+            spawn.len = len;
+        }
+        if let Some(pos) = self.spawn {
+            spawn.pos = pos;
+        }
+        if let Some(dir) = self.direction {
+            spawn.dir = dir;
+        }
+    }
+}
+
+/// report an invalid combination of options the way clap reports parse errors
+pub fn exit_with_error(message: &str) -> ! {
+    crate::tty::restore();
+    Cli::command()
+        .error(ErrorKind::InvalidValue, message)
+        .exit()
+}
+
+pub fn print_completions(shell: Shell) {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, name, &mut io::stdout());
+}
+
+pub fn print_manpage() -> io::Result<()> {
+    clap_mangen::Man::new(Cli::command()).render(&mut io::stdout())
+}
+
+const FEATURES: &[(&str, bool)] = &[
+    ("net", cfg!(feature = "net")),
+    ("audio", cfg!(feature = "audio")),
+    ("leaderboard", cfg!(feature = "leaderboard")),
+    ("scripting", cfg!(feature = "scripting")),
+];
+
+pub fn print_version(features: bool) {
+    println!("rust-snake {}", env!("CARGO_PKG_VERSION"));
+    if features {
+        let list: Vec<_> = FEATURES
+            .iter()
+            .map(|(name, on)| format!("{}{}", if *on { '+' } else { '-' }, name))
+            .collect();
+        println!("features: {}", list.join(" "));
+    }
+}
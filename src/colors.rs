@@ -0,0 +1,306 @@
+//! How many colors the terminal shows, and the nearest it has to the rest.
+//!
+//! Themes may use any RGB color, but serial lines, old terminals and some
+//! SSH sessions only know 256 or 16 colors, and a dumb terminal none. The
+//! depth is detected from `NO_COLOR`, `COLORTERM` and `TERM` unless it is set
+//! with `[display] colors`. A [`ColorWriter`] around the terminal output then
+//! rewrites the color codes of whatever is drawn to ones the terminal knows.
+//!
+//! The high-contrast depth, `--no-color`, turns every color into bold white
+//! on black instead, for low vision and terminals whose colors are broken.
+//! Tiles are then told apart by their shapes alone.
+
+use crossterm::style::Color;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::io::{self, Write};
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorDepth {
+    /// detect it from the environment
+    #[default]
+    Auto,
+    /// any RGB color
+    Truecolor,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "16")]
+    Ansi16,
+    /// no colors at all, only bold and the like
+    None,
+    /// every color drawn as bold white on black
+    Contrast,
+}
+
+// the usual RGB values of the 16 basic colors, as xterm shows them
+const BASIC: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+// the levels of each channel in the 6x6x6 color cube of the 256 colors
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorDepth {
+    /// the configured depth, or the one the environment suggests for `Auto`
+    pub fn resolve(self) -> Self {
+        if self != ColorDepth::Auto {
+            return self;
+        }
+        let var = |name| env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        if !var("NO_COLOR").is_empty() || term == "dumb" {
+            ColorDepth::None
+        } else if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit")
+            || !var("WT_SESSION").is_empty()
+        {
+            ColorDepth::Truecolor
+        } else if term.contains("256") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).unsigned_abs().pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// the RGB value of color `n` of the 256
+fn rgb_of(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => BASIC[usize::from(n)],
+        16..=231 => {
+            let n = n - 16;
+            let level = |i: u8| CUBE[usize::from(i)];
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        _ => {
+            let grey = 8 + 10 * (n - 232);
+            (grey, grey, grey)
+        }
+    }
+}
+
+/// the RGB value of any color but `Reset`
+pub fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    let basic = match color {
+        Color::Rgb { r, g, b } => return Some((r, g, b)),
+        Color::AnsiValue(n) => return Some(rgb_of(n)),
+        Color::Reset => return None,
+        Color::Black => 0,
+        Color::DarkRed => 1,
+        Color::DarkGreen => 2,
+        Color::DarkYellow => 3,
+        Color::DarkBlue => 4,
+        Color::DarkMagenta => 5,
+        Color::DarkCyan => 6,
+        Color::Grey => 7,
+        Color::DarkGrey => 8,
+        Color::Red => 9,
+        Color::Green => 10,
+        Color::Yellow => 11,
+        Color::Blue => 12,
+        Color::Magenta => 13,
+        Color::Cyan => 14,
+        Color::White => 15,
+    };
+    Some(BASIC[basic])
+}
+
+/// the color `t` of the way from `a` to `b`
+pub fn blend(a: Color, b: Color, t: f64) -> Color {
+    match (rgb(a), rgb(b)) {
+        (Some(a), Some(b)) => {
+            let mix =
+                |x: u8, y: u8| (f64::from(x) + (f64::from(y) - f64::from(x)) * t).round() as u8;
+            Color::Rgb {
+                r: mix(a.0, b.0),
+                g: mix(a.1, b.1),
+                b: mix(a.2, b.2),
+            }
+        }
+        _ => a,
+    }
+}
+
+/// the nearest of the 256 colors, from the cube or the greys
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |c: u8| {
+        (0..6u8)
+            .min_by_key(|&i| CUBE[usize::from(i)].abs_diff(c))
+            .unwrap_or(0)
+    };
+    let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+    let mean = ((u16::from(rgb.0) + u16::from(rgb.1) + u16::from(rgb.2)) / 3) as u8;
+    let grey = 232 + (mean.saturating_sub(3) / 10).min(23);
+    if distance(rgb_of(grey), rgb) < distance(rgb_of(cube), rgb) {
+        grey
+    } else {
+        cube
+    }
+}
+
+/// the nearest of the 16 basic colors
+fn nearest_16(rgb: (u8, u8, u8)) -> u8 {
+    (0..16u8)
+        .min_by_key(|&i| distance(BASIC[usize::from(i)], rgb))
+        .unwrap_or(0)
+}
+
+/// terminal output with its color codes brought down to `depth`; the output is
+/// rewritten when flushed
+pub struct ColorWriter<W: Write> {
+    out: W,
+    depth: ColorDepth,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> ColorWriter<W> {
+    pub fn new(out: W, depth: ColorDepth) -> Self {
+        let depth = depth.resolve();
+        // high contrast starts out white on black, before anything is drawn
+        let pending = match depth {
+            ColorDepth::Contrast => b"\x1b[0m".to_vec(),
+            _ => Vec::new(),
+        };
+        Self {
+            out,
+            depth,
+            pending,
+        }
+    }
+
+    /// the parameters of a color from `params` for the depth, `background`
+    /// or not; `None` if they don't describe a color
+    fn color(&self, params: &mut impl Iterator<Item = u8>, background: bool) -> Option<String> {
+        let rgb = match params.next()? {
+            5 => {
+                let n = params.next()?;
+                if self.depth != ColorDepth::Ansi16 {
+                    return Some(format!("{};5;{}", if background { 48 } else { 38 }, n));
+                }
+                rgb_of(n)
+            }
+            2 => (params.next()?, params.next()?, params.next()?),
+            _ => return None,
+        };
+        Some(match self.depth {
+            ColorDepth::Ansi256 => {
+                format!(
+                    "{};5;{}",
+                    if background { 48 } else { 38 },
+                    nearest_256(rgb)
+                )
+            }
+            _ => {
+                let n = nearest_16(rgb);
+                let base = match (background, n < 8) {
+                    (false, true) => 30,
+                    (false, false) => 90 - 8,
+                    (true, true) => 40,
+                    (true, false) => 100 - 8,
+                };
+                (base + n).to_string()
+            }
+        })
+    }
+
+    /// rewrite the select graphic rendition `params` (the part of `ESC [ ... m`
+    /// between the brackets) into `out`
+    fn rewrite_sgr(&self, params: &str, out: &mut Vec<u8>) {
+        let mut numbers = params.split(';').map(|p| p.parse::<u8>().unwrap_or(0));
+        let mut kept = Vec::new();
+        let contrast = |background: bool| String::from(if background { "40" } else { "1;97" });
+        while let Some(n) = numbers.next() {
+            let is_color = matches!(n, 30..=39 | 40..=49 | 90..=97 | 100..=107);
+            let is_background = matches!(n, 40..=49 | 100..=107);
+            match (n, self.depth) {
+                (38 | 48, depth) => {
+                    let color = self.color(&mut numbers, n == 48);
+                    match depth {
+                        ColorDepth::None => (),
+                        ColorDepth::Contrast => kept.push(contrast(n == 48)),
+                        _ => kept.extend(color),
+                    }
+                }
+                (_, ColorDepth::None) if is_color => (),
+                (_, ColorDepth::Contrast) if is_color => kept.push(contrast(is_background)),
+                // a reset goes back to white on black, not the terminal's colors
+                (0, ColorDepth::Contrast) => {
+                    kept.push(format!("0;{};{}", contrast(false), contrast(true)))
+                }
+                (n, _) => kept.push(n.to_string()),
+            }
+        }
+        // `ESC [ m` would reset everything, not just the dropped colors
+        if !kept.is_empty() {
+            out.extend_from_slice(format!("\x1b[{}m", kept.join(";")).as_bytes());
+        }
+    }
+
+    fn rewrite(&self, bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i..].starts_with(b"\x1b[") {
+                let end = bytes[i + 2..]
+                    .iter()
+                    .position(|b| (0x40..=0x7e).contains(b))
+                    .map(|end| i + 2 + end);
+                if let Some(end) = end {
+                    match (bytes[end], std::str::from_utf8(&bytes[i + 2..end])) {
+                        (b'm', Ok(params)) => self.rewrite_sgr(params, &mut out),
+                        _ => out.extend_from_slice(&bytes[i..=end]),
+                    }
+                    i = end + 1;
+                    continue;
+                }
+            }
+            out.push(bytes[i]);
+            i += 1;
+        }
+        out
+    }
+}
+
+impl<W: Write> Write for ColorWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.depth == ColorDepth::Truecolor {
+            return self.out.write(buf);
+        }
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let pending = std::mem::take(&mut self.pending);
+        if !pending.is_empty() {
+            self.out.write_all(&self.rewrite(&pending))?;
+        }
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Drop for ColorWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
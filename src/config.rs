@@ -0,0 +1,440 @@
+use crate::colors::ColorDepth;
+use crate::effects::WeatherKind;
+use crate::input;
+use crate::CELL_SZ;
+use crossterm::event::KeyCode;
+use serde::{Deserialize, Serialize};
+use snake_core::level::MIN_BOARD;
+use snake_core::{Direction, Spawn, TailRule};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// user settings stored in `config.toml`
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub game: GameConfig,
+    pub snake: SnakeConfig,
+    pub telemetry: TelemetryConfig,
+    pub leaderboard: LeaderboardConfig,
+    pub plugins: PluginConfig,
+    pub rules: RulesConfig,
+    pub controls: ControlsConfig,
+    pub display: DisplayConfig,
+    pub skin: SkinConfig,
+    pub board: BoardConfig,
+    pub keys: KeysConfig,
+    pub speed: SpeedConfig,
+    pub audio: AudioConfig,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    /// pause after this many seconds without input or score change, 0 to never
+    pub idle_pause_secs: u64,
+    /// color theme, built in or from a mod
+    pub theme: String,
+    /// glyph set, built in or from a mod
+    pub glyphs: String,
+    /// ambient rain or snow around the board
+    pub weather: WeatherKind,
+    /// race a ghost of the best run from the same start
+    pub ghost: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            idle_pause_secs: 60,
+            theme: "classic".to_string(),
+            glyphs: "blocks".to_string(),
+            weather: WeatherKind::Off,
+            ghost: true,
+        }
+    }
+}
+
+/// how the snake starts
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnakeConfig {
+    pub length: u16,
+    /// head position in board cells, the middle of the board if unset
+    pub position: Option<(u16, u16)>,
+    pub direction: Direction,
+}
+
+impl Default for SnakeConfig {
+    fn default() -> Self {
+        Self {
+            length: 3,
+            position: None,
+            direction: Direction::Right,
+        }
+    }
+}
+
+impl SnakeConfig {
+    pub fn spawn(&self, board: (u16, u16)) -> Spawn {
+        Spawn {
+            pos: self.position.unwrap_or(Spawn::centered(board).pos),
+            dir: self.direction,
+            len: self.length,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    /// `None` until the player has answered the consent prompt
+    pub enabled: Option<bool>,
+    /// where batched stats are posted; nothing is sent without one
+    pub endpoint: Option<String>,
+}
+
+impl TelemetryConfig {
+    pub fn needs_consent(&self) -> bool {
+        self.enabled.is_none() && self.endpoint.is_some()
+    }
+}
+
+/// sound effects, used with the `audio` feature
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    pub enabled: bool,
+    /// a beep when the snake eats
+    pub eat: bool,
+    /// a beep on every turn
+    pub turn: bool,
+    /// a beep when the snake crashes
+    pub crash: bool,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            eat: true,
+            turn: false,
+            crash: true,
+        }
+    }
+}
+
+/// the online leaderboard, used with the `leaderboard` feature
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LeaderboardConfig {
+    /// where scores are posted and the global top ten is fetched from
+    pub endpoint: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct RulesConfig {
+    /// whether moving into the cell the tail is leaving is a bite
+    pub tail: TailRule,
+    /// food pellets on the board at once
+    pub food: usize,
+    /// rocks scattered over the board at the start, more on harder difficulties
+    pub obstacles: bool,
+    /// poison pellets on the board at once
+    pub poison: usize,
+    /// pairs of portals put at random spots at the start
+    pub portals: usize,
+    /// what food does between meals, `still` or `wander`; by default as the difficulty has it
+    pub food_behavior: Option<String>,
+    /// drop speed, slow-down, double-points and shrink pickups now and then
+    pub power_ups: bool,
+    /// play wrap mode unless `--mode` asks for another
+    pub wrap: bool,
+}
+
+impl Default for RulesConfig {
+    fn default() -> Self {
+        Self {
+            tail: TailRule::default(),
+            food: 1,
+            poison: 0,
+            obstacles: false,
+            portals: 0,
+            food_behavior: None,
+            power_ups: true,
+            wrap: false,
+        }
+    }
+}
+
+/// how the snake's colors run along its body
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkinPattern {
+    /// the first color all along
+    #[default]
+    Solid,
+    /// blending from one color to the next from head to tail
+    Gradient,
+    /// one color after the other, segment by segment
+    Striped,
+}
+
+/// the look of the player's snake; unset glyphs and colors come from the
+/// glyph set and theme
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkinConfig {
+    pub head: Option<String>,
+    pub body: Option<String>,
+    pub tail: Option<String>,
+    pub head_color: Option<String>,
+    /// body colors, names or `#rrggbb`
+    pub colors: Vec<String>,
+    pub pattern: SkinPattern,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ControlScheme {
+    /// each arrow key points the snake in that direction
+    #[default]
+    Absolute,
+    /// left and right turn the snake relative to its heading
+    Relative,
+}
+
+impl ControlScheme {
+    pub fn name(self) -> &'static str {
+        match self {
+            ControlScheme::Absolute => "absolute",
+            ControlScheme::Relative => "relative",
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlsConfig {
+    pub scheme: ControlScheme,
+}
+
+/// what a key does during a run
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Steer(Direction),
+    /// the second snake of a two-player game
+    SteerTwo(Direction),
+    Pause,
+    Quit,
+    Restart,
+    Save,
+}
+
+/// keys bound to each action, named as in key macros (`up`, `w`, `esc`, `f5`)
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeysConfig {
+    pub up: Vec<String>,
+    pub down: Vec<String>,
+    pub left: Vec<String>,
+    pub right: Vec<String>,
+    pub pause: Vec<String>,
+    pub quit: Vec<String>,
+    /// end the run unscored and start a new one
+    pub restart: Vec<String>,
+    /// write the run to the quicksave slot, to continue it from the title screen
+    pub save: Vec<String>,
+    /// player two's keys, taking over from the ones above in a two-player game
+    pub p2_up: Vec<String>,
+    pub p2_down: Vec<String>,
+    pub p2_left: Vec<String>,
+    pub p2_right: Vec<String>,
+}
+
+impl Default for KeysConfig {
+    fn default() -> Self {
+        let keys = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        Self {
+            up: keys(&["up", "w", "k"]),
+            down: keys(&["down", "s", "j"]),
+            left: keys(&["left", "a", "h"]),
+            right: keys(&["right", "d", "l"]),
+            pause: keys(&["p", "esc"]),
+            quit: keys(&["q"]),
+            restart: keys(&["r"]),
+            save: keys(&["f2"]),
+            p2_up: keys(&["w"]),
+            p2_down: keys(&["s"]),
+            p2_left: keys(&["a"]),
+            p2_right: keys(&["d"]),
+        }
+    }
+}
+
+impl KeysConfig {
+    /// the action bound to `code`, the first one listed if several are;
+    /// unknown key names bind nothing, player two's keys only bind in a
+    /// `two_player` game
+    pub fn action(&self, code: KeyCode, two_player: bool) -> Option<Action> {
+        let second = [
+            (&self.p2_up, Action::SteerTwo(Direction::Up)),
+            (&self.p2_down, Action::SteerTwo(Direction::Down)),
+            (&self.p2_left, Action::SteerTwo(Direction::Left)),
+            (&self.p2_right, Action::SteerTwo(Direction::Right)),
+        ];
+        let second = second.into_iter().filter(|_| two_player);
+        second
+            .chain([
+                (&self.up, Action::Steer(Direction::Up)),
+                (&self.down, Action::Steer(Direction::Down)),
+                (&self.left, Action::Steer(Direction::Left)),
+                (&self.right, Action::Steer(Direction::Right)),
+                (&self.pause, Action::Pause),
+                (&self.quit, Action::Quit),
+                (&self.restart, Action::Restart),
+                (&self.save, Action::Save),
+            ])
+            .find(|(names, _)| names.iter().any(|name| input::key_code(name) == Some(code)))
+            .map(|(_, action)| action)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    /// terminal columns and rows drawn for each board cell
+    pub scale: (u16, u16),
+    /// colors the terminal shows: auto, truecolor, 256, 16 or none, or contrast for bold white on black
+    pub colors: ColorDepth,
+    /// draw with ASCII characters only; detected from the locale when unset
+    pub ascii: Option<bool>,
+    /// animate the food and a crash
+    pub animations: bool,
+    /// draw in the colorblind theme and the `shapes` glyphs, whatever `[game]` picks
+    pub colorblind: bool,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            scale: CELL_SZ,
+            colors: ColorDepth::Auto,
+            ascii: None,
+            animations: true,
+            colorblind: false,
+        }
+    }
+}
+
+/// how the snake speeds up as the score grows
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeedConfig {
+    /// points per speed level, 0 to keep the starting speed
+    pub every: u16,
+    /// how much shorter each level makes a tick, in percent
+    pub percent: u8,
+    /// shortest tick in milliseconds
+    pub floor_ms: u64,
+    /// first tick in milliseconds, the difficulty's when unset
+    pub start_ms: Option<u64>,
+}
+
+impl Default for SpeedConfig {
+    fn default() -> Self {
+        Self {
+            every: 5,
+            percent: 10,
+            floor_ms: 60,
+            start_ms: None,
+        }
+    }
+}
+
+impl SpeedConfig {
+    /// the speed level reached with `score`, from 1
+    pub fn level(&self, score: u16) -> u16 {
+        match self.every {
+            0 => 1,
+            every => 1 + score / every,
+        }
+    }
+
+    /// how long a tick lasts at `score`, starting from `base` unless `start_ms` is set
+    pub fn time_step(&self, base: Duration, score: u16) -> Duration {
+        let base = self.start_ms.map_or(base, Duration::from_millis);
+        let keep = 1.0 - f64::from(self.percent.min(90)) / 100.0;
+        let step = base.mul_f64(keep.powi(i32::from(self.level(score)) - 1));
+        step.max(Duration::from_millis(self.floor_ms).min(base))
+    }
+}
+
+/// size of the board when no level is played
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoardConfig {
+    /// width in cells, walls included; fits the terminal if unset
+    pub width: Option<u16>,
+    /// height in cells, walls included; fits the terminal if unset
+    pub height: Option<u16>,
+}
+
+impl BoardConfig {
+    /// the board size, taking unset sides from `fit`
+    pub fn size(&self, fit: (u16, u16)) -> (u16, u16) {
+        (
+            self.width.unwrap_or(fit.0).max(MIN_BOARD),
+            self.height.unwrap_or(fit.1).max(MIN_BOARD),
+        )
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginConfig {
+    /// names of the enabled plugins
+    pub enabled: Vec<String>,
+}
+
+/// the commented config file `config --init` writes, every setting at its default
+const TEMPLATE: &str = include_str!("../config.example.toml");
+
+pub fn config_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "rust-snake")
+        .map(|dirs| dirs.config_dir().join("config.toml"))
+}
+
+impl Config {
+    /// load the config file, falling back to defaults if it is missing or invalid
+    pub fn load() -> Self {
+        config_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = config_path().ok_or_else(|| io::Error::other("no config directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, text)
+    }
+}
+
+/// write the commented template to `path`, never over an existing config
+pub fn write_template(path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?;
+    file.write_all(TEMPLATE.as_bytes())
+}
@@ -0,0 +1,360 @@
+//! Themes, glyph sets and level collections.
+//!
+//! Built-in content is merged with content packs found in the `mods`
+//! directory. Each pack is a folder with a `mod.toml` manifest; paths in the
+//! manifest are relative to that folder, and content with the name of an
+//! existing entry replaces it. Sound packs aren't supported: the `audio`
+//! feature only rings the terminal bell.
+//!
+//! ```toml
+//! name = "neon"
+//!
+//! [[themes]]
+//! name = "neon"
+//! snake = "cyan"
+//! food = "magenta"
+//! wall = "dark_blue"
+//! hazard = "red"
+//! bonus = "yellow"
+//! poison = "green"
+//! rival = "white"
+//! dimmed = "dark_grey"
+//! text = "white"
+//!
+//! [[glyphs]]
+//! name = "ascii"
+//! snake = "[]"
+//! food = "()"
+//! wall = "##"
+//! hazard = "XX"
+//! bonus = "$$"
+//! poison = "xx"
+//! rival = "{}"
+//!
+//! [[levels]]
+//! name = "maze"
+//! file = "levels/maze.toml"
+//! ```
+
+use crate::level::LevelData;
+use crate::save;
+use crate::snake_skin::SnakeSkin;
+use crate::theme::{self, Theme};
+use crate::{Tile, CELL_SZ};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const CROSSROADS: &str = include_str!("../levels/crossroads.toml");
+const LAIR: &str = include_str!("../levels/lair.toml");
+
+/// text drawn for each tile, one character per terminal column of a cell
+#[derive(Clone, Deserialize)]
+pub struct Glyphs {
+    pub name: String,
+    snake: String,
+    food: String,
+    wall: String,
+    hazard: String,
+    bonus: String,
+    #[serde(default = "poison_glyph")]
+    poison: String,
+    /// the second snake, drawn like the first when unset
+    #[serde(default)]
+    rival: Option<String>,
+}
+
+fn poison_glyph() -> String {
+    "xx".to_string()
+}
+
+impl Glyphs {
+    fn blocks() -> Self {
+        let block = "█".repeat(CELL_SZ.0 as usize);
+        Self {
+            name: "blocks".to_string(),
+            snake: block.clone(),
+            food: block.clone(),
+            wall: block.clone(),
+            hazard: block.clone(),
+            bonus: block,
+            poison: poison_glyph(),
+            rival: None,
+        }
+    }
+
+    pub fn ascii() -> Self {
+        Self {
+            name: "ascii".to_string(),
+            snake: "[]".to_string(),
+            food: "()".to_string(),
+            wall: "##".to_string(),
+            hazard: "XX".to_string(),
+            bonus: "$$".to_string(),
+            poison: poison_glyph(),
+            rival: None,
+        }
+    }
+
+    /// a shape of its own for every tile, so no two are told apart by color alone
+    fn shapes() -> Self {
+        Self {
+            name: "shapes".to_string(),
+            snake: "[]".to_string(),
+            food: "()".to_string(),
+            wall: "##".to_string(),
+            hazard: "^^".to_string(),
+            bonus: "$$".to_string(),
+            poison: "!!".to_string(),
+            rival: Some("{}".to_string()),
+        }
+    }
+
+    /// every glyph of the set
+    fn all(&self) -> impl Iterator<Item = &String> {
+        [
+            &self.snake,
+            &self.food,
+            &self.wall,
+            &self.hazard,
+            &self.bonus,
+            &self.poison,
+        ]
+        .into_iter()
+        .chain(&self.rival)
+    }
+
+    /// whether every glyph is plain ASCII
+    pub fn is_ascii(&self) -> bool {
+        self.all().all(|g| g.is_ascii())
+    }
+
+    pub fn glyph(&self, tile: Tile) -> &str {
+        match tile {
+            Tile::Snake => &self.snake,
+            Tile::Rival => self.rival.as_ref().unwrap_or(&self.snake),
+            Tile::Food => &self.food,
+            Tile::Wall => &self.wall,
+            Tile::Hazard => &self.hazard,
+            Tile::Bonus => &self.bonus,
+            Tile::Poison => &self.poison,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self.all().find(|g| g.chars().count() != CELL_SZ.0 as usize) {
+            Some(g) => Err(format!(
+                "glyph set {}: {:?} must be {} characters wide",
+                self.name, g, CELL_SZ.0
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// how the board is drawn
+#[derive(Clone)]
+pub struct Skin {
+    pub theme: Theme,
+    pub glyphs: Glyphs,
+    pub snake: SnakeSkin,
+}
+
+impl Default for Skin {
+    fn default() -> Self {
+        Self {
+            theme: Theme::classic(),
+            glyphs: Glyphs::blocks(),
+            snake: SnakeSkin::default(),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum LevelSource {
+    Builtin(&'static str),
+    File(PathBuf),
+}
+
+#[derive(Clone)]
+pub struct LevelEntry {
+    pub name: String,
+    source: LevelSource,
+}
+
+impl LevelEntry {
+    pub fn load(&self, tick: Duration) -> Result<LevelData, String> {
+        match &self.source {
+            LevelSource::Builtin(text) => {
+                crate::level::parse(text, tick).map_err(|e| format!("level {}: {}", self.name, e))
+            }
+            LevelSource::File(path) => crate::level::load(path, tick),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LevelSpec {
+    name: String,
+    file: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    name: String,
+    #[serde(default)]
+    themes: Vec<Theme>,
+    #[serde(default)]
+    glyphs: Vec<Glyphs>,
+    #[serde(default)]
+    levels: Vec<LevelSpec>,
+}
+
+/// a content pack found in the mods directory
+pub struct Mod {
+    pub name: String,
+    pub dir: PathBuf,
+    pub provides: Vec<String>, // "theme neon", "level maze", ...
+}
+
+/// built-in content merged with all installed mods
+pub struct Content {
+    pub themes: Vec<Theme>,
+    pub glyphs: Vec<Glyphs>,
+    pub levels: Vec<LevelEntry>,
+    pub mods: Vec<Mod>,
+}
+
+pub fn mods_dir() -> Option<PathBuf> {
+    save::data_dir().map(|dir| dir.join("mods"))
+}
+
+/// add `item`, replacing an entry of the same name
+fn merge<T>(items: &mut Vec<T>, item: T, name: impl Fn(&T) -> &str) {
+    match items.iter().position(|i| name(i) == name(&item)) {
+        Some(pos) => items[pos] = item,
+        None => items.push(item),
+    }
+}
+
+impl Content {
+    fn builtin() -> Self {
+        Self {
+            themes: theme::builtin(),
+            glyphs: vec![Glyphs::blocks(), Glyphs::ascii(), Glyphs::shapes()],
+            levels: vec![
+                LevelEntry {
+                    name: "crossroads".to_string(),
+                    source: LevelSource::Builtin(CROSSROADS),
+                },
+                LevelEntry {
+                    name: "lair".to_string(),
+                    source: LevelSource::Builtin(LAIR),
+                },
+            ],
+            mods: Vec::new(),
+        }
+    }
+
+    /// built-in content plus every mod, in directory name order
+    pub fn load() -> Result<Self, String> {
+        let mut content = Self::builtin();
+        let mut dirs: Vec<_> = mods_dir()
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.join("mod.toml").is_file())
+            .collect();
+        dirs.sort();
+        for dir in dirs {
+            content.add_mod(&dir)?;
+        }
+        Ok(content)
+    }
+
+    fn add_mod(&mut self, dir: &Path) -> Result<(), String> {
+        let path = dir.join("mod.toml");
+        let error = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+        let text = fs::read_to_string(&path).map_err(|e| error(&e))?;
+        let manifest: Manifest = toml::from_str(&text).map_err(|e| error(&e))?;
+        let mut provides = Vec::new();
+        for theme in manifest.themes {
+            provides.push(format!("theme {}", theme.name));
+            merge(&mut self.themes, theme, |t| &t.name);
+        }
+        for glyphs in manifest.glyphs {
+            glyphs.validate().map_err(|e| error(&e))?;
+            provides.push(format!("glyphs {}", glyphs.name));
+            merge(&mut self.glyphs, glyphs, |g| &g.name);
+        }
+        for level in manifest.levels {
+            provides.push(format!("level {}", level.name));
+            let entry = LevelEntry {
+                name: level.name,
+                source: LevelSource::File(dir.join(level.file)),
+            };
+            merge(&mut self.levels, entry, |l| &l.name);
+        }
+        self.mods.push(Mod {
+            name: manifest.name,
+            dir: dir.to_path_buf(),
+            provides,
+        });
+        Ok(())
+    }
+
+    pub fn level(&self, name: &str) -> Option<&LevelEntry> {
+        self.levels.iter().find(|l| l.name == name)
+    }
+
+    /// the named theme and glyph set, falling back to the defaults for unknown names
+    pub fn skin(&self, theme: &str, glyphs: &str) -> Skin {
+        let default = Skin::default();
+        Skin {
+            theme: self
+                .themes
+                .iter()
+                .find(|t| t.name == theme)
+                .cloned()
+                .unwrap_or(default.theme),
+            glyphs: self
+                .glyphs
+                .iter()
+                .find(|g| g.name == glyphs)
+                .cloned()
+                .unwrap_or(default.glyphs),
+            snake: default.snake,
+        }
+    }
+}
+
+/// print the installed mods and everything available to choose from
+pub fn print_mods(content: &Content) {
+    match mods_dir() {
+        Some(dir) => println!("mods directory: {}", dir.display()),
+        None => println!("mods directory: unavailable"),
+    }
+    for m in &content.mods {
+        println!(
+            "{} ({}): {}",
+            m.name,
+            m.dir.display(),
+            m.provides.join(", ")
+        );
+    }
+    let names = |names: Vec<&str>| names.join(", ");
+    println!(
+        "themes: {}",
+        names(content.themes.iter().map(|t| t.name.as_str()).collect())
+    );
+    println!(
+        "glyphs: {}",
+        names(content.glyphs.iter().map(|g| g.name.as_str()).collect())
+    );
+    println!(
+        "levels: {}",
+        names(content.levels.iter().map(|l| l.name.as_str()).collect())
+    );
+}
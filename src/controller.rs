@@ -0,0 +1,93 @@
+//! Who steers the rival snake: player two at the keyboard, a bot or a
+//! player who joined over the network.
+//!
+//! The bot is the one behind `--autopilot`, handed the board as the rival
+//! sees it, with the rival in the snake's place. A remote player's turns are
+//! queued like the keys pressed ahead at the keyboard, one made each tick.
+
+use crate::bot::Bot;
+use crate::net::{Link, Message};
+use crate::TURN_QUEUE;
+use snake_core::{Direction, GameState};
+use std::collections::VecDeque;
+
+pub enum Controller {
+    /// player two's keys, see `[keys]`
+    Keyboard,
+    /// a bot chasing the food
+    Bot(Bot),
+    /// a player on another machine, see `net`
+    Remote(Remote),
+}
+
+/// the link to a remote player and the turns they sent ahead
+pub struct Remote {
+    link: Link,
+    turns: VecDeque<Direction>,
+    left: bool, // they said goodbye or the connection dropped
+}
+
+impl Remote {
+    /// queue a turn the way the snake's keys are queued
+    fn steer(&mut self, dir: Direction, heading: Direction) {
+        let heading = self.turns.back().copied().unwrap_or(heading);
+        if dir != heading && dir != heading.opposite() && self.turns.len() < TURN_QUEUE {
+            self.turns.push_back(dir);
+        }
+    }
+}
+
+impl Controller {
+    pub fn remote(link: Link) -> Self {
+        Controller::Remote(Remote {
+            link,
+            turns: VecDeque::new(),
+            left: false,
+        })
+    }
+
+    pub fn takes_keys(&self) -> bool {
+        matches!(self, Controller::Keyboard)
+    }
+
+    /// whether the remote player has left the game
+    pub fn left(&self) -> bool {
+        matches!(self, Controller::Remote(remote) if remote.left)
+    }
+
+    /// the turn planned for the tick after `state`, for controllers that plan
+    pub fn turn(&mut self, state: &GameState) -> Option<Direction> {
+        match self {
+            Controller::Keyboard => None,
+            Controller::Bot(bot) => bot.answer(state.tick),
+            Controller::Remote(remote) => {
+                let heading = state.rival.as_ref()?.snake.dir;
+                let received: Vec<_> = remote.link.received().collect();
+                for message in received {
+                    match message {
+                        Message::Turn(dir) => remote.steer(dir, heading),
+                        Message::Bye => remote.left = true,
+                        Message::State(_) => (),
+                    }
+                }
+                remote.turns.pop_front()
+            }
+        }
+    }
+
+    /// start planning the rival's next move from `state`, or show it to the
+    /// remote player
+    pub fn plan(&self, state: &GameState) {
+        match self {
+            Controller::Keyboard => (),
+            Controller::Bot(bot) => {
+                if let Some(view) = state.rival_view() {
+                    bot.ask(&view);
+                }
+            }
+            Controller::Remote(remote) => {
+                let _ = remote.link.send(&Message::State(Box::new(state.clone())));
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Difficulty presets: how fast a run starts, how long the snake is and how
+//! much each food is worth.
+
+use std::time::Duration;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+pub const DIFFICULTIES: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+impl Difficulty {
+    pub fn find(name: &str) -> Option<Self> {
+        DIFFICULTIES.into_iter().find(|d| d.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "slower ticks",
+            Difficulty::Normal => "the standard game",
+            Difficulty::Hard => {
+                "faster ticks, a longer snake, wandering food and deadly poison; food is worth double"
+            }
+        }
+    }
+
+    /// how long a tick lasts before the snake speeds up
+    pub fn time_step(self) -> Duration {
+        Duration::from_millis(match self {
+            Difficulty::Easy => 200,
+            Difficulty::Normal => 150,
+            Difficulty::Hard => 100,
+        })
+    }
+
+    /// cells added to the configured starting length
+    pub fn extra_length(self) -> u16 {
+        match self {
+            Difficulty::Hard => 3,
+            _ => 0,
+        }
+    }
+
+    /// whether eating poison ends the run rather than costing length
+    pub fn poison_kills(self) -> bool {
+        self == Difficulty::Hard
+    }
+
+    /// what food does between meals, unless the config says otherwise
+    pub fn food_behavior(self) -> &'static str {
+        match self {
+            Difficulty::Hard => "wander",
+            _ => "still",
+        }
+    }
+
+    /// share of the free cells covered by rocks when obstacles are on
+    pub fn rock_density(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.01,
+            Difficulty::Normal => 0.02,
+            Difficulty::Hard => 0.04,
+        }
+    }
+
+    /// points for each food are multiplied by this
+    pub fn multiplier(self) -> u16 {
+        match self {
+            Difficulty::Hard => 2,
+            _ => 1,
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Map editor: `rust-snake edit FILE` draws a plain-text map (see `--map`)
+//! cell by cell and saves it back.
+//!
+//! The arrow keys move the cursor, space toggles a wall, `s` puts the
+//! snake's start under the cursor, `f` toggles a food zone cell, `w` saves
+//! and `q` quits. A map is only saved once it parses, so its border must be
+//! closed; other tiles of level maps in the file are kept as they are.
+
+use crate::content::Skin;
+use crate::input;
+use crate::view::View;
+use crate::{render_cell, Tile};
+use crossterm::{
+    cursor,
+    event::{Event, KeyCode, KeyEvent},
+    queue,
+    style::{self, Stylize},
+    terminal, Result,
+};
+use snake_core::level::Level;
+use snake_core::Cell;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+const UNSAVED: &str = "unsaved changes, q again to quit without saving";
+
+struct Editor {
+    rows: Vec<Vec<char>>,
+    cursor: (u16, u16),
+    changed: bool,
+    message: String,
+}
+
+impl Editor {
+    /// the map in `path`, or an empty bordered one of `size` if there is none
+    fn open(path: &Path, size: (u16, u16)) -> std::result::Result<Self, String> {
+        let rows = match fs::read_to_string(path) {
+            Ok(text) => {
+                let rows: Vec<Vec<char>> = text
+                    .lines()
+                    .filter(|row| !row.trim().is_empty())
+                    .map(|row| row.chars().collect())
+                    .collect();
+                let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+                if width == 0 {
+                    return Err(format!("{}: the map is empty", path.display()));
+                }
+                rows.into_iter()
+                    .map(|mut row| {
+                        row.resize(width, '.');
+                        row
+                    })
+                    .collect()
+            }
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                return Err(format!("{}: {}", path.display(), e))
+            }
+            Err(_) => {
+                let (w, h) = (usize::from(size.0), usize::from(size.1));
+                (0..h)
+                    .map(|y| {
+                        (0..w)
+                            .map(|x| {
+                                if x == 0 || y == 0 || x == w - 1 || y == h - 1 {
+                                    '#'
+                                } else {
+                                    '.'
+                                }
+                            })
+                            .collect()
+                    })
+                    .collect()
+            }
+        };
+        Ok(Self {
+            rows,
+            cursor: (1, 1),
+            changed: false,
+            message: String::new(),
+        })
+    }
+
+    fn size(&self) -> (u16, u16) {
+        (self.rows[0].len() as u16, self.rows.len() as u16)
+    }
+
+    fn tile_at(&mut self) -> &mut char {
+        let (x, y) = self.cursor;
+        &mut self.rows[usize::from(y)][usize::from(x)]
+    }
+
+    /// put `tile` under the cursor, or clear the cell if it is there already
+    fn toggle(&mut self, tile: char) {
+        let cell = self.tile_at();
+        *cell = if *cell == tile { '.' } else { tile };
+        self.changed = true;
+    }
+
+    /// move the start under the cursor, the map keeps a single `S`
+    fn set_start(&mut self) {
+        for tile in self.rows.iter_mut().flatten() {
+            if *tile == 'S' {
+                *tile = '.';
+            }
+        }
+        *self.tile_at() = 'S';
+        self.changed = true;
+    }
+
+    fn step(&mut self, dx: i32, dy: i32) {
+        let (w, h) = self.size();
+        let (x, y) = self.cursor;
+        self.cursor = (
+            (i32::from(x) + dx).clamp(0, i32::from(w) - 1) as u16,
+            (i32::from(y) + dy).clamp(0, i32::from(h) - 1) as u16,
+        );
+    }
+
+    fn text(&self) -> String {
+        self.rows
+            .iter()
+            .map(|row| row.iter().collect::<String>() + "\n")
+            .collect()
+    }
+
+    fn save(&mut self, path: &Path) {
+        let text = self.text();
+        self.message = match Level::parse(&text) {
+            Err(e) => format!("not saved: {}", e),
+            Ok(_) => match fs::write(path, text) {
+                Ok(()) => {
+                    self.changed = false;
+                    format!("saved to {}", path.display())
+                }
+                Err(e) => format!("not saved: {}", e),
+            },
+        };
+    }
+}
+
+fn tile(c: char) -> Option<Tile> {
+    match c {
+        '#' | '%' | '1'..='9' => Some(Tile::Wall),
+        'S' | '^' | 'v' | '<' | '>' => Some(Tile::Snake),
+        'F' => Some(Tile::Food),
+        _ => None,
+    }
+}
+
+fn render<T: Write>(buffer: &mut T, editor: &Editor, skin: &Skin, view: &View) -> Result<()> {
+    queue!(buffer, terminal::Clear(terminal::ClearType::All))?;
+    for (y, row) in editor.rows.iter().enumerate() {
+        for (x, &c) in row.iter().enumerate() {
+            if let Some(tile) = tile(c) {
+                let cell = Cell::new(x as u16, y as u16);
+                render_cell(buffer, view, &cell, tile, skin, false)?;
+            }
+        }
+    }
+    let under = editor.rows[usize::from(editor.cursor.1)][usize::from(editor.cursor.0)];
+    let glyph = match tile(under) {
+        Some(tile) => skin.glyphs.glyph(tile).with(skin.theme.color(tile, false)),
+        None => "  ".reset(),
+    };
+    view.draw(buffer, editor.cursor, glyph.reverse())?;
+    let row = view.extent(editor.size()).1 + 1;
+    let (x, y) = editor.cursor;
+    queue!(
+        buffer,
+        cursor::MoveTo(0, row),
+        style::PrintStyledContent(
+            format!(
+                "{},{}{}  space: wall  s: start  f: food zone  w: save  q: quit",
+                x,
+                y,
+                if editor.changed { " *" } else { "" }
+            )
+            .dark_grey()
+        ),
+        cursor::MoveTo(0, row + 1),
+        style::PrintStyledContent(editor.message.clone().yellow())
+    )?;
+    buffer.flush()
+}
+
+/// edit the map in `path`, starting a new one of `size` if there is none
+pub fn run<T: Write>(
+    buffer: &mut T,
+    path: &Path,
+    size: (u16, u16),
+    skin: &Skin,
+    mut view: View,
+) -> Result<()> {
+    let mut editor = Editor::open(path, size).unwrap_or_else(|e| crate::cli::exit_with_error(&e));
+    loop {
+        view.follow(editor.cursor, editor.size(), &[], terminal::size()?);
+        render(buffer, &editor, skin, &view)?;
+        let code = match input::read()? {
+            Event::Key(KeyEvent { code, .. }) => code,
+            _ => continue,
+        };
+        let quitting = std::mem::take(&mut editor.message) == UNSAVED;
+        match code {
+            KeyCode::Up => editor.step(0, -1),
+            KeyCode::Down => editor.step(0, 1),
+            KeyCode::Left => editor.step(-1, 0),
+            KeyCode::Right => editor.step(1, 0),
+            KeyCode::Char(' ') => editor.toggle('#'),
+            KeyCode::Char('f') => editor.toggle('F'),
+            KeyCode::Char('s') => editor.set_start(),
+            KeyCode::Char('w') => editor.save(path),
+            KeyCode::Char('q') | KeyCode::Esc if editor.changed && !quitting => {
+                editor.message = UNSAVED.to_string()
+            }
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            _ => (),
+        }
+    }
+    Ok(())
+}
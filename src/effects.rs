@@ -0,0 +1,120 @@
+//! Ambient visual effects drawn around the board.
+//!
+//! Effects are decoration only: they never touch the game state and are
+//! drawn before the board, outside the area the board and HUD use.
+
+use crate::render::Renderer;
+use crossterm::{style::Stylize, Result};
+use serde::{Deserialize, Serialize};
+use snake_core::Rng;
+use std::time::{Duration, Instant};
+
+const CELLS_PER_PARTICLE: usize = 60; // terminal cells per rain drop or snowflake
+const STEP: Duration = Duration::from_millis(75); // time for a rain drop to fall one row
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeatherKind {
+    #[default]
+    Off,
+    Rain,
+    Snow,
+}
+
+impl WeatherKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            WeatherKind::Off => "off",
+            WeatherKind::Rain => "rain",
+            WeatherKind::Snow => "snow",
+        }
+    }
+
+    /// the kind after this one, for cycling through them in the options screen
+    pub fn next(self) -> Self {
+        match self {
+            WeatherKind::Off => WeatherKind::Rain,
+            WeatherKind::Rain => WeatherKind::Snow,
+            WeatherKind::Snow => WeatherKind::Off,
+        }
+    }
+}
+
+/// rain or snow falling in the background
+pub struct Weather {
+    pub kind: WeatherKind,
+    particles: Vec<(u16, u16)>,
+    rng: Rng,
+    frame: u64,
+    moved: Instant, // when the particles last moved
+}
+
+impl Weather {
+    pub fn new(kind: WeatherKind) -> Self {
+        Self {
+            kind,
+            particles: Vec::new(),
+            rng: Rng::new(rand::random()),
+            frame: 0,
+            moved: Instant::now(),
+        }
+    }
+
+    /// move every particle one step on in a terminal of `width` x `height`,
+    /// once a step is due; frames are drawn more often than that
+    pub fn advance(&mut self, (width, height): (u16, u16)) {
+        if self.kind == WeatherKind::Off || width == 0 || height == 0 {
+            self.particles.clear();
+            return;
+        }
+        if self.moved.elapsed() < STEP {
+            return;
+        }
+        self.moved = Instant::now();
+        self.frame += 1;
+        let count = usize::from(width) * usize::from(height) / CELLS_PER_PARTICLE;
+        self.particles.truncate(count);
+        while self.particles.len() < count {
+            let particle = (self.rng.gen_range(0, width), self.rng.gen_range(0, height));
+            self.particles.push(particle);
+        }
+        for (x, y) in &mut self.particles {
+            match self.kind {
+                WeatherKind::Rain => *y += 1,
+                // snow drifts down at half the speed, wobbling sideways
+                WeatherKind::Snow if self.frame.is_multiple_of(2) => {
+                    *y += 1;
+                    *x = match self.rng.gen_range(0, 3) {
+                        0 => x.saturating_sub(1),
+                        1 => (*x + 1).min(width - 1),
+                        _ => *x,
+                    };
+                }
+                _ => (),
+            }
+            if *y >= height || *x >= width {
+                *x = self.rng.gen_range(0, width);
+                *y = 0;
+            }
+        }
+    }
+
+    /// draw the particles, skipping every position where `reserved` is true
+    pub fn render<R: Renderer>(
+        &self,
+        out: &mut R,
+        reserved: impl Fn(u16, u16) -> bool,
+    ) -> Result<()> {
+        let glyph = match self.kind {
+            WeatherKind::Off => return Ok(()),
+            WeatherKind::Rain => "|".dark_blue(),
+            WeatherKind::Snow => "·".grey(),
+        };
+        for &(x, y) in &self.particles {
+            if !reserved(x, y) {
+                out.draw_text(x, y, glyph)?;
+            }
+        }
+        Ok(())
+    }
+}
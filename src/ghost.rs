@@ -0,0 +1,95 @@
+//! The ghost of a personal best: the best run from the same start, replayed
+//! dimmed under the snake to race against.
+//!
+//! The best ranked run for each seed and board size is kept as a replay file
+//! in `ghosts/` in the data directory. A run starting from exactly the same
+//! state (seed, board, level and mode) is shadowed by it tick for tick; the
+//! ghost goes where that run went, whatever happens to the food meanwhile,
+//! and vanishes where it crashed.
+
+use crate::plugin::Plugins;
+use crate::replay::ReplayFile;
+use crate::save;
+use crate::share::Playback;
+use snake_core::{Cell, GameState};
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub struct Ghost {
+    state: GameState,
+    playback: Playback,
+    plugins: Plugins,
+    shield: bool,
+}
+
+/// where the best run starting from `start` is kept
+fn path(start: &GameState) -> Option<PathBuf> {
+    let (w, h) = start.size;
+    let name = format!("{}-{}x{}.replay", start.seed, w, h);
+    save::data_dir().map(|dir| dir.join("ghosts").join(name))
+}
+
+/// whether `a` and `b` are the same starting state
+fn same_start(a: &GameState, b: &GameState) -> bool {
+    match (serde_json::to_value(a), serde_json::to_value(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+impl Ghost {
+    /// the ghost of the best run that started from `start`, if there is one
+    pub fn load(start: &GameState) -> Option<Self> {
+        let file = ReplayFile::load(&path(start)?).ok()?;
+        if !same_start(&file.start, start) {
+            return None;
+        }
+        let replay = file.replay().ok()?;
+        let mut state = file.start;
+        let mut plugins = Plugins::default();
+        plugins.sync(&file.plugins, &mut state);
+        Some(Self {
+            state,
+            shield: replay.shield,
+            playback: Playback::new(replay),
+            plugins,
+        })
+    }
+
+    /// follow the best run one tick further, a tick lasting `tick` in game time
+    pub fn tick(&mut self, tick: Duration) {
+        if self.state.is_over {
+            return;
+        }
+        while let Some(dir) = self.playback.next_at(self.state.tick) {
+            self.state.turn(dir);
+        }
+        let before = self.shield.then(|| self.state.clone());
+        self.state.step(None);
+        if let (true, Some(before)) = (self.state.is_over, before) {
+            self.state = before;
+            self.shield = false;
+        }
+        self.plugins.on_tick(&mut self.state, tick);
+        self.state.bus.clear();
+    }
+
+    /// the cells of the ghost snake, none once its run is over
+    pub fn body(&self) -> impl Iterator<Item = Cell> + '_ {
+        self.state.snake.body.iter().filter(|_| !self.state.is_over)
+    }
+}
+
+/// keep `file` as the ghost of its start if it beats the best run kept so far
+pub fn save_if_best(file: &ReplayFile) {
+    let path = match path(&file.start) {
+        Some(path) => path,
+        None => return,
+    };
+    let beaten = ReplayFile::load(&path).map_or(true, |best| {
+        !same_start(&best.start, &file.start) || file.score > best.score
+    });
+    if beaten {
+        let _ = file.save(&path);
+    }
+}
@@ -0,0 +1,92 @@
+//! Per-cell occupancy counts of a run, shown as a heatmap after game over.
+
+use crate::input;
+use crate::view::View;
+use crate::CELL_SZ;
+use crossterm::{
+    cursor,
+    event::Event,
+    execute, queue,
+    style::{self, Color, Stylize},
+    terminal, Result,
+};
+use snake_core::GameState;
+use std::io::Write;
+
+// from rarely to most often occupied
+const SCALE: [Color; 6] = [
+    Color::DarkBlue,
+    Color::Blue,
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Red,
+];
+
+/// how many ticks each board cell was occupied by the snake
+pub struct Heatmap {
+    size: (u16, u16),
+    counts: Vec<u32>,
+}
+
+impl Heatmap {
+    pub fn new(size: (u16, u16)) -> Self {
+        Self {
+            size,
+            counts: vec![0; usize::from(size.0) * usize::from(size.1)],
+        }
+    }
+
+    fn index(&self, (x, y): (u16, u16)) -> Option<usize> {
+        (x < self.size.0 && y < self.size.1)
+            .then(|| usize::from(y) * usize::from(self.size.0) + usize::from(x))
+    }
+
+    /// count every cell the snake covers after a tick
+    pub fn record(&mut self, state: &GameState) {
+        for cell in state.snake.body.iter() {
+            if let Some(i) = self.index(cell.pos) {
+                self.counts[i] += 1;
+            }
+        }
+    }
+
+    /// draw the heatmap over the walls of `state` and wait for a key
+    pub fn show<T: Write>(&self, buffer: &mut T, view: &View, state: &GameState) -> Result<()> {
+        execute!(buffer, terminal::Clear(terminal::ClearType::All))?;
+        let max = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        let block = "█".repeat(usize::from(CELL_SZ.0));
+        let mut draw = |pos: (u16, u16), color| view.draw(buffer, pos, block.as_str().with(color));
+        for cell in state.wall.cells.iter().chain(&state.breakables) {
+            draw(cell.pos, Color::DarkGrey)?;
+        }
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                let count = self.index((x, y)).map_or(0, |i| self.counts[i]);
+                if count > 0 {
+                    // scale relative to the busiest cell
+                    let level = (count as usize * SCALE.len()).div_ceil(max as usize) - 1;
+                    draw((x, y), SCALE[level.min(SCALE.len() - 1)])?;
+                }
+            }
+        }
+        queue!(
+            buffer,
+            cursor::MoveTo(10, 0),
+            style::PrintStyledContent("Heatmap".magenta()),
+            style::Print("  rare "),
+        )?;
+        for color in SCALE {
+            queue!(buffer, style::PrintStyledContent("█".with(color)))?;
+        }
+        queue!(
+            buffer,
+            style::Print(format!(" often (max {} ticks)", max)),
+            cursor::MoveTo(10, view.extent(self.size).1 + 1),
+            style::PrintStyledContent("press any key to go back".dark_grey())
+        )?;
+        buffer.flush()?;
+        while !matches!(input::read()?, Event::Key(_)) {}
+        Ok(())
+    }
+}
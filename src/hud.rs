@@ -0,0 +1,73 @@
+//! The status bar on the top row: the score, run time, length, tick rate and
+//! seed, and whatever else the run has going on, such as the time left, the
+//! boss, the shield and power-ups.
+//!
+//! Items go left to right in the order they are added. On a terminal too
+//! narrow for all of them the least important ones are left out, rather than
+//! cut off or written over each other.
+
+use crossterm::style::StyledContent;
+use std::time::Duration;
+
+const GAP: u16 = 3; // columns between two items
+
+/// how much an item matters, the first ones are left out last
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rank {
+    Score,
+    Status,
+    Time,
+    Length,
+    Tick,
+    Speed,
+    Seed,
+    Title,
+}
+
+#[derive(Default)]
+pub struct Bar {
+    items: Vec<(Rank, StyledContent<String>)>,
+}
+
+impl Bar {
+    pub fn push(&mut self, rank: Rank, text: StyledContent<String>) {
+        self.items.push((rank, text));
+    }
+
+    /// the items that fit between column `x` and `width`, with the column each starts at
+    pub fn layout(mut self, x: u16, width: u16) -> Vec<(u16, StyledContent<String>)> {
+        let len = |text: &StyledContent<String>| text.content().chars().count() as u16;
+        let needed = |items: &[(Rank, StyledContent<String>)]| {
+            let gaps = GAP * items.len().saturating_sub(1) as u16;
+            items.iter().map(|(_, text)| len(text)).sum::<u16>() + gaps
+        };
+        while needed(&self.items) > width.saturating_sub(x) {
+            let least = self
+                .items
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, (rank, _))| *rank);
+            match least.map(|(i, _)| i) {
+                Some(i) => self.items.remove(i),
+                None => break,
+            };
+        }
+        let mut placed = Vec::new();
+        let mut x = x;
+        for (_, text) in self.items {
+            let width = len(&text);
+            placed.push((x, text));
+            x += width + GAP;
+        }
+        placed
+    }
+}
+
+/// `duration` as minutes and seconds, with hours once it gets that long
+pub fn clock(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs / 3600 {
+        0 => format!("{}:{:02}", secs / 60, secs % 60),
+        hours => format!("{}:{:02}:{:02}", hours, secs / 60 % 60, secs % 60),
+    }
+}
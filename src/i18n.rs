@@ -0,0 +1,79 @@
+//! Translations of the text around the board: the title, the score, and the
+//! pause and game over messages.
+//!
+//! English is built in, and any string a translation leaves out stays
+//! English. Chinese ships with the game. Players add languages, or change the
+//! shipped ones, with a `<code>.toml` file in the `lang` folder of the data
+//! directory, e.g. `lang/de.toml`. `--lang` picks the language; otherwise it
+//! comes from the `LC_ALL`, `LC_MESSAGES` and `LANG` environment variables.
+
+use crate::save;
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+// the translations shipped with the game, by language code
+const BUILTIN: [(&str, &str); 1] = [("zh", include_str!("../lang/zh.toml"))];
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Strings {
+    pub title: String,
+    pub score: String, // the label of the score, as in "Score: 12"
+    pub paused: String,
+    pub paused_idle: String, // paused after no key was pressed for a while
+    pub game_over: String,
+    pub you_win: String,
+}
+
+impl Default for Strings {
+    fn default() -> Self {
+        Self {
+            title: "Rust Snake Game".to_string(),
+            score: "Score".to_string(),
+            paused: "PAUSED - o: options, any other key: resume".to_string(),
+            paused_idle: "PAUSED (idle) - o: options, any other key: resume".to_string(),
+            game_over: "GAME OVER".to_string(),
+            you_win: "YOU WIN".to_string(),
+        }
+    }
+}
+
+pub fn lang_dir() -> Option<PathBuf> {
+    save::data_dir().map(|dir| dir.join("lang"))
+}
+
+/// the language code the locale names, e.g. `zh` for `zh_CN.UTF-8`
+pub fn detect() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|locale| {
+            let code = locale.split(['_', '.', '@']).next()?.to_lowercase();
+            (!code.is_empty()).then_some(code)
+        })
+}
+
+impl Strings {
+    /// the strings of language `code`: the player's file for it, else the
+    /// shipped translation, else English
+    pub fn load(code: &str) -> Result<Self, String> {
+        let file = lang_dir()
+            .map(|dir| dir.join(format!("{}.toml", code)))
+            .filter(|path| path.is_file());
+        let (text, source) = match file {
+            Some(path) => {
+                let text =
+                    fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                (text, path.display().to_string())
+            }
+            None => match BUILTIN.iter().find(|(name, _)| *name == code) {
+                Some((name, text)) => (text.to_string(), format!("lang/{}.toml", name)),
+                None => return Ok(Self::default()),
+            },
+        };
+        toml::from_str(&text).map_err(|e| format!("{}: {}", source, e))
+    }
+}
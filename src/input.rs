@@ -0,0 +1,160 @@
+//! Terminal input for every screen, with a hidden key macro recorder.
+//!
+//! `--record-keys FILE` writes each key with the milliseconds since start,
+//! one per line (`1520 up`, `2310 ctrl+c`). `--replay-keys FILE` feeds such
+//! a file back at the same times instead of reading the keyboard, so menus,
+//! pausing and restarts can be driven reproducibly. Once the macro runs out
+//! the keyboard takes over again.
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::Result;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+enum Driver {
+    Live,
+    Recording(File, Instant),
+    Replaying(VecDeque<(u64, KeyEvent)>, Instant),
+}
+
+static DRIVER: Mutex<Driver> = Mutex::new(Driver::Live);
+
+fn driver() -> std::sync::MutexGuard<'static, Driver> {
+    DRIVER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// record every key read from now on to `path`
+pub fn record(path: &Path) -> io::Result<()> {
+    *driver() = Driver::Recording(File::create(path)?, Instant::now());
+    Ok(())
+}
+
+/// replay the keys recorded in `path` instead of reading the keyboard
+pub fn replay(path: &Path) -> std::result::Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut keys = VecDeque::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let bad_line = || format!("{}:{}: expected `<ms> <key>`", path.display(), i + 1);
+        let (ms, key) = line.trim().split_once(' ').ok_or_else(bad_line)?;
+        let ms = ms.parse().map_err(|_| bad_line())?;
+        let key = decode(key.trim())
+            .ok_or_else(|| format!("{}:{}: unknown key `{}`", path.display(), i + 1, key))?;
+        keys.push_back((ms, key));
+    }
+    *driver() = Driver::Replaying(keys, Instant::now());
+    Ok(())
+}
+
+/// like `event::poll`: whether an event is ready within `timeout`
+pub fn poll(timeout: Duration) -> Result<bool> {
+    let mut guard = driver();
+    if let Driver::Replaying(keys, start) = &*guard {
+        match keys.front() {
+            Some((ms, _)) => {
+                let due = *start + Duration::from_millis(*ms);
+                let now = Instant::now();
+                if due > now + timeout {
+                    thread::sleep(timeout);
+                    return Ok(false);
+                }
+                thread::sleep(due.saturating_duration_since(now));
+                return Ok(true);
+            }
+            None => *guard = Driver::Live,
+        }
+    }
+    drop(guard);
+    event::poll(timeout)
+}
+
+/// like `event::read`: wait for the next event
+pub fn read() -> Result<Event> {
+    let mut guard = driver();
+    if let Driver::Replaying(keys, start) = &mut *guard {
+        match keys.pop_front() {
+            Some((ms, key)) => {
+                let due = *start + Duration::from_millis(ms);
+                thread::sleep(due.saturating_duration_since(Instant::now()));
+                return Ok(Event::Key(key));
+            }
+            None => *guard = Driver::Live,
+        }
+    }
+    let recording = matches!(*guard, Driver::Recording(..));
+    // don't hold the lock while blocked on the keyboard
+    drop(guard);
+    let event = event::read()?;
+    if let (true, Event::Key(key)) = (recording, event) {
+        if let Driver::Recording(file, start) = &mut *driver() {
+            if let Some(name) = encode(key) {
+                writeln!(file, "{} {}", start.elapsed().as_millis(), name)?;
+            }
+        }
+    }
+    Ok(event)
+}
+
+/// the name of `key` in a macro file; keys no screen uses are not recorded
+fn encode(key: KeyEvent) -> Option<String> {
+    let name = match key.code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Backspace => "backspace".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        KeyCode::F(n) => format!("f{}", n),
+        _ => return None,
+    };
+    Some(if key.modifiers.contains(KeyModifiers::CONTROL) {
+        format!("ctrl+{}", name)
+    } else {
+        name
+    })
+}
+
+/// the key named `name` as in a key macro, without modifiers
+pub fn key_code(name: &str) -> Option<KeyCode> {
+    decode(name)
+        .filter(|key| key.modifiers.is_empty())
+        .map(|key| key.code)
+}
+
+fn decode(text: &str) -> Option<KeyEvent> {
+    let (modifiers, name) = match text.strip_prefix("ctrl+") {
+        Some(name) => (KeyModifiers::CONTROL, name),
+        None => (KeyModifiers::NONE, text),
+    };
+    let code = match name {
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "backspace" => KeyCode::Backspace,
+        "tab" => KeyCode::Tab,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        _ if name.len() > 1 && name.starts_with('f') => KeyCode::F(name[1..].parse().ok()?),
+        _ => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return None,
+            }
+        }
+    };
+    Some(KeyEvent::new(code, modifiers))
+}
@@ -0,0 +1,59 @@
+//! Online leaderboard, with the `leaderboard` feature.
+//!
+//! After a ranked run the score is posted as JSON (name, score, mode, seed
+//! and board size) to the endpoint in `[leaderboard]`, and the global top
+//! ten is fetched back from the same URL for the game over screen. Nothing
+//! is sent without an endpoint, and an unreachable server only costs the
+//! table.
+
+use crate::config::LeaderboardConfig;
+use crossterm::style::{StyledContent, Stylize};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const TIMEOUT: u64 = 3; // seconds per request
+const TOP: usize = 10;
+
+#[derive(Serialize)]
+pub struct Submission {
+    pub name: String,
+    pub score: u16,
+    pub mode: String,
+    pub seed: u64,
+    pub board: (u16, u16), // width and height in cells
+}
+
+#[derive(Deserialize)]
+struct GlobalEntry {
+    name: String,
+    score: u16,
+}
+
+/// post `submission`, then fetch the global top ten as lines for the game
+/// over screen; no lines without an endpoint
+pub fn submit(config: &LeaderboardConfig, submission: &Submission) -> Vec<StyledContent<String>> {
+    let endpoint = match &config.endpoint {
+        Some(endpoint) => endpoint,
+        None => return Vec::new(),
+    };
+    let timeout = Duration::from_secs(TIMEOUT);
+    let _ = ureq::post(endpoint).timeout(timeout).send_json(submission);
+    let top = ureq::get(endpoint)
+        .timeout(timeout)
+        .call()
+        .ok()
+        .and_then(|response| response.into_json::<Vec<GlobalEntry>>().ok());
+    let mut lines = vec!["GLOBAL TOP 10".to_string().magenta()];
+    match top {
+        Some(entries) => lines.extend(entries.iter().take(TOP).enumerate().map(|(i, entry)| {
+            let line = format!("{:>2}. {:<12} {:>5}", i + 1, entry.name, entry.score);
+            if entry.name == submission.name && entry.score == submission.score {
+                line.yellow().bold()
+            } else {
+                line.white()
+            }
+        })),
+        None => lines.push("leaderboard unreachable".to_string().dark_grey()),
+    }
+    lines
+}
@@ -0,0 +1,165 @@
+//! Level files: an ASCII map (see `snake_core::level`) plus metadata, in TOML.
+//!
+//! ```toml
+//! spawn_order = "random" # or "assigned": player n gets the n-th spawn point
+//! map = """
+//! ##########
+//! #>......<#
+//! ##########
+//! """
+//!
+//! [[events]]
+//! at = 60 # seconds into the run
+//! action = "open_gap"
+//!
+//! [[events]]
+//! every = 45 # seconds
+//! action = "spawn_hazard"
+//!
+//! [[events]]
+//! chance = 0.01 # probability per second
+//! action = { rain_food = 5 }
+//!
+//! [[events]]
+//! every = 30
+//! action = "spawn_ram" # lets the snake smash the `%` walls of the map
+//!
+//! [gates] # timing of the gates marked `1` to `9` in the map, in seconds
+//! 1 = { closed = 6, open = 4 }
+//! 2 = { closed = 6, open = 4, offset = 5 } # starts 5 seconds into its cycle
+//!
+//! [boss] # optional, see `snake_core::boss`
+//! at = 5       # seconds into the run it appears
+//! size = 3     # side of the square, in cells
+//! every = 0.3  # seconds between its moves
+//! survive = 60 # seconds to outlast it for
+//!
+//! [medals] # optional, makes this a campaign level (see `campaign`)
+//! bronze = { food = 5, time = 60 }
+//! silver = { food = 10, time = 90 }
+//! gold = { food = 15, time = 100 }
+//! ```
+
+use crate::campaign::Medals;
+use serde::Deserialize;
+use snake_core::boss::BossSpec;
+use snake_core::events::{Action, Event, Trigger};
+use snake_core::level::{Level, SpawnOrder};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Deserialize)]
+struct LevelFile {
+    #[serde(default)]
+    spawn_order: SpawnOrder,
+    map: String,
+    #[serde(default)]
+    events: Vec<EventSpec>,
+    #[serde(default)]
+    gates: BTreeMap<String, GateFile>,
+    boss: Option<BossFile>,
+    medals: Option<Medals>,
+}
+
+#[derive(Deserialize)]
+struct GateFile {
+    closed: f64,
+    open: f64,
+    #[serde(default)]
+    offset: f64,
+}
+
+#[derive(Deserialize)]
+struct BossFile {
+    #[serde(default)]
+    at: f64,
+    size: u16,
+    every: f64,
+    survive: f64,
+}
+
+/// a parsed level file
+pub struct LevelData {
+    pub level: Level,
+    pub medals: Option<Medals>,
+}
+
+/// an event with its timing in seconds, exactly one of `at`, `every`, `chance` is set
+#[derive(Deserialize)]
+struct EventSpec {
+    at: Option<f64>,
+    every: Option<f64>,
+    chance: Option<f64>,
+    action: Action,
+}
+
+impl EventSpec {
+    fn to_event(&self, tick: Duration) -> Result<Event, String> {
+        let ticks = |secs: f64| (secs / tick.as_secs_f64()).round() as u64;
+        let trigger = match (self.at, self.every, self.chance) {
+            (Some(at), None, None) => Trigger::At(ticks(at)),
+            (None, Some(every), None) => Trigger::Every(ticks(every).max(1)),
+            (None, None, Some(chance)) => {
+                Trigger::Chance((chance * tick.as_secs_f64() * 1e6).round() as u32)
+            }
+            _ => return Err("each event needs exactly one of at, every or chance".to_string()),
+        };
+        Ok(Event {
+            trigger,
+            action: self.action,
+        })
+    }
+}
+
+/// parse the text of a level file; event times are converted to game ticks of length `tick`
+pub fn parse(text: &str, tick: Duration) -> Result<LevelData, String> {
+    let file: LevelFile = toml::from_str(text).map_err(|e| e.to_string())?;
+    let mut level = Level::parse(&file.map).map_err(|e| e.to_string())?;
+    level.spawn_order = file.spawn_order;
+    level.events = file
+        .events
+        .iter()
+        .map(|spec| spec.to_event(tick))
+        .collect::<Result<_, _>>()?;
+    let ticks = |secs: f64| (secs / tick.as_secs_f64()).round() as u64;
+    for gate in &mut level.gates {
+        let timing = file
+            .gates
+            .get(&gate.id.to_string())
+            .ok_or_else(|| format!("gate {} has no timing in [gates]", gate.id))?;
+        gate.closed = ticks(timing.closed);
+        gate.open = ticks(timing.open);
+        gate.offset = ticks(timing.offset);
+    }
+    if let Some(boss) = &file.boss {
+        let spec = BossSpec {
+            size: boss.size,
+            every: ticks(boss.every).max(1),
+            survive: ticks(boss.survive),
+        };
+        level.events.push(Event {
+            // events fire from the first tick on
+            trigger: Trigger::At(ticks(boss.at).max(1)),
+            action: Action::SpawnBoss(spec),
+        });
+    }
+    Ok(LevelData {
+        level,
+        medals: file.medals,
+    })
+}
+
+/// load a plain-text map, without the rest of a level file
+pub fn load_map(path: &Path) -> Result<Level, String> {
+    let error = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+    let text = fs::read_to_string(path).map_err(|e| error(&e))?;
+    Level::parse(&text).map_err(|e| error(&e))
+}
+
+pub fn load(path: &Path, tick: Duration) -> Result<LevelData, String> {
+    let error = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+    let text = fs::read_to_string(path).map_err(|e| error(&e))?;
+    parse(&text, tick).map_err(|e| error(&e))
+}
@@ -0,0 +1,210 @@
+//! Stages: a fixed run through hand-designed layouts.
+//!
+//! Each stage has a target score; reaching it moves the snake on to the next
+//! layout after a short screen, keeping the score. Clearing the last stage
+//! wins the run.
+
+use crate::input;
+use crossterm::{
+    cursor, execute, queue,
+    style::{self, Stylize},
+    terminal, Result,
+};
+use snake_core::level::Level;
+use std::io::Write;
+use std::time::Duration;
+
+const TRANSITION: Duration = Duration::from_secs(2); // or until a key is pressed
+
+struct StageMap {
+    name: &'static str,
+    target: u16, // score to reach, counted from the start of the run
+    map: &'static str,
+}
+
+const STAGES: [StageMap; 4] = [
+    StageMap {
+        name: "Open Field",
+        target: 5,
+        map: "
+##############################
+#............................#
+#............................#
+#............................#
+#............................#
+#............................#
+#............................#
+#............................#
+#............................#
+#.........>..................#
+#............................#
+#............................#
+#............................#
+#............................#
+#............................#
+#............................#
+#............................#
+##############################
+",
+    },
+    StageMap {
+        name: "Pillars",
+        target: 12,
+        map: "
+##############################
+#............................#
+#.........>..................#
+#............................#
+#.....##....##....##....##...#
+#.....##....##....##....##...#
+#............................#
+#............................#
+#.....##....##....##....##...#
+#.....##....##....##....##...#
+#............................#
+#............................#
+#.....##....##....##....##...#
+#.....##....##....##....##...#
+#............................#
+#............................#
+#............................#
+##############################
+",
+    },
+    StageMap {
+        name: "Cross",
+        target: 20,
+        map: "
+##############################
+#............................#
+#.........>..................#
+#..............#.............#
+#..............#.............#
+#..............#.............#
+#..............#.............#
+#..............#.............#
+#............................#
+#....#########..#########....#
+#............................#
+#..............#.............#
+#..............#.............#
+#..............#.............#
+#..............#.............#
+#..............#.............#
+#............................#
+##############################
+",
+    },
+    StageMap {
+        name: "Corridors",
+        target: 30,
+        map: "
+##############################
+#............................#
+#.........>..................#
+#............................#
+########################.....#
+#............................#
+#............................#
+#............................#
+#.....########################
+#............................#
+#............................#
+#............................#
+########################.....#
+#............................#
+#............................#
+#............................#
+#............................#
+##############################
+",
+    },
+];
+
+#[derive(Clone)]
+pub struct Stage {
+    pub name: &'static str,
+    pub target: u16,
+    pub level: Level,
+}
+
+/// progress through the stages of a run
+#[derive(Clone)]
+pub struct Stages {
+    stages: Vec<Stage>,
+    current: usize,
+}
+
+impl Stages {
+    pub fn load() -> std::result::Result<Self, String> {
+        let stages = STAGES
+            .iter()
+            .map(|stage| {
+                let level = Level::parse(stage.map.trim_start())
+                    .map_err(|e| format!("stage {}: {}", stage.name, e))?;
+                Ok(Stage {
+                    name: stage.name,
+                    target: stage.target,
+                    level,
+                })
+            })
+            .collect::<std::result::Result<_, String>>()?;
+        Ok(Self { stages, current: 0 })
+    }
+
+    pub fn current(&self) -> &Stage {
+        &self.stages[self.current]
+    }
+
+    /// back to the first stage, for a new run
+    pub fn rewound(&self) -> Self {
+        Self {
+            stages: self.stages.clone(),
+            current: 0,
+        }
+    }
+
+    pub fn is_cleared(&self, score: u16) -> bool {
+        score >= self.current().target
+    }
+
+    /// move on to the next stage; `None` once the last one is cleared
+    pub fn advance(&mut self) -> Option<&Stage> {
+        if self.current + 1 >= self.stages.len() {
+            return None;
+        }
+        self.current += 1;
+        Some(self.current())
+    }
+
+    pub fn hud(&self) -> String {
+        format!(
+            "Stage {}/{}: {}  target {}",
+            self.current + 1,
+            self.stages.len(),
+            self.current().name,
+            self.current().target
+        )
+    }
+}
+
+/// announce the stage about to start
+pub fn transition_screen<T: Write>(buffer: &mut T, stages: &Stages) -> Result<()> {
+    execute!(buffer, terminal::Clear(terminal::ClearType::All))?;
+    queue!(
+        buffer,
+        cursor::MoveTo(10, 2),
+        style::PrintStyledContent(
+            format!("STAGE {}: {}", stages.current + 1, stages.current().name)
+                .green()
+                .bold()
+        ),
+        cursor::MoveTo(10, 4),
+        style::PrintStyledContent(format!("Reach {} points", stages.current().target).yellow())
+    )?;
+    buffer.flush()?;
+    if input::poll(TRANSITION)? {
+        input::read()?;
+    }
+    Ok(())
+}
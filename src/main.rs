@@ -1,314 +1,2499 @@
+mod anim;
+mod ascii;
+#[cfg(feature = "audio")]
+mod audio;
+mod bot;
+mod campaign;
+mod cli;
+mod colors;
+mod config;
+mod content;
+mod controller;
+mod difficulty;
+mod editor;
+mod effects;
+mod ghost;
+mod heatmap;
+mod hud;
+mod i18n;
+mod input;
+#[cfg(feature = "leaderboard")]
+mod leaderboard;
+mod level;
+mod levels;
+mod menu;
+mod metrics;
+mod moves;
+mod net;
+mod options;
+mod plugin;
+mod powerup;
+mod profile;
+mod render;
+mod replay;
+mod results;
+mod save;
+mod scores;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod session;
+mod share;
+mod shop;
+mod simulate;
+mod slots;
+mod snake_skin;
+mod speedrun;
+mod stats;
+#[cfg(feature = "net")]
+mod telemetry;
+mod theme;
+mod title;
+mod tty;
+mod tutorial;
+#[cfg(feature = "net")]
+mod update;
+mod view;
+
+use anim::{Death, Look};
+use ascii::AsciiWriter;
+use bot::Bot;
+use campaign::{Attempt, CampaignLevel};
+use clap::Parser;
+use cli::{Cli, Command};
+use colors::{ColorDepth, ColorWriter};
+use config::{Action, Config, ControlScheme, KeysConfig, SpeedConfig};
+use content::{Content, Glyphs, Skin};
+use controller::Controller;
 use crossterm::{
     cursor,
-    event::{self, Event, KeyCode, KeyEvent},
+    event::{Event, KeyCode, KeyEvent},
     execute, queue,
-    style::{self, Stylize},
+    style::{self, Color, StyledContent, Stylize},
     terminal, Result,
 };
-use rand::Rng;
+use difficulty::Difficulty;
+use effects::{Weather, WeatherKind};
+use ghost::Ghost;
+use heatmap::Heatmap;
+use hud::Rank;
+use i18n::Strings;
+use levels::Stages;
+use metrics::TickLog;
+use moves::MoveScript;
+use net::{Link, Message};
+use plugin::Plugins;
+use profile::Profile;
+use render::{Renderer, Screen};
+use replay::ReplayFile;
+use results::RunResult;
+use save::SaveFile;
+use scores::Scores;
+use session::Session;
+use share::{Playback, ShareReplay};
+use slots::Loaded;
+use snake_core::bus::Notice;
+use snake_core::food::{self, FoodBehavior};
+use snake_core::level::Level;
+use snake_core::maze;
+use snake_core::modes::{self, GameMode};
+use snake_core::powerup::PowerUps;
+use snake_core::rival::Rival;
+use snake_core::{Cell, Direction, GameState, InputSource, Rng, Spawn, TailRule};
+use snake_skin::SnakeSkin;
+use speedrun::{Goal, Speedrun};
+use stats::{Bests, RunStats};
 use std::collections::VecDeque;
-use std::io::{stdout, Write};
-use std::{
-    thread,
-    time::{Duration, Instant},
-};
+use std::io::{stdout, Stdout, Write};
+use std::time::{Duration, Instant};
+use theme::Theme;
+use title::Choice;
+use tty::TerminalGuard;
+use tutorial::Tutorial;
+use view::View;
 
 const CELL_SZ: (u16, u16) = (2, 1);
 const GND_SZ: (u16, u16) = (64, 32);
-const TIME_STEP: u64 = 150; // game state refresh timestep in milliseconds
+const FRAME_TIME: u64 = 16; // milliseconds between frames drawn, about 60 a second
+const MAX_CATCH_UP: u32 = 3; // ticks run at most between two frames
+const PULSE_FRAMES: u64 = 48; // frames the food takes to brighten and fade back
+const AUTOSAVE_TICKS: u64 = 50; // ticks between writes of the autosave slot
+const SAVED_SECS: u64 = 2; // seconds the HUD shows that the save key worked
+const HISTORY_LEN: usize = 100; // ticks kept for rewinding in practice mode
+const TURN_QUEUE: usize = 3; // turns pressed ahead at most
+const REWIND_TICKS: usize = 5; // ticks undone per rewind keypress
+const GATE_COUNTDOWN: u64 = 3; // seconds before a gate opens or closes that are counted down
+const BREAKABLE_GLYPH: &str = "%%"; // drawn in the wall color
+const RAM_GLYPH: &str = "<>"; // drawn in the bonus color
+const PORTAL_GLYPH: char = '@'; // followed by the pair's letter, in the bonus color
 
-#[derive(Debug, Eq, PartialEq)]
-struct Cell {
-    pos: (u16, u16),  // (horizontal coord, vertical coord)
-    size: (u16, u16), // (horizontal length, vertical length)
+// board size in cells when there is no terminal to fit, as for snapshots;
+// the wall is drawn from the second cell column and row 1
+const NEW_MAP_SZ: (u16, u16) = (30, 20); // a map the editor creates without --width and --height
+const BOARD_SZ: (u16, u16) = (GND_SZ.0 / CELL_SZ.0 - 1, GND_SZ.1 / CELL_SZ.1);
+
+/// what a cell on the board shows; the skin decides how it looks
+#[derive(Clone, Copy)]
+enum Tile {
+    Snake,
+    Food,
+    Wall,
+    Hazard,
+    Bonus,
+    Poison,
+    // the second snake, drawn like the first in a color of its own
+    Rival,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+fn render_cell<R: Renderer>(
+    out: &mut R,
+    view: &View,
+    cell: &Cell,
+    tile: Tile,
+    skin: &Skin,
+    dimmed: bool,
+) -> Result<()> {
+    let glyph = skin.glyphs.glyph(tile).with(skin.theme.color(tile, dimmed));
+    out.draw_cell(view, cell.pos, glyph)
+}
+
+/// the plain text frame of a `seed` run after `ticks` ticks, steered by `moves`
+fn snapshot(setup: Setup, seed: u64, ticks: u64, mut moves: Option<MoveScript>) -> String {
+    let state = setup.new_state(seed);
+    let mut game = Game {
+        setup,
+        ..Game::from_state(state)
+    };
+    game.skin.glyphs = Glyphs::ascii();
+    for _ in 0..ticks {
+        if game.state.is_over {
+            break;
+        }
+        let turn = moves.as_mut().and_then(|moves| moves.poll());
+        game.state.step(turn);
+    }
+    game.render_to_string()
 }
 
-enum Color {
-    Red,
-    Blue,
-    White,
+/// what every new run of a game starts from
+#[derive(Clone)]
+struct Setup {
+    level: Level,
+    spawn: Spawn,
+    // use `spawn` even if the level has spawn points of its own
+    fixed_spawn: bool,
+    mode: &'static dyn GameMode,
+    tail_rule: TailRule,
+    difficulty: Difficulty,
+    food: usize,
+    poison: usize,
+    // scatter rocks over the board, as many as the difficulty asks for
+    obstacles: bool,
+    // pairs of portals to scatter, on top of the level's own
+    portals: usize,
+    food_behavior: &'static dyn FoodBehavior,
+    // `--two-player`: a rival snake for player two
+    two_player: bool,
+    // `--opponent`: a rival snake for a bot
+    opponent: bool,
+    // `--maze`: a new maze for every seed, on a board the size of `level`
+    maze: bool,
+    // `--battle`: how many battle snakes the computer steers
+    battle: usize,
+    // `[rules] power_ups`: pickups dropped on the board now and then
+    power_ups: bool,
 }
 
-impl Cell {
-    pub fn new(x: u16, y: u16) -> Self {
+impl Setup {
+    fn classic() -> Self {
         Self {
-            pos: (x, y),
-            size: CELL_SZ,
-        }
-    }
-    pub fn clone_with_pos_shift(&self, dir: Direction, steps: u16) -> Self {
-        let mut x = self.pos.0;
-        let mut y = self.pos.1;
-        match dir {
-            Direction::Up => y -= steps * self.size.1,
-            Direction::Down => y += steps * self.size.1,
-            Direction::Left => x -= steps * self.size.0,
-            Direction::Right => x += steps * self.size.0,
-        }
-        Self::new(x, y)
-    }
-    fn render<T: Write>(&self, output: &mut T, color: Color) -> Result<()> {
-        for x in self.pos.0..self.pos.0 + self.size.0 {
-            for y in self.pos.1..self.pos.1 + self.size.1 {
-                queue!(
-                    output,
-                    cursor::MoveTo(x, y),
-                    style::PrintStyledContent(match color {
-                        Color::Red => "█".red(),
-                        Color::Blue => "█".blue(),
-                        Color::White => "█".white(),
-                    })
-                )?;
+            level: Level::bordered(BOARD_SZ),
+            spawn: Spawn::centered(BOARD_SZ),
+            fixed_spawn: false,
+            mode: modes::find("classic"),
+            tail_rule: TailRule::Lenient,
+            difficulty: Difficulty::Normal,
+            food: 1,
+            poison: 0,
+            obstacles: false,
+            portals: 0,
+            food_behavior: food::find("still"),
+            two_player: false,
+            opponent: false,
+            maze: false,
+            battle: 0,
+            power_ups: false,
+        }
+    }
+
+    /// spawns the setup can start from, each must fit the level
+    fn candidate_spawns(&self) -> Vec<Spawn> {
+        if self.fixed_spawn || self.level.spawns.is_empty() {
+            return vec![self.spawn.clone()];
+        }
+        self.level
+            .spawns
+            .iter()
+            .map(|point| Spawn {
+                pos: point.pos,
+                dir: point.dir,
+                len: self.spawn.len,
+            })
+            .collect()
+    }
+
+    /// where player two starts when player one starts from `spawn`: another
+    /// spawn point of the level, or the other side of the board
+    fn rival_spawn(&self, spawn: &Spawn) -> Spawn {
+        match self
+            .level
+            .spawns
+            .iter()
+            .find(|point| point.pos != spawn.pos)
+        {
+            Some(point) if !self.fixed_spawn => Spawn {
+                pos: point.pos,
+                dir: point.dir,
+                len: spawn.len,
+            },
+            _ => spawn.mirrored(self.level.size),
+        }
+    }
+
+    /// where the battle snakes start when the snake starts from `spawn`: the
+    /// level's other spawn points, then spots in three corners of the board
+    fn battle_spawns(&self, spawn: &Spawn) -> Vec<Spawn> {
+        let (w, h) = self.level.size;
+        let corners = [
+            ((w - 1 - w / 4, h / 4), Direction::Left),
+            ((w / 4, h - 1 - h / 4), Direction::Right),
+            ((w - 1 - w / 4, h - 1 - h / 4), Direction::Left),
+        ];
+        self.level
+            .spawns
+            .iter()
+            .filter(|point| !self.fixed_spawn && point.pos != spawn.pos)
+            .map(|point| (point.pos, point.dir))
+            .chain(corners)
+            .take(self.battle)
+            .map(|(pos, dir)| Spawn {
+                pos,
+                dir,
+                len: spawn.len,
+            })
+            .collect()
+    }
+
+    /// setup for `level` with the configured snake, exiting if a spawn doesn't fit
+    fn checked(level: Level, config: &Config, cli: &Cli) -> Self {
+        let difficulty = cli.difficulty.as_deref().and_then(Difficulty::find);
+        let difficulty = difficulty.unwrap_or_default();
+        let mut spawn = config.snake.spawn(level.size);
+        spawn.len += difficulty.extra_length();
+        cli.apply_spawn(&mut spawn);
+        // the spawn points are checked against a maze like the ones that will be played
+        let level = if cli.maze {
+            maze::generate(level.size, spawn.len, &mut Rng::new(0))
+        } else {
+            level
+        };
+        let setup = Self {
+            fixed_spawn: cli.spawn.is_some(),
+            level,
+            spawn,
+            mode: modes::find(cli.mode.as_deref().unwrap_or(default_mode(config))),
+            tail_rule: config.rules.tail,
+            difficulty,
+            food: config.rules.food,
+            poison: config.rules.poison,
+            obstacles: config.rules.obstacles,
+            portals: config.rules.portals,
+            food_behavior: food::find(
+                config
+                    .rules
+                    .food_behavior
+                    .as_deref()
+                    .unwrap_or(difficulty.food_behavior()),
+            ),
+            two_player: cli.two_player || cli.host.is_some(),
+            opponent: cli.opponent,
+            maze: cli.maze,
+            battle: cli.battle.map_or(0, usize::from),
+            power_ups: config.rules.power_ups,
+        };
+        for spawn in setup.candidate_spawns() {
+            if let Err(e) = spawn.validate(&setup.level) {
+                cli::exit_with_error(&format!("spawn point {:?}: {}", spawn.pos, e));
+            }
+            let rival = setup.rival_spawn(&spawn);
+            let has_rival = setup.two_player || setup.opponent;
+            if let (true, Err(e)) = (has_rival, rival.validate(&setup.level)) {
+                cli::exit_with_error(&format!("the rival's spawn point {:?}: {}", rival.pos, e));
+            }
+            for bot in setup.battle_spawns(&spawn) {
+                if let Err(e) = bot.validate(&setup.level) {
+                    let message = format!("a battle snake's spawn point {:?}: {}", bot.pos, e);
+                    cli::exit_with_error(&message);
+                }
             }
         }
-        Ok(())
+        setup
+    }
+
+    fn new_state(&self, seed: u64) -> GameState {
+        let maze = self
+            .maze
+            .then(|| maze::generate(self.level.size, self.spawn.len, &mut Rng::new(seed)));
+        let level = maze.as_ref().unwrap_or(&self.level);
+        let spawn = match level.pick_spawn(0, self.spawn.len, &mut Rng::new(seed)) {
+            Some(spawn) if !self.fixed_spawn => spawn,
+            _ => self.spawn.clone(),
+        };
+        let mut state = GameState::with_level(level, seed, &spawn);
+        if self.two_player || self.opponent {
+            state.rival = Some(Rival::new(&self.rival_spawn(&spawn), self.two_player));
+        }
+        for bot in self.battle_spawns(&spawn) {
+            state.bots.push(Rival::new(&bot, false));
+        }
+        state.mode = self.mode.name().to_string();
+        state.tail_rule = self.tail_rule;
+        state.multiplier = self.difficulty.multiplier();
+        if self.obstacles {
+            let (w, h) = state.size;
+            let cells = f64::from(w.saturating_sub(2)) * f64::from(h.saturating_sub(2));
+            state.scatter_rocks((cells * self.difficulty.rock_density()) as usize);
+        }
+        state.scatter_portals(self.portals);
+        state.set_food_count(self.food);
+        state.set_poison_count(self.poison);
+        state.poison_kills = self.difficulty.poison_kills();
+        state.food_behavior = self.food_behavior.name().to_string();
+        state.power_ups = self.power_ups.then(PowerUps::default);
+        state
     }
 }
 
-struct Snake {
-    body: VecDeque<Cell>,
-    dir: Direction,
+struct Game {
+    state: GameState,
+    setup: Setup,
+    time: Instant, // how far the game has been simulated
+    time_step: Duration,
+    ticks: u64,
+    quit: bool,
+    // turns pressed ahead of the ticks that make them, one per tick
+    turns: VecDeque<Direction>,
+    // states before each recent tick, only kept in practice mode
+    history: Option<VecDeque<GameState>>,
+    // debug mode: the simulation only advances on request
+    frame_step: bool,
+    step_requested: bool,
+    // a tick of this run was taken in frame-step mode, which leaves it unranked
+    frame_stepped: bool,
+    tutorial: Option<Tutorial>,
+    // attract mode on the idle title screen: any key ends it, nothing is kept
+    demo: bool,
+    // auto-pause after this long without input or score change
+    idle_timeout: Option<Duration>,
+    last_activity: Instant,
+    last_score: u16,
+    paused: bool,
+    #[cfg(feature = "scripting")]
+    scripts: Option<scripting::Scripts>,
+    // `[audio]`: beeps for food, turns and crashes
+    #[cfg(feature = "audio")]
+    sounds: Option<audio::Sounds>,
+    plugins: Plugins,
+    skin: Skin,
+    // `--lang`: the title, score and messages in the player's language
+    strings: Strings,
+    weather: Weather,
+    heatmap: Heatmap,
+    stats: RunStats,
+    speedrun: Option<Speedrun>,
+    campaign: Option<Attempt>,
+    // `--stages`: the stage being played, its layout replaces the setup's level
+    stages: Option<Stages>,
+    // power-up bought in the shop, undoes the first crash of a run
+    shield: bool,
+    controls: ControlScheme,
+    keys: KeysConfig,
+    speed: SpeedConfig,
+    // the restart key was pressed: the run ends unscored and a new one starts
+    restart_requested: bool,
+    view: View,
+    // `--input-script`: steers instead of the arrow keys until it runs out
+    moves: Option<MoveScript>,
+    // `--autopilot`: steers instead of the arrow keys
+    bot: Option<Bot>,
+    // steers the rival snake, if there is one
+    rival: Option<Controller>,
+    // `--battle`: steers the battle snakes, one bot each
+    battle: Vec<Bot>,
+    // `--join`: the host ticks the game, the keys steer its rival
+    host: Option<Link>,
+    // turns of this run for its share string, if it was played from the start
+    replay: Option<ShareReplay>,
+    // the state the recorded run started from, for its replay file
+    start: Option<GameState>,
+    // `[game] ghost`: race the best run from the same start, if there is one
+    race_ghost: bool,
+    ghost: Option<Ghost>,
+    // an imported share string, steers instead of the arrow keys
+    playback: Option<Playback>,
+    // `replay FILE`: the run is only watched, at this many times its speed
+    watch: Option<u32>,
+    // `--tick-csv`, with the time the last frame took to draw
+    tick_log: Option<TickLog>,
+    render_time: Duration,
+    // the last frame, so the next one only redraws what changed
+    screen: Screen,
+    // frames drawn so far, the clock of the animations
+    frames: u64,
+    // game time of the run: the ticks so far, each as long as it lasted
+    played: Duration,
+    // when the save key last wrote the quicksave slot
+    saved: Option<Instant>,
+    // `[display] animations`: the food pulses and a crash is played out
+    animate: bool,
+    // the snake's death being played out before the game over screen
+    death: Option<Death>,
+    // the themes the options screen offers, the ones not sold in the shop or bought
+    themes: Vec<Theme>,
 }
 
-impl Snake {
-    pub fn new((x, y): (u16, u16), dir: Direction, len: u16) -> Self {
-        let head = Cell {
-            pos: (x, y),
-            size: CELL_SZ,
-        };
-        let dir_rev = match dir {
-            Direction::Up => Direction::Down,
-            Direction::Down => Direction::Up,
-            Direction::Left => Direction::Right,
-            Direction::Right => Direction::Left,
+impl Game {
+    /// a run of `setup` started from `seed`
+    pub fn new(setup: Setup, seed: u64) -> Self {
+        let state = setup.new_state(seed);
+        Self {
+            time_step: setup.difficulty.time_step(),
+            setup,
+            ..Self::from_state(state)
+        }
+    }
+
+    pub fn from_state(state: GameState) -> Self {
+        Self {
+            heatmap: Heatmap::new(state.size),
+            state,
+            setup: Setup::classic(),
+            time: Instant::now(),
+            time_step: Difficulty::default().time_step(),
+            ticks: 0,
+            quit: false,
+            turns: VecDeque::new(),
+            history: None,
+            frame_step: false,
+            step_requested: false,
+            frame_stepped: false,
+            tutorial: None,
+            demo: false,
+            idle_timeout: None,
+            last_activity: Instant::now(),
+            last_score: 0,
+            paused: false,
+            #[cfg(feature = "scripting")]
+            scripts: None,
+            #[cfg(feature = "audio")]
+            sounds: None,
+            plugins: Plugins::default(),
+            skin: Skin::default(),
+            strings: Strings::default(),
+            weather: Weather::new(WeatherKind::Off),
+            stats: RunStats::default(),
+            speedrun: None,
+            campaign: None,
+            stages: None,
+            shield: false,
+            controls: ControlScheme::Absolute,
+            keys: KeysConfig::default(),
+            speed: SpeedConfig::default(),
+            restart_requested: false,
+            view: View::default(),
+            moves: None,
+            bot: None,
+            rival: None,
+            battle: Vec::new(),
+            host: None,
+            replay: None,
+            start: None,
+            race_ghost: false,
+            ghost: None,
+            playback: None,
+            watch: None,
+            tick_log: None,
+            render_time: Duration::ZERO,
+            screen: Screen::default(),
+            frames: 0,
+            played: Duration::ZERO,
+            saved: None,
+            animate: false,
+            death: None,
+            themes: Vec::new(),
+        }
+    }
+
+    /// carry on with the saved run `slot` in the mode it was saved in,
+    /// keeping the other settings
+    fn resume(&mut self, slot: SaveFile) {
+        self.played = slot.played();
+        // the runs started after it play by the same rules
+        self.setup.mode = modes::find(&slot.state.mode);
+        if slot.mode == "practice" && self.history.is_none() {
+            self.enable_practice();
+        }
+        self.state = slot.state;
+        self.turns.clear();
+        // a loaded run can't be replayed from its seed
+        self.replay = None;
+    }
+
+    /// start a new run from `seed`, keeping the mode and settings
+    pub fn restart(&mut self, seed: u64) {
+        *self = Self {
+            history: self
+                .history
+                .as_ref()
+                .map(|_| VecDeque::with_capacity(HISTORY_LEN)),
+            frame_step: self.frame_step,
+            demo: self.demo,
+            race_ghost: self.race_ghost,
+            animate: self.animate,
+            controls: self.controls,
+            keys: self.keys.clone(),
+            stages: self.stages.as_ref().map(Stages::rewound),
+            speed: self.speed,
+            view: self.view,
+            moves: self.moves.as_ref().map(MoveScript::rewound),
+            bot: self.bot.take(),
+            rival: self.rival.take(),
+            battle: std::mem::take(&mut self.battle),
+            tick_log: self.tick_log.take(),
+            idle_timeout: self.idle_timeout,
+            setup: self.setup.clone(),
+            #[cfg(feature = "scripting")]
+            scripts: self.scripts.take(),
+            #[cfg(feature = "audio")]
+            sounds: self.sounds.take(),
+            plugins: std::mem::take(&mut self.plugins),
+            skin: self.skin.clone(),
+            strings: self.strings.clone(),
+            themes: std::mem::take(&mut self.themes),
+            weather: std::mem::replace(&mut self.weather, Weather::new(WeatherKind::Off)),
+            ..Self::from_state(self.setup.new_state(seed))
         };
-        let body: VecDeque<_> = (0..len)
-            .map(|i| head.clone_with_pos_shift(dir_rev, i))
-            .collect();
-        Self { body, dir }
+        if let Some(log) = &mut self.tick_log {
+            log.next_run();
+        }
+        self.run_hook("on_spawn");
+    }
+
+    /// have each battle snake's bot plan its next move, as that snake sees the board
+    fn plan_battle(&self) {
+        for (i, bot) in self.battle.iter().enumerate() {
+            if let Some(view) = self.state.bot_view(i) {
+                bot.ask(&view);
+            }
+        }
+    }
+
+    /// guided game for new players; nothing is saved or recorded
+    pub fn tutorial() -> Self {
+        let mut game = Self::new(Setup::classic(), rand::random());
+        game.tutorial = Some(Tutorial::new(&mut game.state));
+        game
     }
 
-    pub fn head(&self) -> &Cell {
-        self.body.front().unwrap()
+    /// practice mode: recent ticks can be rewound with `z`, the run is unranked
+    pub fn enable_practice(&mut self) {
+        self.history = Some(VecDeque::with_capacity(HISTORY_LEN));
     }
 
-    /// grow snake body when eating food
-    pub fn grow_body(&mut self) {
-        self.body
-            .push_front(self.head().clone_with_pos_shift(self.dir, 1));
+    pub fn mode(&self) -> &'static str {
+        if self.tutorial.is_some() {
+            "tutorial"
+        } else if self.history.is_some() {
+            "practice"
+        } else if self.speedrun.is_some() {
+            "speedrun"
+        } else if self.campaign.is_some() {
+            "campaign"
+        } else if self.stages.is_some() {
+            "stages"
+        } else if self.setup.maze {
+            "maze"
+        } else if self.setup.battle > 0 {
+            "battle"
+        } else {
+            self.state.rules().name()
+        }
+    }
+
+    fn rewind(&mut self) {
+        if let Some(history) = &mut self.history {
+            for _ in 0..REWIND_TICKS {
+                if let Some(state) = history.pop_back() {
+                    self.state = state;
+                }
+            }
+        }
+        if let Some(replay) = &mut self.replay {
+            replay.truncate(self.state.tick);
+        }
+        self.turns.clear();
     }
 
-    pub fn move_body(&mut self) {
-        self.body
-            .push_front(self.head().clone_with_pos_shift(self.dir, 1));
-        self.body.pop_back();
+    /// start over from the seed of `replay` and let it steer
+    fn play_replay(&mut self, replay: ShareReplay) {
+        self.restart(replay.seed);
+        self.shield = replay.shield;
+        self.playback = Some(Playback::new(replay));
     }
 
-    pub fn check_bite_body(&self) -> bool {
-        self.body.iter().skip(1).any(|c| c == self.head())
+    /// the replay file of the run, if it was recorded from the start
+    fn replay_file(&self) -> Option<ReplayFile> {
+        Some(ReplayFile::new(
+            self.mode(),
+            self.state.score,
+            self.start.as_ref()?,
+            self.state.power_ups.is_some(),
+            self.plugins.names(),
+            self.replay.as_ref()?,
+        ))
     }
 
-    pub fn check_bite_food(&self, food: &Cell) -> bool {
-        self.head() == food
+    /// practice, autopilot, replayed, move-scripted, frame-stepped and
+    /// rule-scripted runs are unranked
+    fn is_ranked(&self) -> bool {
+        self.history.is_none()
+            && self.bot.is_none()
+            && self.playback.is_none()
+            && self.moves.is_none()
+            && !self.frame_stepped
+            && !self.is_scripted()
     }
 
-    /// check if the snake body overlaps with food when generating food
-    pub fn check_overlap_food(&self, food: &Cell) -> bool {
-        self.body.iter().any(|c| c == food)
+    #[cfg(feature = "scripting")]
+    fn is_scripted(&self) -> bool {
+        self.scripts.is_some()
     }
 
-    pub fn check_collide_wall(&self, wall: &Wall) -> bool {
-        wall.cells.iter().any(|c| c == self.head())
+    #[cfg(not(feature = "scripting"))]
+    fn is_scripted(&self) -> bool {
+        false
     }
 
-    pub fn render<T: Write>(&self, buffer: &mut T) -> Result<()> {
-        for cell in &self.body {
-            cell.render(buffer, Color::Blue)?;
+    /// call a plugin and script hook
+    #[cfg(feature = "scripting")]
+    fn run_hook(&mut self, hook: &str) {
+        self.run_plugins(hook);
+        if let Some(scripts) = &mut self.scripts {
+            scripts.call(hook, &mut self.state);
         }
-        Ok(())
     }
-}
 
-struct Wall {
-    cells: Vec<Cell>,
-}
+    #[cfg(not(feature = "scripting"))]
+    fn run_hook(&mut self, hook: &str) {
+        self.run_plugins(hook);
+    }
 
-impl Wall {
-    pub fn new() -> Self {
-        let top_wall = (1..GND_SZ.0 / CELL_SZ.0).map(|i| (i * CELL_SZ.0, CELL_SZ.1));
-        let btm_wall = (1..GND_SZ.0 / CELL_SZ.0).map(|i| (i * CELL_SZ.0, GND_SZ.1));
-        let lft_wall = (2..GND_SZ.1 / CELL_SZ.1).map(|i| (CELL_SZ.0, i * CELL_SZ.1));
-        let rht_wall = (2..GND_SZ.1 / CELL_SZ.1).map(|i| (GND_SZ.0 - CELL_SZ.0, i * CELL_SZ.1));
-        Self {
-            cells: top_wall
-                .chain(lft_wall)
-                .chain(rht_wall)
-                .chain(btm_wall)
-                .map(|(x, y)| Cell::new(x, y))
-                .collect::<Vec<_>>(),
+    fn run_plugins(&mut self, hook: &str) {
+        match hook {
+            "on_spawn" => self.plugins.on_spawn(&mut self.state),
+            "on_tick" => {
+                let tick = self.tick_length();
+                self.plugins.on_tick(&mut self.state, tick)
+            }
+            _ => (),
         }
     }
 
-    pub fn render<T: Write>(&self, buffer: &mut T) -> Result<()> {
-        for cell in &self.cells {
-            cell.render(buffer, Color::White)?;
+    /// terminal row `n` lines below the board
+    fn hud_row(&self, n: u16) -> u16 {
+        self.view.extent(self.state.size).1 - 1 + n
+    }
+
+    /// how long a tick lasts in game time at the current speed
+    fn tick_length(&self) -> Duration {
+        self.time_step * self.watch.unwrap_or(1)
+    }
+
+    fn render_title<R: Renderer>(&self, out: &mut R) -> Result<()> {
+        let tick = self.tick_length().as_secs_f64();
+        let mut bar = hud::Bar::default();
+        let title = self.strings.title.clone();
+        bar.push(Rank::Title, title.with(self.skin.theme.text));
+        match &self.state.rival {
+            Some(rival) => {
+                let color = |tile| self.skin.theme.color(tile, false);
+                let p1 = format!("P1: {}", self.state.score);
+                let p2 = format!("{}: {}", if rival.duel { "P2" } else { "CPU" }, rival.score);
+                bar.push(Rank::Score, p1.with(color(Tile::Snake)));
+                bar.push(Rank::Score, p2.with(color(Tile::Rival)));
+            }
+            None => {
+                let score = format!("{}: {}", self.strings.score, self.state.score);
+                bar.push(Rank::Score, score.green())
+            }
+        }
+        if !self.state.bots.is_empty() {
+            let left = self.state.bots.iter().filter(|bot| !bot.crashed).count();
+            bar.push(Rank::Status, format!("Foes: {}", left).yellow());
+        }
+        bar.push(Rank::Time, hud::clock(self.played).white());
+        let length = self.state.snake.body.len();
+        bar.push(Rank::Length, format!("Length {}", length).white());
+        if self.speed.every > 0 {
+            let speed = format!("Speed {}", self.speed.level(self.state.score));
+            bar.push(Rank::Speed, speed.cyan());
+        }
+        let rate = 1.0 / self.time_step.as_secs_f64();
+        bar.push(Rank::Tick, format!("{:.1} ticks/s", rate).cyan());
+        if let Some(ticks) = self.state.rules().ticks_left(&self.state) {
+            let secs = tick * ticks as f64;
+            bar.push(Rank::Status, format!("Time: {:.0}s", secs.ceil()).yellow());
+        }
+        // the walls close in after a number of ticks, so this counts down at the current speed
+        if let Some(ticks) = self.state.rules().ticks_to_shrink(&self.state) {
+            let secs = tick * ticks as f64;
+            bar.push(
+                Rank::Status,
+                format!("Walls in {:.0}s", secs.ceil()).yellow(),
+            );
+        }
+        if self.shield {
+            bar.push(Rank::Status, "SHIELD".to_string().cyan());
+        }
+        if self
+            .saved
+            .is_some_and(|at| at.elapsed() < Duration::from_secs(SAVED_SECS))
+        {
+            bar.push(Rank::Status, "SAVED".to_string().green());
+        }
+        if let Some(boss) = &self.state.boss {
+            let text = if boss.trapped {
+                "BOSS TRAPPED".to_string()
+            } else {
+                format!("BOSS {:.0}s", (tick * boss.left as f64).ceil())
+            };
+            bar.push(Rank::Status, text.red().bold());
+        }
+        if self.state.ram > 0 {
+            let secs = tick * self.state.ram as f64;
+            bar.push(Rank::Status, format!("RAM {:.0}s", secs.ceil()).yellow());
+        }
+        if let Some(power_ups) = &self.state.power_ups {
+            for (kind, left) in power_ups.active(self.state.tick) {
+                let text = format!(
+                    "{} {:.0}s",
+                    kind.name().to_uppercase(),
+                    (tick * left as f64).ceil()
+                );
+                bar.push(Rank::Status, text.with(powerup::color(kind)));
+            }
+        }
+        bar.push(Rank::Seed, format!("Seed {}", self.state.seed).dark_grey());
+        for (x, text) in bar.layout(2, out.width()) {
+            out.draw_text(x, 0, text)?;
+        }
+        if self.history.is_some() {
+            let hint = if self.state.is_over {
+                "PRACTICE (unranked)  crashed - z: rewind  q: quit"
+            } else {
+                "PRACTICE (unranked)  z: rewind"
+            };
+            out.draw_text(10, self.hud_row(1), hint.yellow())?;
+        }
+        if let Some(tutorial) = &self.tutorial {
+            out.draw_text(10, self.hud_row(1), tutorial.prompt().yellow())?;
+        }
+        if self.demo {
+            out.draw_text(10, self.hud_row(1), "DEMO - press any key".yellow().bold())?;
+        }
+        if let Some(speed) = self.watch {
+            let text = match (self.state.is_over, self.frame_step) {
+                (true, _) => format!("REPLAY OVER  score {}  q: quit", self.state.score),
+                (false, true) => format!(
+                    "REPLAY PAUSED  tick {}  space: resume  right: next tick  q: quit",
+                    self.state.tick
+                ),
+                (false, false) => format!("REPLAY {}x  space: pause  1/2/4: speed  q: quit", speed),
+            };
+            out.draw_text(10, self.hud_row(1), text.yellow())?;
+        }
+        if self.host.is_some() && self.state.is_over {
+            out.draw_text(
+                10,
+                self.hud_row(1),
+                "GAME OVER - waiting for the host  q: quit".yellow(),
+            )?;
+        }
+        if let Some(attempt) = &self.campaign {
+            out.draw_text(10, self.hud_row(1), attempt.hud(self.played).cyan())?;
+        }
+        if let Some(stages) = &self.stages {
+            out.draw_text(10, self.hud_row(1), stages.hud().cyan())?;
+        }
+        if let Some(run) = &self.speedrun {
+            let mut x = 10;
+            for part in run.hud() {
+                let width = part.content().chars().count() as u16;
+                out.draw_text(x, self.hud_row(1), part)?;
+                x += width + 2;
+            }
+        }
+        if self.paused {
+            let idle = self
+                .idle_timeout
+                .is_some_and(|timeout| self.last_activity.elapsed() > timeout);
+            let text = if idle {
+                &self.strings.paused_idle
+            } else {
+                &self.strings.paused
+            };
+            let (width, height) = self.view.extent(self.state.size);
+            out.draw_text(
+                (width / 2).saturating_sub(text.chars().count() as u16 / 2),
+                height / 2,
+                text.as_str().white().bold(),
+            )?;
+        }
+        if self.frame_step && self.watch.is_none() {
+            let text = format!(
+                "FRAME STEP  tick {}  space: next tick  f: resume",
+                self.ticks
+            );
+            out.draw_text(10, self.hud_row(2), text.cyan())?;
+        }
+        #[cfg(feature = "scripting")]
+        if let Some(scripts) = &self.scripts {
+            let line = match (&scripts.error, &scripts.message) {
+                (Some(error), _) => format!("script error: {}", error).red(),
+                (None, Some(message)) => message.clone().yellow(),
+                (None, None) => String::new().reset(),
+            };
+            out.draw_text(10, self.hud_row(3), line)?;
         }
         Ok(())
     }
-}
-
-struct Game {
-    wall: Wall,
-    snake: Snake,
-    food: Cell,
-    score: u16,
-    time: Instant,
-    time_step: Duration,
-    is_over: bool,
-}
 
-impl Game {
-    pub fn new() -> Self {
-        Self {
-            wall: Wall::new(),
-            snake: Snake::new((GND_SZ.0 / 2, GND_SZ.1 / 2), Direction::Right, 3),
-            food: Cell::new(30, 30),
-            score: 0,
-            time: Instant::now(),
-            time_step: Duration::from_millis(TIME_STEP),
-            is_over: false,
+    /// gate cells to draw: walls for closed gates, or the seconds until the
+    /// gate changes once the countdown shows
+    fn gate_marks(&self) -> Vec<((u16, u16), Option<u64>)> {
+        let tick = self.state.tick;
+        let mut marks = Vec::new();
+        for gate in &self.state.gates {
+            let left = self.tick_length() * gate.ticks_to_change(tick) as u32;
+            let secs = left.as_secs_f64().ceil() as u64;
+            for cell in gate
+                .cells
+                .iter()
+                .filter(|c| !self.state.snake.body.contains(c))
+            {
+                if secs <= GATE_COUNTDOWN {
+                    marks.push((cell.pos, Some(secs)));
+                } else if gate.is_closed(tick) {
+                    marks.push((cell.pos, None));
+                }
+            }
         }
+        marks
     }
 
-    pub fn render_food<T: Write>(&self, buffer: &mut T) -> Result<()> {
-        self.food.render(buffer, Color::Red)?;
+    /// breakable walls and ram power-ups, with their glyph and the tile they take the color of
+    fn breakable_marks(&self) -> Vec<((u16, u16), &'static str, Tile)> {
+        let walls = self
+            .state
+            .breakables
+            .iter()
+            .map(|c| (c, BREAKABLE_GLYPH, Tile::Wall));
+        let rams = self.state.rams.iter().map(|c| (c, RAM_GLYPH, Tile::Bonus));
+        walls
+            .chain(rams)
+            .map(|(c, glyph, tile)| (c.pos, glyph, tile))
+            .collect()
+    }
+
+    fn render_breakables<R: Renderer>(&self, out: &mut R) -> Result<()> {
+        for (pos, glyph, tile) in self.breakable_marks() {
+            let color = self.skin.theme.color(tile, self.paused);
+            out.draw_cell(&self.view, pos, glyph.with(color))?;
+        }
+        if let Some((cell, kind)) = self.state.power_ups.as_ref().and_then(PowerUps::lying) {
+            let color = if self.paused {
+                self.skin.theme.color(Tile::Bonus, true)
+            } else {
+                powerup::color(kind)
+            };
+            out.draw_cell(&self.view, cell.pos, powerup::glyph(kind).with(color))?;
+        }
         Ok(())
     }
 
-    pub fn update_food_pos(&mut self) {
-        let x = rand::thread_rng().gen_range(1..GND_SZ.0 / CELL_SZ.0 - 1) * CELL_SZ.0;
-        let y = rand::thread_rng().gen_range(2..GND_SZ.1 / CELL_SZ.1 - 1) * CELL_SZ.1;
-        self.food.pos = (x, y);
+    /// both ends of every portal with their glyph, except where a snake is passing through
+    fn portal_marks(&self) -> Vec<((u16, u16), String)> {
+        let bots = self.state.bots.iter().filter(|bot| !bot.crashed);
+        let others: Vec<_> = self.state.rival.iter().chain(bots).collect();
+        let mut marks = Vec::new();
+        for portal in &self.state.portals {
+            for end in &portal.ends {
+                let on_other = others.iter().any(|other| other.snake.body.contains(end));
+                if self.state.snake.body.contains(end) || on_other {
+                    continue;
+                }
+                marks.push((end.pos, format!("{}{}", PORTAL_GLYPH, portal.id)));
+            }
+        }
+        marks
     }
 
-    fn render_title<T: Write>(&self, buffer: &mut T) -> Result<()> {
-        queue!(
-            buffer,
-            cursor::MoveTo(10, 0),
-            style::PrintStyledContent("Rust Snake Game".magenta())
-        )?;
-        queue!(
-            buffer,
-            cursor::MoveTo(40, 0),
-            style::PrintStyledContent(format!("Score: {}", self.score).green())
-        )?;
+    fn render_portals<R: Renderer>(&self, out: &mut R) -> Result<()> {
+        let color = self.skin.theme.color(Tile::Bonus, self.paused);
+        for (pos, glyph) in self.portal_marks() {
+            out.draw_cell(&self.view, pos, glyph.as_str().with(color))?;
+        }
         Ok(())
     }
 
-    pub fn render<T: Write>(&self, buffer: &mut T) -> Result<()> {
-        execute!(buffer, terminal::Clear(terminal::ClearType::All))?;
-        self.render_title(buffer)?;
-        self.snake.render(buffer)?;
-        self.render_food(buffer)?;
-        self.wall.render(buffer)?;
-        buffer.flush()?;
+    fn render_gates<R: Renderer>(&self, out: &mut R) -> Result<()> {
+        let color = self.skin.theme.color(Tile::Wall, self.paused);
+        for (pos, mark) in self.gate_marks() {
+            match mark {
+                Some(secs) => {
+                    let digit = format!("{:<2}", secs);
+                    out.draw_cell(&self.view, pos, digit.as_str().with(color))?;
+                }
+                None => render_cell(
+                    out,
+                    &self.view,
+                    &Cell::new(pos.0, pos.1),
+                    Tile::Wall,
+                    &self.skin,
+                    self.paused,
+                )?,
+            }
+        }
         Ok(())
     }
 
-    fn process_event(&mut self) -> Result<()> {
-        if event::poll(Duration::from_millis(0))? {
-            match event::read()? {
-                Event::Key(KeyEvent {
-                    code: KeyCode::Up, ..
-                }) => {
-                    if self.snake.dir != Direction::Down {
-                        self.snake.dir = Direction::Up;
+    /// every tile on the board, in drawing order so later ones cover earlier ones
+    fn board_tiles(&self) -> Vec<(Cell, Tile)> {
+        let state = &self.state;
+        let mut tiles: Vec<_> = state.snake.body.iter().map(|c| (c, Tile::Snake)).collect();
+        if let Some(rival) = &state.rival {
+            tiles.extend(rival.snake.body.iter().map(|c| (c, Tile::Rival)));
+        }
+        for bot in state.bots.iter().filter(|bot| !bot.crashed) {
+            tiles.extend(bot.snake.body.iter().map(|c| (c, Tile::Rival)));
+        }
+        tiles.extend(state.food.iter().map(|c| (c.clone(), Tile::Food)));
+        tiles.extend(state.extra_food.iter().map(|c| (c.clone(), Tile::Food)));
+        tiles.extend(state.poison.iter().map(|c| (c.clone(), Tile::Poison)));
+        tiles.extend(state.hazards.iter().map(|c| (c.clone(), Tile::Hazard)));
+        if let Some(boss) = &state.boss {
+            tiles.extend(boss.cells().map(|c| (c, Tile::Hazard)));
+        }
+        tiles.extend(state.wall.cells.iter().map(|c| (c.clone(), Tile::Wall)));
+        tiles.extend(self.plugins.overlay(state));
+        tiles
+    }
+
+    /// the board as plain text, one line per row and no escape codes, for
+    /// golden-file tests: the same state always gives the same frame
+    pub fn render_to_string(&self) -> String {
+        let (width, height) = self.state.size;
+        let empty = " ".repeat(CELL_SZ.0 as usize);
+        let mut grid = vec![vec![empty; width as usize]; height as usize];
+        let mut put = |(x, y): (u16, u16), glyph: String| {
+            if let Some(slot) = grid
+                .get_mut(y as usize)
+                .and_then(|row| row.get_mut(x as usize))
+            {
+                *slot = glyph;
+            }
+        };
+        for (cell, tile) in self.board_tiles() {
+            put(cell.pos, self.skin.glyphs.glyph(tile).to_string());
+        }
+        for (pos, mark) in self.gate_marks() {
+            let glyph = match mark {
+                Some(secs) => format!("{:<2}", secs),
+                None => self.skin.glyphs.glyph(Tile::Wall).to_string(),
+            };
+            put(pos, glyph);
+        }
+        for (pos, glyph, _) in self.breakable_marks() {
+            put(pos, glyph.to_string());
+        }
+        for (pos, glyph) in self.portal_marks() {
+            put(pos, glyph);
+        }
+        let mut frame = format!(
+            "tick {} score {} length {}{}\n",
+            self.state.tick,
+            self.state.score,
+            self.state.snake.body.len(),
+            if self.state.is_over { " over" } else { "" }
+        );
+        for row in grid {
+            frame.push_str(row.concat().trim_end());
+            frame.push('\n');
+        }
+        frame
+    }
+
+    /// the food color, pulsing brighter and back while the game runs
+    fn food_color(&self) -> Color {
+        let color = self.skin.theme.color(Tile::Food, self.paused);
+        if self.paused || !self.animate {
+            return color;
+        }
+        let phase = self.frames % PULSE_FRAMES;
+        let bright = phase.min(PULSE_FRAMES - phase) as f64 / (PULSE_FRAMES / 2) as f64;
+        colors::blend(color, Color::White, 0.6 * bright)
+    }
+
+    pub fn render<R: Renderer>(&self, out: &mut R) -> Result<()> {
+        out.clear()?;
+        if !self.paused {
+            // the board, title row and HUD stay clear of the weather
+            let board_right = self.view.extent(self.state.size).0;
+            let reserved = |x, y| y == 0 || (x < board_right && y <= self.hud_row(3));
+            self.weather.render(out, reserved)?;
+        }
+        // the ghost is always dimmed, and goes under everything else
+        for cell in self.ghost.iter().flat_map(Ghost::body) {
+            render_cell(out, &self.view, &cell, Tile::Snake, &self.skin, true)?;
+        }
+        // everything is drawn dimmed while paused
+        let len = self.state.snake.body.len();
+        for (i, (cell, tile)) in self.board_tiles().into_iter().enumerate() {
+            match tile {
+                // the snake comes first, head to tail
+                Tile::Snake if i < len => {
+                    let skin = &self.skin;
+                    let glyph = skin.snake.glyph(i, len, skin.glyphs.glyph(tile));
+                    let color = if self.paused {
+                        skin.theme.color(tile, true)
+                    } else {
+                        skin.snake.color(i, len, skin.theme.color(tile, false))
+                    };
+                    let look = self.death.as_ref().map_or(Look::Whole, |d| d.look(i));
+                    let debris = "*".repeat(usize::from(CELL_SZ.0));
+                    let styled = match look {
+                        Look::Whole => glyph.with(color),
+                        Look::Flash => glyph.with(Color::White),
+                        Look::Debris => debris.as_str().with(color),
+                        Look::Gone => continue,
+                    };
+                    out.draw_cell(&self.view, cell.pos, styled)?;
+                }
+                Tile::Food => {
+                    let glyph = self.skin.glyphs.glyph(tile);
+                    out.draw_cell(&self.view, cell.pos, glyph.with(self.food_color()))?;
+                }
+                _ => render_cell(out, &self.view, &cell, tile, &self.skin, self.paused)?,
+            }
+        }
+        self.render_gates(out)?;
+        self.render_breakables(out)?;
+        self.render_portals(out)?;
+        // point to the food when the camera can't show it
+        let color = self.food_color();
+        for cell in self.state.food.iter().chain(&self.state.extra_food) {
+            if let Some((pos, arrow)) = self.view.edge_toward(cell.pos) {
+                out.draw_cell(&self.view, pos, arrow.with(color))?;
+            }
+        }
+        self.render_title(out)?;
+        out.present()
+    }
+
+    /// draw the next frame to `buffer`, only where it differs from the last
+    fn redraw<T: Write>(&mut self, buffer: &mut T) -> Result<()> {
+        self.frames += 1;
+        #[cfg(feature = "audio")]
+        if let Some(sounds) = &mut self.sounds {
+            sounds.play(buffer)?;
+        }
+        let mut screen = std::mem::take(&mut self.screen);
+        let result = self.render(&mut screen.frame(buffer));
+        self.screen = screen;
+        result
+    }
+
+    /// steer with an arrow key according to the control scheme
+    fn arrow(&mut self, key: Direction) {
+        if self.bot.is_some()
+            || self.moves.as_ref().is_some_and(|moves| !moves.is_done())
+            || self.playback.as_ref().is_some_and(|p| !p.is_done())
+        {
+            return;
+        }
+        let heading = self.heading();
+        match (self.controls, key) {
+            (ControlScheme::Absolute, dir) => self.steer(dir),
+            (ControlScheme::Relative, Direction::Left) => self.steer(heading.turn_left()),
+            (ControlScheme::Relative, Direction::Right) => self.steer(heading.turn_right()),
+            (ControlScheme::Relative, _) => (),
+        }
+    }
+
+    /// where the snake is headed once the queued turns are made
+    fn heading(&self) -> Direction {
+        self.turns.back().copied().unwrap_or(self.state.snake.dir)
+    }
+
+    /// queue a turn of the snake
+    fn steer(&mut self, dir: Direction) {
+        let heading = self.heading();
+        if dir != heading && dir != heading.opposite() && self.turns.len() < TURN_QUEUE {
+            self.turns.push_back(dir);
+        }
+    }
+
+    fn process_event<T: Write>(&mut self, buffer: &mut T) -> Result<()> {
+        if input::poll(Duration::from_millis(0))? {
+            self.last_activity = Instant::now();
+            // time spent in menus doesn't count towards a speedrun
+            if let Some(run) = &mut self.speedrun {
+                run.pause();
+            }
+            let code = match input::read()? {
+                Event::Key(KeyEvent { code, .. }) => code,
+                // the terminal may have reflowed what it showed
+                Event::Resize(..) => {
+                    self.screen.invalidate();
+                    KeyCode::Null
+                }
+                _ => KeyCode::Null,
+            };
+            if self.demo {
+                self.quit = code != KeyCode::Null;
+                return Ok(());
+            }
+            if let Some(speed) = &mut self.watch {
+                match code {
+                    KeyCode::Char(' ') => {
+                        self.frame_step = !self.frame_step;
+                        self.time = Instant::now();
                     }
+                    KeyCode::Right if self.frame_step => self.step_requested = true,
+                    KeyCode::Char('1') => *speed = 1,
+                    KeyCode::Char('2') => *speed = 2,
+                    KeyCode::Char('4') => *speed = 4,
+                    KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+                    _ => (),
                 }
-                Event::Key(KeyEvent {
-                    code: KeyCode::Down,
-                    ..
-                }) => {
-                    if self.snake.dir != Direction::Up {
-                        self.snake.dir = Direction::Down;
+                return Ok(());
+            }
+            if let Some(host) = &self.host {
+                match self.keys.action(code, false) {
+                    Some(Action::Steer(dir)) => {
+                        let _ = host.send(&Message::Turn(dir));
                     }
+                    Some(Action::Quit) => self.quit = true,
+                    _ => (),
                 }
-                Event::Key(KeyEvent {
-                    code: KeyCode::Left,
-                    ..
-                }) => {
-                    if self.snake.dir != Direction::Right {
-                        self.snake.dir = Direction::Left;
+                return Ok(());
+            }
+            let two_player = self.rival.as_ref().is_some_and(Controller::takes_keys);
+            match (self.keys.action(code, two_player), code) {
+                (Some(Action::Steer(dir)), _) => self.arrow(dir),
+                (Some(Action::SteerTwo(dir)), _) => self.state.turn_rival(dir),
+                (Some(Action::Pause), _) if !self.state.is_over => self.paused = true,
+                (Some(Action::Quit), _) => {
+                    if !self.state.is_over && self.tutorial.is_none() {
+                        let _ =
+                            save::write_slot(save::AUTOSAVE, self.mode(), &self.state, self.played);
+                    }
+                    self.quit = true;
+                }
+                (Some(Action::Restart), _) if self.tutorial.is_none() => {
+                    self.restart_requested = true
+                }
+                (Some(Action::Save), _) if !self.state.is_over && self.tutorial.is_none() => {
+                    let written =
+                        save::write_slot(save::QUICKSAVE, self.mode(), &self.state, self.played);
+                    self.saved = written.ok().map(|_| Instant::now());
+                }
+                (Some(_), _) => (),
+                (None, KeyCode::F(5)) => {
+                    slots::save_screen(buffer, self.mode(), &self.state, self.played)?;
+                    self.screen.invalidate();
+                    self.time = Instant::now();
+                }
+                (None, KeyCode::F(9)) if self.speedrun.is_none() => {
+                    match slots::load_screen(buffer)? {
+                        Some(Loaded::State(slot)) => self.resume(*slot),
+                        Some(Loaded::Replay(replay)) => self.play_replay(replay),
+                        None => (),
                     }
+                    self.screen.invalidate();
+                    self.time = Instant::now();
                 }
+                (None, KeyCode::Char('o')) if self.tutorial.is_none() => {
+                    self.open_options(buffer)?;
+                }
+                (None, KeyCode::Char('f')) => {
+                    self.frame_step = !self.frame_step;
+                    self.time = Instant::now();
+                }
+                (None, KeyCode::Char(' ')) if self.frame_step => self.step_requested = true,
+                (None, KeyCode::Char('z')) => self.rewind(),
+                _ => (),
+            };
+            if let Some(run) = &mut self.speedrun {
+                run.resume();
+            }
+        }
+        Ok(())
+    }
+
+    /// the options screen; what was changed there applies right away, the
+    /// wrap mode from the next run
+    fn open_options<T: Write>(&mut self, buffer: &mut T) -> Result<()> {
+        let themes: Vec<_> = self.themes.iter().map(|t| t.name.clone()).collect();
+        let start_ms = Config::load().speed.start_ms;
+        let config = options::options_screen(buffer, &themes)?;
+        self.plugins.sync(&config.plugins.enabled, &mut self.state);
+        self.weather.kind = config.game.weather;
+        self.setup.tail_rule = config.rules.tail;
+        self.state.tail_rule = config.rules.tail;
+        self.controls = config.controls.scheme;
+        // a `--speed` start stays unless the speed was changed here
+        self.speed = SpeedConfig {
+            start_ms: if config.speed.start_ms == start_ms {
+                self.speed.start_ms
+            } else {
+                config.speed.start_ms
+            },
+            ..config.speed
+        };
+        #[cfg(feature = "audio")]
+        {
+            self.sounds = audio::Sounds::new(config.audio);
+        }
+        if let Some(theme) = self.themes.iter().find(|t| t.name == config.game.theme) {
+            self.skin.theme = theme.clone();
+        }
+        // another mode asked for with `--mode` stays
+        if ["classic", "wrap"].contains(&self.setup.mode.name()) {
+            self.setup.mode = modes::find(default_mode(&config));
+        }
+        self.keys = config.keys;
+        self.screen.invalidate();
+        self.time = Instant::now();
+        Ok(())
+    }
+
+    fn tick(&mut self) {
+        // a watched replay runs faster, its clock shouldn't
+        self.played += self.tick_length();
+        self.frame_stepped |= self.frame_step;
+        if let Some(history) = &mut self.history {
+            if history.len() == HISTORY_LEN {
+                history.pop_front();
+            }
+            history.push_back(self.state.clone());
+        }
+        if let Some(playback) = &mut self.playback {
+            while let Some(dir) = playback.next_at(self.state.tick) {
+                self.state.turn(dir);
+            }
+        }
+        if let Some(dir) = self.turns.pop_front() {
+            let heading = self.state.snake.dir;
+            self.state.turn(dir);
+            if self.state.snake.dir != heading {
+                if let Some(tutorial) = &mut self.tutorial {
+                    tutorial.on_turn();
+                }
+            }
+        }
+        let tick = self.state.tick;
+        let before = self.shield.then(|| self.state.clone());
+        let turn = self.moves.as_mut().and_then(|moves| moves.poll());
+        let turn = turn.or_else(|| self.bot.as_ref()?.answer(self.state.tick));
+        if let Some(dir) = self
+            .rival
+            .as_mut()
+            .and_then(|rival| rival.turn(&self.state))
+        {
+            self.state.turn_rival(dir);
+        }
+        for (i, bot) in self.battle.iter().enumerate() {
+            if let Some(dir) = bot.answer(tick) {
+                self.state.turn_bot(i, dir);
+            }
+        }
+        self.state.step(turn);
+        if let (true, Some(before)) = (self.state.is_over, before) {
+            // the shield takes the hit; the snake gets a tick to turn away
+            self.state = before;
+            self.shield = false;
+        }
+        // a remote player who left loses the duel
+        if self.rival.as_ref().is_some_and(Controller::left) {
+            self.state.forfeit_rival();
+        }
+        let length = self.tick_length();
+        if let Some(ghost) = &mut self.ghost {
+            ghost.tick(length);
+        }
+        if let Some(bot) = &self.bot {
+            bot.ask(&self.state);
+        }
+        if let Some(rival) = &self.rival {
+            rival.plan(&self.state);
+        }
+        self.plan_battle();
+        self.ticks += 1;
+        self.heatmap.record(&self.state);
+        if let Some(tutorial) = &mut self.tutorial {
+            tutorial.after_tick(&mut self.state);
+            return;
+        }
+        if self.demo {
+            self.state.bus.clear();
+            return;
+        }
+        let notices = std::mem::take(&mut self.state.bus);
+        if let Some(replay) = &mut self.replay {
+            for notice in &notices {
+                if let Notice::Turned(dir) = notice {
+                    replay.record(tick, *dir);
+                }
+            }
+        }
+        #[cfg(feature = "audio")]
+        if let Some(sounds) = &mut self.sounds {
+            sounds.hear(&notices);
+        }
+        if notices.iter().any(|n| matches!(n, Notice::Ate(_))) {
+            self.run_hook("on_eat");
+        }
+        if notices.iter().any(|n| matches!(n, Notice::Crashed(_))) {
+            self.run_hook("on_collision");
+        }
+        self.run_hook("on_tick");
+        // hooks may have posted notices of their own
+        for notice in notices.into_iter().chain(self.state.bus.drain(..)) {
+            self.stats.record(notice, self.state.tick);
+        }
+        if let Some(log) = &mut self.tick_log {
+            let since_food = self.stats.ticks_since_food();
+            let _ = log.record(&self.state, since_food, self.render_time);
+        }
+        if let Some(run) = &mut self.speedrun {
+            run.update(&self.state);
+        }
+        if let Some(attempt) = &mut self.campaign {
+            let food = self.stats.foods.values().sum();
+            attempt.update(food, self.played);
+        }
+        // a watched replay is a finished run, nothing of it is saved again
+        if self.watch.is_some() {
+            return;
+        }
+        if self.state.is_over {
+            // a finished run can't be continued
+            save::untrack();
+            save::remove_slot(save::AUTOSAVE);
+        } else if self.ticks.is_multiple_of(AUTOSAVE_TICKS) {
+            // an emergency save after a crash resumes from here as well
+            save::track(self.mode(), &self.state, self.played);
+            let _ = save::write_slot(save::AUTOSAVE, self.mode(), &self.state, self.played);
+        }
+    }
+
+    /// move on to the next stage keeping the score, or win after the last one
+    fn next_stage<T: Write>(&mut self, buffer: &mut T) -> Result<()> {
+        let stages = match &mut self.stages {
+            Some(stages) => stages,
+            None => return Ok(()),
+        };
+        let level = match stages.advance() {
+            Some(stage) => stage.level.clone(),
+            None => {
+                self.state.won = true;
+                self.state.is_over = true;
+                return Ok(());
+            }
+        };
+        levels::transition_screen(buffer, stages)?;
+        self.screen.invalidate();
+        let mut setup = self.setup.clone();
+        setup.level = level;
+        let score = self.state.score;
+        self.state = setup.new_state(self.state.rng.next_u64());
+        self.state.score = score;
+        // a share string, ghost or rewind can't cross into another layout
+        self.replay = None;
+        self.ghost = None;
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+        self.heatmap = Heatmap::new(self.state.size);
+        self.run_hook("on_spawn");
+        self.time = Instant::now();
+        Ok(())
+    }
+
+    /// whether the run ended by reaching its goal rather than by crashing
+    fn is_won(&self) -> bool {
+        self.state.won
+            || self.speedrun.as_ref().is_some_and(|r| r.is_complete())
+            || self.campaign.as_ref().is_some_and(|a| a.is_complete())
+    }
+
+    /// the player who won a two-player run: the one left standing, or the
+    /// higher score if neither crashed; `None` for a draw
+    fn duel_winner(&self) -> Option<u8> {
+        let rival = self.state.rival.as_ref().filter(|r| r.duel)?;
+        match (self.stats.crash.is_some(), rival.crashed) {
+            (true, false) => Some(2),
+            (false, true) => Some(1),
+            (true, true) => None,
+            (false, false) => match self.state.score.cmp(&rival.score) {
+                std::cmp::Ordering::Greater => Some(1),
+                std::cmp::Ordering::Less => Some(2),
+                std::cmp::Ordering::Equal => None,
+            },
+        }
+    }
+
+    /// the results of the run so far, for `--results-json` and `--results-csv`
+    fn result(&self) -> RunResult {
+        RunResult {
+            seed: self.state.seed,
+            mode: self.mode().to_string(),
+            score: self.state.score,
+            length: self.state.snake.body.len(),
+            duration_secs: self.played.as_secs_f64(),
+            ticks: self.stats.ticks,
+            end: results::end_name(self.stats.crash, self.is_won()),
+            foods: self.stats.foods.clone(),
+            turns: self.stats.turns,
+            distance: self.stats.distance,
+            max_combo: self.stats.max_combo,
+        }
+    }
+
+    /// take the states the host sent; a goodbye from it ends the game
+    fn sync_with_host(&mut self) {
+        let host = match &self.host {
+            Some(host) => host,
+            None => return,
+        };
+        for message in host.received() {
+            match message {
+                Message::State(state) => self.state = *state,
+                Message::Turn(_) => (),
+                Message::Bye => self.quit = true,
+            }
+        }
+    }
+
+    /// in practice mode a crash waits for a rewind instead of ending the run;
+    /// a joined game goes on for as long as the host keeps it going, and a
+    /// watched replay until it is closed
+    fn is_finished(&self) -> bool {
+        let tutorial_done = self.tutorial.as_ref().is_some_and(|t| t.is_complete());
+        let speedrun_done = self.speedrun.as_ref().is_some_and(|r| r.is_complete());
+        let level_done = self.campaign.as_ref().is_some_and(|a| a.is_complete());
+        self.quit
+            || self.restart_requested
+            || tutorial_done
+            || speedrun_done
+            || level_done
+            || (self.state.is_over
+                && self.history.is_none()
+                && self.host.is_none()
+                && self.watch.is_none())
+    }
+
+    /// pause once the player seems to have walked away
+    fn check_idle(&mut self) {
+        if self.state.score != self.last_score {
+            self.last_score = self.state.score;
+            self.last_activity = Instant::now();
+        }
+        let active = !self.frame_step && !self.state.is_over;
+        if let Some(timeout) = self.idle_timeout {
+            // a pause asked for with `p` stays until a key is pressed
+            self.paused |= active && self.last_activity.elapsed() > timeout;
+        }
+    }
+
+    /// keep the dimmed screen up until a key is pressed, without spinning
+    fn wait_while_paused<T: Write>(&mut self, buffer: &mut T) -> Result<()> {
+        self.redraw(buffer)?;
+        if let Some(run) = &mut self.speedrun {
+            run.pause();
+        }
+        loop {
+            match input::read()? {
                 Event::Key(KeyEvent {
-                    code: KeyCode::Right,
+                    code: KeyCode::Char('o'),
                     ..
-                }) => {
-                    if self.snake.dir != Direction::Left {
-                        self.snake.dir = Direction::Right;
-                    }
+                }) if self.tutorial.is_none() => {
+                    self.open_options(buffer)?;
+                    self.redraw(buffer)?;
                 }
+                Event::Key(_) => break,
+                _ => (),
+            }
+        }
+        if let Some(run) = &mut self.speedrun {
+            run.resume();
+        }
+        self.paused = false;
+        self.last_activity = Instant::now();
+        self.time = Instant::now();
+        Ok(())
+    }
+
+    /// hold the game on a notice while the terminal is smaller than `need`,
+    /// until it is resized or `q` is pressed
+    fn wait_for_room<T: Write>(&mut self, buffer: &mut T, need: (u16, u16)) -> Result<()> {
+        if let Some(run) = &mut self.speedrun {
+            run.pause();
+        }
+        let mut term = terminal::size()?;
+        while (term.0 < need.0 || term.1 < need.1) && !self.quit {
+            execute!(
+                buffer,
+                terminal::Clear(terminal::ClearType::All),
+                cursor::MoveTo(0, 0),
+                style::PrintStyledContent("terminal too small".red()),
+                cursor::MoveTo(0, 1),
+                style::Print(format!(
+                    "{}x{}, needs {}x{}",
+                    term.0, term.1, need.0, need.1
+                )),
+                cursor::MoveTo(0, 2),
+                style::Print("q: quit"),
+            )?;
+            match input::read()? {
+                Event::Resize(w, h) => term = (w, h),
                 Event::Key(KeyEvent {
-                    code: KeyCode::Char('q'),
+                    code: KeyCode::Char('q') | KeyCode::Esc,
                     ..
-                }) => self.is_over = true,
+                }) => self.quit = true,
                 _ => (),
-            };
-            // flush bufferred events before next loop
-            while event::poll(Duration::from_millis(0))? {
-                event::read()?;
             }
         }
+        self.screen.invalidate();
+        if let Some(run) = &mut self.speedrun {
+            run.resume();
+        }
+        self.last_activity = Instant::now();
+        self.time = Instant::now();
         Ok(())
     }
 
-    fn update_game_state(&mut self) {
-        if self.snake.check_bite_body() || self.snake.check_collide_wall(&self.wall) {
-            self.is_over = true;
+    /// run the ticks due since the last one: `time` is how far the game has
+    /// been simulated, and it keeps up with the clock one time step at a time
+    fn simulate(&mut self) {
+        let running = !self.state.is_over && !self.paused && self.host.is_none();
+        let requested = std::mem::take(&mut self.step_requested);
+        if !running || self.frame_step {
+            if running && requested {
+                self.tick();
+            }
+            self.time = Instant::now();
+            return;
+        }
+        let mut ticks = 0;
+        while self.time.elapsed() >= self.time_step && !self.state.is_over {
+            if ticks == MAX_CATCH_UP {
+                // too far behind to catch up: carry on from now
+                self.time = Instant::now();
+                break;
+            }
+            self.tick();
+            self.time += self.time_step;
+            ticks += 1;
+        }
+    }
+
+    pub fn looping<T: Write>(&mut self, buffer: &mut T) -> Result<()> {
+        // a share string only carries player one's turns
+        if self.state.tick == 0 && self.state.rival.is_none() && self.state.bots.is_empty() {
+            self.replay = Some(ShareReplay::new(self.state.seed, self.shield));
+            self.start = Some(self.state.clone());
+            // a rewind would leave the ghost behind
+            if self.race_ghost && self.history.is_none() && self.playback.is_none() {
+                self.ghost = Ghost::load(&self.state);
+            }
+        }
+        if let Some(bot) = &self.bot {
+            bot.ask(&self.state);
         }
-        if self.snake.check_bite_food(&self.food) {
-            self.score += 1;
-            self.snake.grow_body();
-            // generate new food: update food position
+        if let Some(rival) = &self.rival {
+            rival.plan(&self.state);
+        }
+        self.plan_battle();
+        self.time = Instant::now();
+        while !self.is_finished() {
+            self.check_idle();
+            if self.paused {
+                self.wait_while_paused(buffer)?;
+                continue;
+            }
+            let term = terminal::size()?;
+            let need = self.view.min_term(self.state.size);
+            if term.0 < need.0 || term.1 < need.1 {
+                self.wait_for_room(buffer, need)?;
+                continue;
+            }
+            self.sync_with_host();
+            self.weather.advance(term);
+            // the player who joined follows the rival
+            let head = match (&self.host, &self.state.rival) {
+                (Some(_), Some(rival)) => rival.snake.head().pos,
+                _ => self.state.snake.head().pos,
+            };
+            self.view
+                .follow(head, self.state.size, &self.state.rooms, term);
+            self.screen.fit(term);
+            let started = Instant::now();
+            self.redraw(buffer)?;
+            self.render_time = started.elapsed();
+            // keys are handled as they come in until the next frame is due
+            let next_frame = started + Duration::from_millis(FRAME_TIME);
             loop {
-                self.update_food_pos();
-                if !self.snake.check_overlap_food(&self.food) {
+                let left = next_frame.saturating_duration_since(Instant::now());
+                if left.is_zero() || !input::poll(left)? {
                     break;
                 }
+                self.process_event(buffer)?;
             }
-        } else {
-            self.snake.move_body();
+            self.time_step = self
+                .speed
+                .time_step(self.setup.difficulty.time_step(), self.state.score)
+                .mul_f64(
+                    self.state
+                        .power_ups
+                        .as_ref()
+                        .map_or(1.0, PowerUps::time_factor),
+                )
+                / self.watch.unwrap_or(1);
+            self.simulate();
+            let cleared = self
+                .stages
+                .as_ref()
+                .is_some_and(|s| s.is_cleared(self.state.score));
+            if cleared && !self.state.is_over {
+                self.next_stage(buffer)?;
+            }
+        }
+        // the crash is heard even if it isn't played out
+        #[cfg(feature = "audio")]
+        if let Some(sounds) = &mut self.sounds {
+            sounds.play(buffer)?;
+            buffer.flush()?;
         }
+        let crashed = self.state.is_over && !self.is_won();
+        if self.animate && crashed && !self.quit && !self.restart_requested {
+            self.play_death(buffer)?;
+        }
+        Ok(())
     }
 
-    pub fn looping<T: Write>(&mut self, buffer: &mut T) -> Result<()> {
-        while !self.is_over {
-            self.render(buffer)?;
-            self.process_event()?;
-            if self.time.elapsed() > self.time_step {
-                self.update_game_state();
-                self.time = Instant::now();
+    /// play out the crash before the game over screen; a key skips the rest
+    fn play_death<T: Write>(&mut self, buffer: &mut T) -> Result<()> {
+        let death = Death::new(self.state.snake.body.len());
+        self.death = Some(death);
+        while self.death.as_ref().is_some_and(|death| !death.is_done()) {
+            let started = Instant::now();
+            self.redraw(buffer)?;
+            let left = Duration::from_millis(FRAME_TIME).saturating_sub(started.elapsed());
+            if input::poll(left)? && matches!(input::read()?, Event::Key(_)) {
+                break;
             }
-            thread::sleep(self.time_step / 2); // screen refreshing rate
         }
+        self.death = None;
         Ok(())
     }
 }
 
+/// show a question on a cleared screen and wait for a y/n answer
+fn prompt_yes_no<T: Write>(buffer: &mut T, question: &str) -> Result<bool> {
+    execute!(
+        buffer,
+        terminal::Clear(terminal::ClearType::All),
+        cursor::MoveTo(10, 0),
+        style::PrintStyledContent(format!("{} [y/n]", question).yellow())
+    )?;
+    loop {
+        if let Event::Key(KeyEvent { code, .. }) = input::read()? {
+            match code {
+                KeyCode::Char('y') => return Ok(true),
+                KeyCode::Char('n') | KeyCode::Esc => return Ok(false),
+                _ => (),
+            }
+        }
+    }
+}
+
+enum Retry {
+    SameSeed,
+    NewSeed,
+    Quit,
+}
+
+/// summary lines of a finished run, each flagged if it beats the previous best
+fn run_summary(game: &Game, bests: &Bests) -> Vec<(String, bool)> {
+    let stats = &game.stats;
+    let best = |value: String, best: String, beaten: bool| {
+        if beaten {
+            (format!("{}  NEW BEST", value), true)
+        } else {
+            (format!("{}  (best {})", value, best), false)
+        }
+    };
+    let clock = |secs: u64| format!("{}:{:02}", secs / 60, secs % 60);
+    let survived = game.played.as_secs();
+    let mut lines = Vec::new();
+    let mut foods: Vec<_> = stats
+        .foods
+        .iter()
+        .map(|(kind, n)| format!("{} {}", kind, n))
+        .collect();
+    if foods.is_empty() {
+        foods.push("none".to_string());
+    }
+    lines.extend([
+        best(
+            format!("{}: {}", game.strings.score, game.state.score),
+            bests.score.to_string(),
+            game.state.score > bests.score,
+        ),
+        (format!("Foods: {}", foods.join(", ")), false),
+        best(
+            format!("Max combo: {}", stats.max_combo),
+            bests.max_combo.to_string(),
+            stats.max_combo > bests.max_combo,
+        ),
+        best(
+            format!("Time survived: {}", clock(survived)),
+            clock(bests.survived_secs),
+            survived > bests.survived_secs,
+        ),
+        (
+            format!("Average speed: {:.1} cells/s", stats.speed(game.played)),
+            false,
+        ),
+        (format!("Turns made: {}", stats.turns), false),
+        best(
+            format!("Distance: {} cells", stats.distance),
+            bests.distance.to_string(),
+            stats.distance > bests.distance,
+        ),
+    ]);
+    if let Some(rival) = &game.state.rival {
+        let name = if rival.duel { "Player 2" } else { "CPU" };
+        lines.insert(1, (format!("{} score: {}", name, rival.score), false));
+    }
+    if let Some(run) = &game.speedrun {
+        lines.extend(run.summary());
+    }
+    if let Some(attempt) = &game.campaign {
+        let medal = attempt.medal().map_or("none", |m| m.name());
+        lines.push((format!("Medal: {}", medal), attempt.medal().is_some()));
+    }
+    lines
+}
+
+/// stats of the finished run compared against the personal `bests` before it
+fn game_over_screen<T: Write>(
+    buffer: &mut T,
+    game: &Game,
+    bests: &Bests,
+    coins: u32,
+    profile: &mut Profile,
+    session: &Session,
+    scores: (&Scores, Option<usize>, &[StyledContent<String>]),
+) -> Result<Retry> {
+    let state = &game.state;
+    let mut summary = run_summary(game, bests);
+    summary.push((format!("Coins: +{}", coins), false));
+    loop {
+        execute!(buffer, terminal::Clear(terminal::ClearType::All))?;
+        queue!(
+            buffer,
+            cursor::MoveTo(10, 0),
+            style::PrintStyledContent(match &game.speedrun {
+                Some(run) if run.is_complete() => "SPEEDRUN COMPLETE".green().bold(),
+                _ if game.campaign.as_ref().is_some_and(|a| a.is_complete()) => {
+                    "LEVEL COMPLETE".green().bold()
+                }
+                _ if state.boss.as_ref().is_some_and(|b| b.is_defeated()) => {
+                    "BOSS DEFEATED".green().bold()
+                }
+                _ if state.rival.as_ref().is_some_and(|r| r.duel) => match game.duel_winner() {
+                    Some(1) => "PLAYER 1 WINS".green().bold(),
+                    Some(_) => "PLAYER 2 WINS".green().bold(),
+                    None => "DRAW".yellow().bold(),
+                },
+                _ if state.won => game.strings.you_win.as_str().green().bold(),
+                _ => game.strings.game_over.as_str().red().bold(),
+            })
+        )?;
+        for (i, (line, beaten)) in summary.iter().enumerate() {
+            queue!(
+                buffer,
+                cursor::MoveTo(10, 2 + i as u16),
+                style::PrintStyledContent(if *beaten {
+                    line.clone().yellow().bold()
+                } else {
+                    line.clone().green()
+                })
+            )?;
+        }
+        let row = 3 + summary.len() as u16;
+        let mut hint = if session.is_over() {
+            "enter: session results  h: heatmap  u: unlocks".to_string()
+        } else {
+            "r: new game  s: retry same seed  b: scoreboard  h: heatmap  u: unlocks  q: quit"
+                .to_string()
+        };
+        if game.replay.is_some() {
+            hint.push_str("  x: share replay");
+        }
+        queue!(
+            buffer,
+            cursor::MoveTo(10, row),
+            style::PrintStyledContent(format!("Seed: {}", state.seed).dark_grey()),
+            cursor::MoveTo(10, row + 2),
+            style::PrintStyledContent(hint.yellow())
+        )?;
+        // the new entry of this run, if it made the table, is highlighted
+        let local = scores.0.lines(scores.1);
+        for (i, line) in local.iter().chain(scores.2).enumerate() {
+            // a blank line between the local and the global table
+            let gap = if i < local.len() { 0 } else { 1 };
+            queue!(
+                buffer,
+                cursor::MoveTo(10, row + 4 + gap + i as u16),
+                style::PrintStyledContent(line.clone())
+            )?;
+        }
+        buffer.flush()?;
+        if let Event::Key(KeyEvent { code, .. }) = input::read()? {
+            match code {
+                KeyCode::Char('h') => game.heatmap.show(buffer, &game.view, state)?,
+                KeyCode::Char('u') => shop::shop_screen(buffer, profile)?,
+                KeyCode::Char('x') => {
+                    if let Some(replay) = &game.replay {
+                        share::share_screen(buffer, replay)?;
+                    }
+                }
+                // the last round of the session has been played
+                KeyCode::Enter if session.is_over() => return Ok(Retry::Quit),
+                _ if session.is_over() => (),
+                KeyCode::Char('b') => session.show(buffer, false)?,
+                KeyCode::Char('s') => return Ok(Retry::SameSeed),
+                KeyCode::Char('r') => return Ok(Retry::NewSeed),
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(Retry::Quit),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// the skin from the config or `--theme`, falling back to defaults for items
+/// not bought yet
+fn configured_skin(content: &Content, config: &Config, cli: &Cli, profile: &Profile) -> Skin {
+    let theme = cli.theme.as_deref().unwrap_or(&config.game.theme);
+    let (theme, glyphs) = if cli.colorblind || config.display.colorblind {
+        ("colorblind", "shapes")
+    } else if high_contrast(config, cli) {
+        (theme, "shapes")
+    } else {
+        shop::usable_skin(profile, theme, &config.game.glyphs)
+    };
+    let mut skin = content.skin(theme, glyphs);
+    skin.snake = SnakeSkin::new(&config.skin).unwrap_or_else(|e| cli::exit_with_error(&e));
+    if uses_ascii(config, cli) && !skin.glyphs.is_ascii() {
+        skin.glyphs = Glyphs::ascii();
+    }
+    skin
+}
+
+/// the mode played unless `--mode` asks for another
+fn default_mode(config: &Config) -> &'static str {
+    if config.rules.wrap {
+        "wrap"
+    } else {
+        "classic"
+    }
+}
+
+/// the themes the player may use: all but the ones sold in the shop and not bought yet
+fn usable_themes(content: &Content, profile: &Profile) -> Vec<Theme> {
+    let themes = content.themes.iter();
+    themes
+        .filter(|theme| !shop::is_theme_locked(profile, &theme.name))
+        .cloned()
+        .collect()
+}
+
+/// whether to draw with ASCII only: `--ascii`, the config or the locale
+fn uses_ascii(config: &Config, cli: &Cli) -> bool {
+    cli.ascii || config.display.ascii.unwrap_or_else(ascii::detect)
+}
+
+/// whether to draw in bold white on black: `--no-color` or `[display] colors`
+fn high_contrast(config: &Config, cli: &Cli) -> bool {
+    cli.no_color || config.display.colors == ColorDepth::Contrast
+}
+
+/// the terminal, drawn to in the colors and characters it can show
+fn terminal_output(config: &Config, cli: &Cli) -> ColorWriter<AsciiWriter<Stdout>> {
+    let out = AsciiWriter::new(stdout(), uses_ascii(config, cli));
+    let depth = if high_contrast(config, cli) {
+        ColorDepth::Contrast
+    } else {
+        config.display.colors
+    };
+    ColorWriter::new(out, depth)
+}
+
+/// attract mode: the autopilot plays run after run of `setup` until a key is pressed
+fn run_demo<T: Write>(buffer: &mut T, setup: &Setup, skin: &Skin, view: View) -> Result<()> {
+    let setup = Setup {
+        two_player: false,
+        opponent: false,
+        battle: 0,
+        ..setup.clone()
+    };
+    let mut demo = Game::new(setup, rand::random());
+    demo.demo = true;
+    demo.bot = Some(Bot::spawn(demo.setup.difficulty.time_step() / 2));
+    demo.skin = skin.clone();
+    demo.view = view;
+    loop {
+        demo.looping(buffer)?;
+        if demo.quit {
+            return Ok(());
+        }
+        demo.restart(rand::random());
+    }
+}
+
+/// `--join`: steer the rival in the game `host` runs, until either side quits
+fn join_game<T: Write>(buffer: &mut T, host: Link, skin: Skin, view: View) -> Result<()> {
+    execute!(
+        buffer,
+        terminal::Clear(terminal::ClearType::All),
+        cursor::MoveTo(10, 0),
+        style::PrintStyledContent("Connected, waiting for the host to start".yellow())
+    )?;
+    let state = loop {
+        match host.wait() {
+            Message::State(state) => break *state,
+            Message::Turn(_) => (),
+            Message::Bye => return Ok(()),
+        }
+    };
+    let mut game = Game::from_state(state);
+    game.host = Some(host);
+    game.skin = skin;
+    game.view = view;
+    game.keys = Config::load().keys;
+    game.looping(buffer)
+}
+
+/// `replay FILE`: watch a recorded run from its start, nothing is scored or saved
+fn watch_replay<T: Write>(
+    buffer: &mut T,
+    file: ReplayFile,
+    replay: ShareReplay,
+    skin: Skin,
+    view: View,
+) -> Result<()> {
+    let mut game = Game::from_state(file.start);
+    game.shield = replay.shield;
+    game.playback = Some(Playback::new(replay));
+    game.plugins.sync(&file.plugins, &mut game.state);
+    game.skin = skin;
+    game.view = view;
+    game.watch = Some(1);
+    game.looping(buffer)
+}
+
+/// play the tutorial; returns whether the player wants a real game afterwards
+fn run_tutorial<T: Write>(buffer: &mut T, skin: &Skin, view: View) -> Result<bool> {
+    let mut tutorial = Game::tutorial();
+    tutorial.skin = skin.clone();
+    tutorial.view = view;
+    tutorial.looping(buffer)?;
+    Ok(!tutorial.quit && prompt_yes_no(buffer, "Tutorial complete! Start a real game?")?)
+}
+
 fn main() -> Result<()> {
-    terminal::enable_raw_mode()?;
-    let mut buffer = stdout();
-    let mut game = Game::new();
-    game.looping(&mut buffer)?;
-    terminal::disable_raw_mode()?;
-    Ok(())
+    let cli = Cli::parse();
+    if cli.version {
+        cli::print_version(cli.features);
+        return Ok(());
+    }
+    match cli.command {
+        #[cfg(feature = "net")]
+        Some(Command::Update) => return update::run(),
+        Some(Command::Completions { shell }) => {
+            cli::print_completions(shell);
+            return Ok(());
+        }
+        Some(Command::Manpage) => return cli::print_manpage(),
+        Some(Command::Config { init }) => {
+            let path = config::config_path()
+                .unwrap_or_else(|| cli::exit_with_error("no config directory"));
+            if init {
+                config::write_template(&path).unwrap_or_else(|e| {
+                    cli::exit_with_error(&format!("{}: {}", path.display(), e))
+                });
+            }
+            println!("{}", path.display());
+            return Ok(());
+        }
+        Some(Command::Stats) => {
+            profile::print_stats(&Profile::load(), &Scores::load());
+            return Ok(());
+        }
+        Some(
+            Command::Play
+            | Command::Mods
+            | Command::Snapshot { .. }
+            | Command::Simulate { .. }
+            | Command::Edit { .. }
+            | Command::Replay { .. },
+        )
+        | None => (),
+    }
+    let content = Content::load().unwrap_or_else(|e| cli::exit_with_error(&e));
+    if let Some(Command::Mods) = cli.command {
+        content::print_mods(&content);
+        return Ok(());
+    }
+    if let Some(theme) = &cli.theme {
+        if !content.themes.iter().any(|t| &t.name == theme) {
+            let names: Vec<_> = content.themes.iter().map(|t| t.name.as_str()).collect();
+            let message = format!("unknown theme {:?}; available: {}", theme, names.join(", "));
+            cli::exit_with_error(&message);
+        }
+    }
+    let lang = cli.lang.clone().or_else(i18n::detect);
+    let strings =
+        Strings::load(lang.as_deref().unwrap_or("en")).unwrap_or_else(|e| cli::exit_with_error(&e));
+    save::install_panic_hook();
+    let mut config = Config::load();
+    // level times in seconds become ticks of the speed a run starts at
+    let mut speed = config.speed;
+    cli.apply_speed(&mut speed);
+    let difficulty = cli.difficulty.as_deref().and_then(Difficulty::find);
+    let tick = speed.time_step(difficulty.unwrap_or_default().time_step(), 0);
+    let view = View::scaled(cli.scale.unwrap_or(config.display.scale))
+        .unwrap_or_else(|e| cli::exit_with_error(&e));
+    if let Some(Command::Edit { file }) = &cli.command {
+        let size = (
+            cli.width.unwrap_or(NEW_MAP_SZ.0),
+            cli.height.unwrap_or(NEW_MAP_SZ.1),
+        );
+        let skin = configured_skin(&content, &config, &cli, &Profile::load());
+        let _terminal = TerminalGuard::enter()?;
+        let mut buffer = terminal_output(&config, &cli);
+        return editor::run(&mut buffer, file, size, &skin, view);
+    }
+    if let Some(Command::Replay { file }) = &cli.command {
+        let file = ReplayFile::load(file).unwrap_or_else(|e| cli::exit_with_error(&e));
+        let replay = file.replay().unwrap_or_else(|e| cli::exit_with_error(&e));
+        let skin = configured_skin(&content, &config, &cli, &Profile::load());
+        let _terminal = TerminalGuard::enter()?;
+        let mut buffer = terminal_output(&config, &cli);
+        return watch_replay(&mut buffer, file, replay, skin, view);
+    }
+    let mut board = config.board;
+    cli.apply_board(&mut board);
+    // snapshots and simulations don't depend on the terminal they run in
+    let fit = match cli.command {
+        Some(Command::Snapshot { .. } | Command::Simulate { .. }) => BOARD_SZ,
+        _ => terminal::size().map_or(BOARD_SZ, |term| view.fit(term)),
+    };
+    let stages = cli
+        .stages
+        .then(|| Stages::load().unwrap_or_else(|e| cli::exit_with_error(&e)));
+    let level = match (&stages, &cli.level) {
+        (Some(stages), _) => stages.current().level.clone(),
+        (None, Some(path)) if path.exists() => {
+            level::load(path, tick)
+                .unwrap_or_else(|e| cli::exit_with_error(&e))
+                .level
+        }
+        (None, Some(path)) => match content.level(&path.to_string_lossy()) {
+            Some(entry) => {
+                entry
+                    .load(tick)
+                    .unwrap_or_else(|e| cli::exit_with_error(&e))
+                    .level
+            }
+            None => cli::exit_with_error(&format!(
+                "{}: no such level file or level name",
+                path.display()
+            )),
+        },
+        (None, None) => match &cli.map {
+            Some(path) => level::load_map(path).unwrap_or_else(|e| cli::exit_with_error(&e)),
+            None => Level::bordered(board.size(fit)),
+        },
+    };
+    let mut profile = Profile::load();
+    if let Some(mode) = &cli.mode {
+        if shop::is_mode_locked(&profile, mode) {
+            cli::exit_with_error(&format!(
+                "mode {} is locked, buy it in the unlock shop first",
+                mode
+            ));
+        }
+    }
+    let skin = configured_skin(&content, &config, &cli, &profile);
+    let setup = Setup::checked(level, &config, &cli);
+    if let Some(Command::Snapshot { ticks }) = cli.command {
+        let seed = cli.seed.unwrap_or(0);
+        let moves = cli
+            .input_script
+            .as_ref()
+            .map(|path| MoveScript::load(path).unwrap_or_else(|e| cli::exit_with_error(&e)));
+        print!("{}", snapshot(setup, seed, ticks, moves));
+        return Ok(());
+    }
+    if let Some(Command::Simulate { bot, games }) = &cli.command {
+        // the bot plays alone
+        let setup = Setup {
+            two_player: false,
+            opponent: false,
+            battle: 0,
+            ..setup
+        };
+        let seed = cli.seed.unwrap_or_else(rand::random);
+        print!("{}", simulate::run(&setup, bot, *games, seed).report());
+        return Ok(());
+    }
+    // campaign levels are all loaded up front, so errors show before the screen takes over
+    let campaign: Vec<_> = if cli.campaign {
+        content
+            .levels
+            .iter()
+            .filter_map(|entry| {
+                let data = entry
+                    .load(tick)
+                    .unwrap_or_else(|e| cli::exit_with_error(&e));
+                let level = CampaignLevel {
+                    name: entry.name.clone(),
+                    medals: data.medals?,
+                };
+                Some((level, Setup::checked(data.level, &config, &cli)))
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    if cli.campaign && campaign.is_empty() {
+        cli::exit_with_error("no campaign levels: none of the levels has medals");
+    }
+    #[cfg(feature = "scripting")]
+    let scripts = cli
+        .script
+        .as_ref()
+        .map(|path| scripting::Scripts::load(path).unwrap_or_else(|e| cli::exit_with_error(&e)));
+    // find out about unwritable result files before the game starts
+    let no_results: &[RunResult] = &[];
+    if let Some(path) = &cli.results_json {
+        results::write_json(path, no_results)
+            .unwrap_or_else(|e| cli::exit_with_error(&format!("{}: {}", path.display(), e)));
+    }
+    if let Some(path) = &cli.results_csv {
+        results::write_csv(path, no_results)
+            .unwrap_or_else(|e| cli::exit_with_error(&format!("{}: {}", path.display(), e)));
+    }
+    let moves = cli
+        .input_script
+        .as_ref()
+        .map(|path| MoveScript::load(path).unwrap_or_else(|e| cli::exit_with_error(&e)));
+    let tick_log = cli.tick_csv.as_ref().map(|path| {
+        TickLog::create(path)
+            .unwrap_or_else(|e| cli::exit_with_error(&format!("{}: {}", path.display(), e)))
+    });
+    if let Some(path) = &cli.replay_keys {
+        input::replay(path).unwrap_or_else(|e| cli::exit_with_error(&e));
+    }
+    if let Some(path) = &cli.record_keys {
+        input::record(path).unwrap_or_else(|e| cli::exit_with_error(&e.to_string()));
+    }
+    let listener = cli.host.map(|port| {
+        net::listen(port).unwrap_or_else(|e| cli::exit_with_error(&format!("port {}: {}", port, e)))
+    });
+    let host = cli.join.as_ref().map(|addr| {
+        Link::join(addr).unwrap_or_else(|e| cli::exit_with_error(&format!("{}: {}", addr, e)))
+    });
+    let _terminal = TerminalGuard::enter()?;
+    let mut buffer = terminal_output(&config, &cli);
+    if let Some(host) = host {
+        return join_game(&mut buffer, host, skin, view);
+    }
+    if cfg!(feature = "net") && config.telemetry.needs_consent() {
+        let question = "Share anonymous stats (mode, score range, session length)?";
+        config.telemetry.enabled = Some(prompt_yes_no(&mut buffer, question)?);
+        let _ = config.save();
+    }
+    let themes = usable_themes(&content, &profile);
+    let names: Vec<_> = themes.iter().map(|theme| theme.name.clone()).collect();
+    let demo = |buffer: &mut _| run_demo(buffer, &setup, &skin, view);
+    let choice = title::title_screen(&mut buffer, &strings.title, &names, demo)?;
+    if let Choice::Quit = choice {
+        return Ok(());
+    }
+    // the settings may have been changed from the title screen
+    config = Config::load();
+    let skin = configured_skin(&content, &config, &cli, &profile);
+    let mut setup = setup;
+    setup.tail_rule = config.rules.tail;
+    setup.power_ups = config.rules.power_ups;
+    if cli.mode.is_none() {
+        setup.mode = modes::find(default_mode(&config));
+    }
+    let mut game = Game::new(setup, cli.seed.unwrap_or_else(rand::random));
+    let mut restored = false;
+    if let Choice::Continue(slot) = choice {
+        game.resume(*slot);
+        restored = true;
+    } else if let Some(snapshot) = save::take_emergency() {
+        let question = format!(
+            "An interrupted run (score {}) was found. Restore it?",
+            snapshot.state.score
+        );
+        if prompt_yes_no(&mut buffer, &question)? {
+            game.resume(snapshot);
+            restored = true;
+        }
+    }
+    let mut campaign_level = None;
+    if cli.campaign && !restored {
+        let levels: Vec<_> = campaign.iter().map(|(level, _)| level).collect();
+        match campaign::level_select(&mut buffer, &levels, &profile.medals)? {
+            Some(i) => {
+                game = Game::new(campaign[i].1.clone(), game.state.seed);
+                campaign_level = Some(&campaign[i].0);
+            }
+            None => {
+                return Ok(());
+            }
+        }
+    }
+    if !restored && (cli.tutorial || !profile.tutorial_done) {
+        let carry_on = run_tutorial(&mut buffer, &skin, view);
+        profile.tutorial_done = true;
+        let _ = profile.save();
+        if !matches!(carry_on, Ok(true)) {
+            return carry_on.map(|_| ());
+        }
+    }
+    if cli.practice {
+        game.enable_practice();
+    }
+    game.frame_step = cli.frame_step;
+    game.controls = config.controls.scheme;
+    game.keys = config.keys.clone();
+    game.speed = config.speed;
+    cli.apply_speed(&mut game.speed);
+    game.setup.tail_rule = config.rules.tail;
+    game.state.tail_rule = config.rules.tail;
+    game.moves = moves;
+    if cli.autopilot {
+        // half a tick to plan, leaving the rest for hooks and drawing
+        game.bot = Some(Bot::spawn(game.setup.difficulty.time_step() / 2));
+    }
+    if let (Some(listener), Some(port)) = (&listener, cli.host) {
+        match net::host_screen(&mut buffer, listener, port)? {
+            Some(link) => game.rival = Some(Controller::remote(link)),
+            None => {
+                return Ok(());
+            }
+        }
+    } else if cli.two_player {
+        game.rival = Some(Controller::Keyboard);
+    } else if cli.opponent {
+        game.rival = Some(Controller::Bot(Bot::spawn(
+            game.setup.difficulty.time_step() / 2,
+        )));
+    }
+    for _ in 0..game.setup.battle {
+        game.battle
+            .push(Bot::spawn(game.setup.difficulty.time_step() / 2));
+    }
+    game.tick_log = tick_log;
+    game.skin = skin;
+    game.strings = strings;
+    game.view = view;
+    game.weather.kind = config.game.weather;
+    game.race_ghost = config.game.ghost;
+    game.animate = config.display.animations;
+    game.themes = themes;
+    #[cfg(feature = "audio")]
+    {
+        game.sounds = audio::Sounds::new(config.audio);
+    }
+    #[cfg(feature = "scripting")]
+    {
+        game.scripts = scripts;
+    }
+    if !restored {
+        game.run_hook("on_spawn");
+    }
+    // newly enabled plugins get their own `on_spawn`
+    game.plugins.sync(&config.plugins.enabled, &mut game.state);
+    game.idle_timeout = match config.game.idle_pause_secs {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    };
+    let mut session = Session::new(cli.best_of);
+    let mut results = Vec::new();
+    let mut scores = Scores::load();
+    let result = loop {
+        if let Some(goal) = cli.speedrun.as_deref().and_then(Goal::parse) {
+            let records = profile.speedrun_records(goal).clone();
+            game.speedrun = Some(Speedrun::new(goal, records));
+        }
+        if let Some(level) = campaign_level {
+            game.campaign = Some(Attempt::new(&level.name, level.medals));
+        }
+        if let Some(stages) = &stages {
+            game.stages = Some(stages.rewound());
+        }
+        // a shield would only stand in for player one
+        game.shield = shop::has_power_up(&profile, "shield") && !cli.two_player;
+        #[cfg(feature = "net")]
+        let started = Instant::now();
+        let result = game.looping(&mut buffer);
+        if result.is_err() {
+            // unexpected exit: keep the run so it can be restored on next launch
+            let _ = save::write_emergency();
+        }
+        save::untrack();
+        #[cfg(feature = "net")]
+        telemetry::record(
+            &config.telemetry,
+            telemetry::Session::new(game.mode(), game.state.score, started.elapsed()),
+        );
+        if result.is_ok() && (cli.results_json.is_some() || cli.results_csv.is_some()) {
+            results.push(game.result());
+            if let Some(path) = &cli.results_json {
+                let _ = results::write_json(path, &results);
+            }
+            if let Some(path) = &cli.results_csv {
+                let _ = results::write_csv(path, &results);
+            }
+        }
+        if result.is_err() || game.quit {
+            break result;
+        }
+        if game.restart_requested {
+            game.restart(rand::random());
+            continue;
+        }
+        session.record(game.state.score, game.is_won());
+        if let Some(file) = game.replay_file().filter(|_| !game.demo) {
+            let paths = replay::last_path().into_iter().chain(cli.record.clone());
+            for path in paths {
+                let _ = file.save(&path);
+            }
+        }
+        let bests = profile.bests.clone();
+        let mut coins = 0;
+        let mut rank = None;
+        // two-player runs are unranked too
+        let ranked = game.is_ranked();
+        if ranked && !game.setup.two_player {
+            coins = shop::coins_for(game.state.score);
+            profile.coins += coins;
+            profile
+                .bests
+                .update(game.state.score, &game.stats, game.played);
+            if let Some(file) = game.replay_file() {
+                ghost::save_if_best(&file);
+            }
+            if let Some(run) = &game.speedrun {
+                run.save_records(profile.speedrun_records(run.goal));
+                let _ = run.log(game.state.seed);
+            }
+            if let Some(attempt) = &game.campaign {
+                if let Some(medal) = attempt.medal() {
+                    let best = profile.medals.entry(attempt.level.clone()).or_insert(medal);
+                    *best = (*best).max(medal);
+                }
+            }
+            let _ = profile.save();
+            if scores.qualifies(game.state.score) {
+                match scores::name_prompt(&mut buffer, game.state.score, &scores.last_name) {
+                    Ok(name) => rank = scores.insert(&name, game.state.score, game.mode()),
+                    Err(e) => break Err(e),
+                }
+                let _ = scores.save();
+            }
+        }
+        // the global table, for ranked runs with the `leaderboard` feature
+        #[cfg(feature = "leaderboard")]
+        let global = if ranked && !game.setup.two_player {
+            let name = match scores.last_name.as_str() {
+                "" => "anonymous".to_string(),
+                name => name.to_string(),
+            };
+            let submission = leaderboard::Submission {
+                name,
+                score: game.state.score,
+                mode: game.mode().to_string(),
+                seed: game.state.seed,
+                board: game.state.size,
+            };
+            leaderboard::submit(&config.leaderboard, &submission)
+        } else {
+            Vec::new()
+        };
+        #[cfg(not(feature = "leaderboard"))]
+        let global = Vec::new();
+        let retry = game_over_screen(
+            &mut buffer,
+            &game,
+            &bests,
+            coins,
+            &mut profile,
+            &session,
+            (&scores, rank, &global),
+        );
+        // the shop may have changed the skin and unlocked themes
+        game.skin = configured_skin(&content, &Config::load(), &cli, &profile);
+        game.themes = usable_themes(&content, &profile);
+        if cli.best_of.is_some()
+            && !session.is_over()
+            && matches!(retry, Ok(Retry::NewSeed | Retry::SameSeed))
+        {
+            if let Err(e) = session.show(&mut buffer, false) {
+                break Err(e);
+            }
+        }
+        match retry {
+            Ok(Retry::SameSeed) => game.restart(game.state.seed),
+            Ok(Retry::NewSeed) => game.restart(rand::random()),
+            Ok(Retry::Quit) => break Ok(()),
+            Err(e) => break Err(e),
+        }
+    };
+    match result {
+        Ok(()) if session.best_of.is_some() || session.played() > 1 => {
+            session.show(&mut buffer, true)
+        }
+        result => result,
+    }
 }
@@ -0,0 +1,108 @@
+//! Selection lists shared by the full-screen menus.
+
+use crate::input;
+use crossterm::{
+    cursor,
+    event::{Event, KeyCode, KeyEvent},
+    execute, queue,
+    style::{self, Stylize},
+    terminal, Result,
+};
+use std::io::Write;
+use std::time::Duration;
+
+/// how a menu was left
+pub enum Outcome {
+    Picked(usize),
+    Back,
+    /// no key was pressed for a while
+    Idle,
+}
+
+fn draw_list<T: Write>(
+    buffer: &mut T,
+    title: &str,
+    entries: &[String],
+    selected: usize,
+) -> Result<()> {
+    execute!(buffer, terminal::Clear(terminal::ClearType::All))?;
+    queue!(
+        buffer,
+        cursor::MoveTo(10, 0),
+        style::PrintStyledContent(title.magenta())
+    )?;
+    for (i, entry) in entries.iter().enumerate() {
+        let line = format!("{} {}", if i == selected { '>' } else { ' ' }, entry);
+        queue!(
+            buffer,
+            cursor::MoveTo(4, 2 + i as u16),
+            style::PrintStyledContent(if i == selected {
+                line.yellow()
+            } else {
+                line.white()
+            })
+        )?;
+    }
+    queue!(
+        buffer,
+        cursor::MoveTo(4, 3 + entries.len() as u16),
+        style::PrintStyledContent("Up/Down: select  Enter: confirm  Esc: back".dark_grey())
+    )?;
+    buffer.flush()?;
+    Ok(())
+}
+
+/// let the player pick one of `entries`; `None` when the screen is left with Esc
+pub fn select<T: Write>(buffer: &mut T, title: &str, entries: &[String]) -> Result<Option<usize>> {
+    select_from(buffer, title, entries, 0)
+}
+
+/// like `select`, with the cursor starting on entry `selected`
+pub fn select_from<T: Write>(
+    buffer: &mut T,
+    title: &str,
+    entries: &[String],
+    selected: usize,
+) -> Result<Option<usize>> {
+    Ok(match pick(buffer, title, entries, selected, None)? {
+        Outcome::Picked(i) => Some(i),
+        Outcome::Back | Outcome::Idle => None,
+    })
+}
+
+/// like `select_from`, also left once no key has been pressed for `idle`
+pub fn select_idle<T: Write>(
+    buffer: &mut T,
+    title: &str,
+    entries: &[String],
+    selected: usize,
+    idle: Duration,
+) -> Result<Outcome> {
+    pick(buffer, title, entries, selected, Some(idle))
+}
+
+fn pick<T: Write>(
+    buffer: &mut T,
+    title: &str,
+    entries: &[String],
+    mut selected: usize,
+    idle: Option<Duration>,
+) -> Result<Outcome> {
+    loop {
+        draw_list(buffer, title, entries, selected)?;
+        if let Some(idle) = idle {
+            if !input::poll(idle)? {
+                return Ok(Outcome::Idle);
+            }
+        }
+        if let Event::Key(KeyEvent { code, .. }) = input::read()? {
+            match code {
+                KeyCode::Up => selected = selected.saturating_sub(1),
+                KeyCode::Down if selected + 1 < entries.len() => selected += 1,
+                KeyCode::Enter if !entries.is_empty() => return Ok(Outcome::Picked(selected)),
+                KeyCode::Esc => return Ok(Outcome::Back),
+                _ => (),
+            }
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Per-tick metrics for `--tick-csv`, for performance tuning and for
+//! analysing bots and difficulty.
+
+use snake_core::GameState;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+pub struct TickLog {
+    out: BufWriter<File>,
+    run: u32, // runs since the game started, from 1
+}
+
+impl TickLog {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(
+            out,
+            "run,tick,head_x,head_y,length,score,ticks_since_food,render_us"
+        )?;
+        Ok(Self { out, run: 1 })
+    }
+
+    pub fn next_run(&mut self) {
+        self.run += 1;
+    }
+
+    /// one row for the tick just played; `render` is how long the last frame took to draw
+    pub fn record(
+        &mut self,
+        state: &GameState,
+        ticks_since_food: u64,
+        render: Duration,
+    ) -> io::Result<()> {
+        let (x, y) = state.snake.head().pos;
+        writeln!(
+            self.out,
+            "{},{},{},{},{},{},{},{}",
+            self.run,
+            state.tick,
+            x,
+            y,
+            state.snake.body.len(),
+            state.score,
+            ticks_since_food,
+            render.as_micros()
+        )
+    }
+}
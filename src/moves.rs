@@ -0,0 +1,140 @@
+//! Scripted moves for `--input-script`, steering the snake instead of the
+//! arrow keys.
+//!
+//! One move per line: the tick it applies at and a direction, e.g. `12 up`.
+//! Blank lines and lines starting with `#` are skipped. Ticks count from the
+//! start of each run; once the script runs out the arrow keys work again.
+
+use snake_core::{Direction, InputSource};
+use std::io::{self, Read};
+use std::path::Path;
+
+#[derive(Clone)]
+pub struct MoveScript {
+    moves: Vec<(u64, Direction)>, // sorted by tick
+    next: usize,
+    tick: u64,
+}
+
+impl MoveScript {
+    /// read a script from `path`, or from stdin for `-`
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let error = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+        let text = if path == Path::new("-") {
+            let mut text = String::new();
+            io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|e| error(&e))?;
+            text
+        } else {
+            std::fs::read_to_string(path).map_err(|e| error(&e))?
+        };
+        Self::parse(&text).map_err(|e| error(&e))
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut moves = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let bad_line = || format!("line {}: expected `<tick> <up|down|left|right>`", i + 1);
+            let (tick, dir) = line.split_once(char::is_whitespace).ok_or_else(bad_line)?;
+            let tick = tick.parse().map_err(|_| bad_line())?;
+            let dir = match dir.trim().to_ascii_lowercase().as_str() {
+                "up" => Direction::Up,
+                "down" => Direction::Down,
+                "left" => Direction::Left,
+                "right" => Direction::Right,
+                _ => return Err(bad_line()),
+            };
+            moves.push((tick, dir));
+        }
+        moves.sort_by_key(|&(tick, _)| tick);
+        Ok(Self {
+            moves,
+            next: 0,
+            tick: 0,
+        })
+    }
+
+    /// the same script from its first move, for the next run
+    pub fn rewound(&self) -> Self {
+        Self {
+            next: 0,
+            tick: 0,
+            ..self.clone()
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next >= self.moves.len()
+    }
+}
+
+impl InputSource for MoveScript {
+    /// called once per tick; moves for the same tick overwrite each other
+    fn poll(&mut self) -> Option<Direction> {
+        let mut turn = None;
+        while let Some(&(tick, dir)) = self.moves.get(self.next) {
+            if tick > self.tick {
+                break;
+            }
+            turn = Some(dir);
+            self.next += 1;
+        }
+        self.tick += 1;
+        turn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the turn polled on each tick until the script runs out
+    fn polled(mut script: MoveScript) -> Vec<Option<Direction>> {
+        let mut turns = Vec::new();
+        while !script.is_done() {
+            turns.push(script.poll());
+        }
+        turns
+    }
+
+    #[test]
+    fn moves_apply_on_their_tick_in_order() {
+        let script =
+            MoveScript::parse("# warm up\n\n2 up\n  0 Left  \n2\tRIGHT\n4 down\n").unwrap();
+        let turns = vec![
+            Some(Direction::Left),
+            None,
+            // a later line for the same tick wins
+            Some(Direction::Right),
+            None,
+            Some(Direction::Down),
+        ];
+        assert_eq!(polled(script.clone()), turns);
+        assert_eq!(polled(script.rewound()), turns);
+    }
+
+    #[test]
+    fn an_empty_script_is_done_at_once() {
+        assert!(MoveScript::parse("").unwrap().is_done());
+        assert!(MoveScript::parse("# nothing\n\n").unwrap().is_done());
+    }
+
+    #[test]
+    fn bad_lines_are_reported_with_their_number() {
+        for (text, line) in [
+            ("up", 1),
+            ("1 up\n2 sideways", 2),
+            ("1 up\n\n-3 left", 3),
+            ("x down", 1),
+            ("5", 1),
+        ] {
+            let error = MoveScript::parse(text).err().unwrap();
+            assert!(error.starts_with(&format!("line {}:", line)), "{}", error);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! LAN games for two: `--host` waits for a player to `--join` over TCP.
+//!
+//! The host runs the game and is the only one to tick it; the player who
+//! joined steers the rival snake. Messages are JSON, one per line: the
+//! joining side sends its turns, the host sends the whole state after every
+//! tick, so snake positions, food spawns and scores always match the host's.
+//! Either side says goodbye when it quits.
+
+use crate::input;
+use crossterm::{
+    cursor,
+    event::{Event, KeyCode, KeyEvent},
+    execute,
+    style::{self, Stylize},
+    terminal,
+};
+use serde::{Deserialize, Serialize};
+use snake_core::{Direction, GameState};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize)]
+pub enum Message {
+    /// the joining player turned the rival
+    Turn(Direction),
+    /// the game after a tick, from the host
+    State(Box<GameState>),
+    Bye,
+}
+
+/// one end of a connection; incoming messages are read on a thread of their own
+pub struct Link {
+    stream: TcpStream,
+    inbox: Receiver<Message>,
+}
+
+impl Link {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        let (outbox, inbox) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let message = match line.map(|line| serde_json::from_str(&line)) {
+                    Ok(Ok(message)) => message,
+                    _ => break,
+                };
+                if matches!(message, Message::Bye) || outbox.send(message).is_err() {
+                    break;
+                }
+            }
+            // a goodbye, a broken line or a dropped connection all end the game
+            let _ = outbox.send(Message::Bye);
+        });
+        Ok(Self { stream, inbox })
+    }
+
+    /// connect to a host at `addr`, `HOST:PORT`
+    pub fn join(addr: &str) -> io::Result<Self> {
+        Self::new(TcpStream::connect(addr)?)
+    }
+
+    pub fn send(&self, message: &Message) -> io::Result<()> {
+        let mut line = serde_json::to_vec(message)?;
+        line.push(b'\n');
+        (&self.stream).write_all(&line)
+    }
+
+    /// the messages that came in since the last call
+    pub fn received(&self) -> impl Iterator<Item = Message> + '_ {
+        self.inbox.try_iter()
+    }
+
+    /// wait for the next message
+    pub fn wait(&self) -> Message {
+        self.inbox.recv().unwrap_or(Message::Bye)
+    }
+}
+
+impl Drop for Link {
+    fn drop(&mut self) {
+        let _ = self.send(&Message::Bye);
+    }
+}
+
+/// listen for a player to join on `port`
+pub fn listen(port: u16) -> io::Result<TcpListener> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+/// wait for a player to join through `listener`; `None` if Esc was pressed first
+pub fn host_screen<T: Write>(
+    buffer: &mut T,
+    listener: &TcpListener,
+    port: u16,
+) -> crossterm::Result<Option<Link>> {
+    execute!(
+        buffer,
+        terminal::Clear(terminal::ClearType::All),
+        cursor::MoveTo(10, 0),
+        style::PrintStyledContent(format!("Waiting for a player on port {}", port).yellow()),
+        cursor::MoveTo(10, 2),
+        style::PrintStyledContent(
+            format!("They start with: rust-snake --join <this address>:{}", port).dark_grey()
+        ),
+        cursor::MoveTo(10, 3),
+        style::PrintStyledContent("Esc: cancel".dark_grey())
+    )?;
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                return Link::new(stream).map(Some);
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => (),
+            Err(e) => return Err(e),
+        }
+        if input::poll(Duration::from_millis(100))? {
+            if let Event::Key(KeyEvent {
+                code: KeyCode::Esc, ..
+            }) = input::read()?
+            {
+                return Ok(None);
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Options screen, changes are written to the config file right away.
+
+use crate::config::{Config, ControlScheme};
+use crate::menu::select_from;
+use crate::plugin::REGISTRY;
+use crossterm::Result;
+use snake_core::TailRule;
+use std::io::Write;
+
+const SETTINGS: usize = 7; // rows before the plugin toggles
+const SPEEDS: [Option<u64>; 6] = [None, Some(250), Some(200), Some(150), Some(100), Some(75)];
+
+fn checkbox(on: bool) -> &'static str {
+    if on {
+        "[x]"
+    } else {
+        "[ ]"
+    }
+}
+
+/// the entry after `current` in `items`, the first one if `current` isn't there
+fn cycle<T: PartialEq + Clone>(items: &[T], current: &T) -> T {
+    let next = items
+        .iter()
+        .position(|item| item == current)
+        .map_or(0, |i| i + 1);
+    items[next % items.len()].clone()
+}
+
+/// toggle options until the player leaves with Esc; returns the updated config.
+/// `themes` are the ones the player may pick
+pub fn options_screen<T: Write>(buffer: &mut T, themes: &[String]) -> Result<Config> {
+    let mut config = Config::load();
+    let plugins: Vec<_> = REGISTRY.iter().map(|make| make()).collect();
+    let mut selected = 0;
+    loop {
+        let weather = format!(
+            "    weather {:<13} rain or snow around the board",
+            config.game.weather.name()
+        );
+        let tail = match config.rules.tail {
+            TailRule::Lenient => ("lenient", "may move into the cell the tail is leaving"),
+            TailRule::Strict => ("strict", "moving into the cell the tail is leaving kills"),
+        };
+        let tail = format!("    tail    {:<13} {}", tail.0, tail.1);
+        let controls = format!(
+            "    controls {:<12} {}",
+            config.controls.scheme.name(),
+            match config.controls.scheme {
+                ControlScheme::Absolute => "arrow keys point the snake that way",
+                ControlScheme::Relative => "left and right turn relative to the heading",
+            }
+        );
+        let sound = format!(
+            "    sound   {:<13} {}",
+            if config.audio.enabled { "on" } else { "off" },
+            if cfg!(feature = "audio") {
+                "beeps when the snake eats or crashes"
+            } else {
+                "needs a build with the audio feature"
+            }
+        );
+        let speed = format!(
+            "    speed   {:<13} how long the first tick of a run lasts",
+            match config.speed.start_ms {
+                Some(ms) => format!("{} ms", ms),
+                None => "difficulty".to_string(),
+            }
+        );
+        let theme = format!("    theme   {:<13} colors of the board", config.game.theme);
+        let wrap = format!(
+            "    wrap    {:<13} leave through the outer wall to come back, from the next run",
+            if config.rules.wrap { "on" } else { "off" }
+        );
+        let entries: Vec<_> = [weather, tail, controls, sound, speed, theme, wrap]
+            .into_iter()
+            .chain(plugins.iter().map(|plugin| {
+                let on = config.plugins.enabled.iter().any(|n| n == plugin.name());
+                format!(
+                    "{} plugin  {:<13} {}",
+                    checkbox(on),
+                    plugin.name(),
+                    plugin.description()
+                )
+            }))
+            .collect();
+        match select_from(buffer, "Options", &entries, selected)? {
+            Some(0) => {
+                config.game.weather = config.game.weather.next();
+                selected = 0;
+            }
+            Some(1) => {
+                config.rules.tail = match config.rules.tail {
+                    TailRule::Lenient => TailRule::Strict,
+                    TailRule::Strict => TailRule::Lenient,
+                };
+                selected = 1;
+            }
+            Some(2) => {
+                config.controls.scheme = match config.controls.scheme {
+                    ControlScheme::Absolute => ControlScheme::Relative,
+                    ControlScheme::Relative => ControlScheme::Absolute,
+                };
+                selected = 2;
+            }
+            Some(3) => {
+                config.audio.enabled = !config.audio.enabled;
+                selected = 3;
+            }
+            Some(4) => {
+                config.speed.start_ms = cycle(&SPEEDS, &config.speed.start_ms);
+                selected = 4;
+            }
+            Some(5) => {
+                if !themes.is_empty() {
+                    config.game.theme = cycle(themes, &config.game.theme);
+                }
+                selected = 5;
+            }
+            Some(6) => {
+                config.rules.wrap = !config.rules.wrap;
+                selected = 6;
+            }
+            Some(i) => {
+                let name = plugins[i - SETTINGS].name();
+                let enabled = &mut config.plugins.enabled;
+                match enabled.iter().position(|n| n == name) {
+                    Some(pos) => {
+                        enabled.remove(pos);
+                    }
+                    None => enabled.push(name.to_string()),
+                }
+                selected = i;
+            }
+            None => break,
+        }
+    }
+    let _ = config.save();
+    Ok(config)
+}
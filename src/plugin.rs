@@ -0,0 +1,135 @@
+//! Compile-time registered plugins contributing extra rules and drawing.
+//!
+//! A plugin adds game content by implementing [`Plugin`]: extra pickups and
+//! rules hook into the run through `on_spawn` and `on_tick`, extra rendering
+//! through `overlay`. Plugins are listed in [`REGISTRY`] and enabled by name
+//! from the options screen.
+//!
+//! Plugins can't add game modes or food behaviors. Saves and replays name
+//! those, and must load whether or not a plugin is enabled, so they go into
+//! `snake_core::modes::MODES` and `snake_core::food::BEHAVIORS` instead.
+
+use crate::Tile;
+use snake_core::bus::Notice;
+use snake_core::{Cell, GameState};
+use std::time::Duration;
+
+pub trait Plugin {
+    /// unique name used in the config file
+    fn name(&self) -> &'static str;
+    /// one line shown in the options screen
+    fn description(&self) -> &'static str;
+    /// called when a run starts or the plugin is enabled mid-run
+    fn on_spawn(&mut self, _state: &mut GameState) {}
+    /// called after every tick, which lasted `tick` in game time; pickups on the
+    /// board go in `GameState::pickups` so nothing else spawns on them
+    fn on_tick(&mut self, _state: &mut GameState, _tick: Duration) {}
+    /// extra cells drawn on top of the board
+    fn overlay(&self, _state: &GameState) -> Vec<(Cell, Tile)> {
+        Vec::new()
+    }
+}
+
+/// every plugin built into this binary
+pub const REGISTRY: &[fn() -> Box<dyn Plugin>] = &[|| Box::new(GoldenFood::default())];
+
+/// the enabled plugins of a game, in registry order
+#[derive(Default)]
+pub struct Plugins {
+    active: Vec<Box<dyn Plugin>>,
+}
+
+impl Plugins {
+    /// enable the plugins named in `enabled`, keeping the state of those already running
+    pub fn sync(&mut self, enabled: &[String], state: &mut GameState) {
+        let mut running = std::mem::take(&mut self.active);
+        for make in REGISTRY {
+            let mut plugin = make();
+            if !enabled.iter().any(|name| name == plugin.name()) {
+                continue;
+            }
+            match running.iter().position(|p| p.name() == plugin.name()) {
+                Some(i) => plugin = running.swap_remove(i),
+                None => plugin.on_spawn(state),
+            }
+            self.active.push(plugin);
+        }
+    }
+
+    /// names of the enabled plugins
+    pub fn names(&self) -> Vec<String> {
+        self.active.iter().map(|p| p.name().to_string()).collect()
+    }
+
+    pub fn on_spawn(&mut self, state: &mut GameState) {
+        for plugin in &mut self.active {
+            plugin.on_spawn(state);
+        }
+    }
+
+    pub fn on_tick(&mut self, state: &mut GameState, tick: Duration) {
+        for plugin in &mut self.active {
+            plugin.on_tick(state, tick);
+        }
+    }
+
+    pub fn overlay(&self, state: &GameState) -> Vec<(Cell, Tile)> {
+        self.active.iter().flat_map(|p| p.overlay(state)).collect()
+    }
+}
+
+const GOLDEN_EVERY: u64 = 80; // ticks between golden food drops
+const GOLDEN_LIFE: Duration = Duration::from_secs(6); // game time a golden food stays on the board
+const GOLDEN_BLINK: Duration = Duration::from_secs(2); // before it disappears, that it blinks for
+const GOLDEN_SCORE: u16 = 3;
+
+/// a rare golden food worth extra points that vanishes after a while
+#[derive(Default)]
+struct GoldenFood {
+    food: Option<(Cell, Duration)>, // cell and the game time left until it disappears
+}
+
+impl Plugin for GoldenFood {
+    fn name(&self) -> &'static str {
+        "golden-food"
+    }
+
+    fn description(&self) -> &'static str {
+        "a golden food worth 3 points appears now and then"
+    }
+
+    fn on_spawn(&mut self, _state: &mut GameState) {
+        self.food = None;
+    }
+
+    fn on_tick(&mut self, state: &mut GameState, tick: Duration) {
+        if let Some((cell, _)) = &self.food {
+            state.pickups.retain(|c| c != cell);
+        }
+        match &mut self.food {
+            Some((cell, _)) if state.snake.head() == cell => {
+                state.score = state.score.saturating_add(GOLDEN_SCORE);
+                state.notify(Notice::Ate("golden"));
+                self.food = None;
+            }
+            Some((_, left)) if *left <= tick => self.food = None,
+            Some((_, left)) => *left -= tick,
+            None if state.tick.is_multiple_of(GOLDEN_EVERY) && !state.is_over => {
+                self.food = state.random_free_cell().map(|cell| (cell, GOLDEN_LIFE));
+            }
+            None => (),
+        }
+        if let Some((cell, _)) = &self.food {
+            state.pickups.push(cell.clone());
+        }
+    }
+
+    fn overlay(&self, state: &GameState) -> Vec<(Cell, Tile)> {
+        self.food
+            .iter()
+            // hidden every other tick once it is about to go
+            .filter(|(_, left)| *left > GOLDEN_BLINK || state.tick.is_multiple_of(2))
+            .map(|(cell, _)| (cell.clone(), Tile::Bonus))
+            .collect()
+    }
+}
@@ -0,0 +1,24 @@
+//! How the power-ups of [`snake_core::powerup`] look on the board and in the
+//! status bar.
+
+use crossterm::style::Color;
+use snake_core::powerup::Kind;
+
+/// one character per terminal column of a cell
+pub fn glyph(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Fast => ">>",
+        Kind::Slow => "<<",
+        Kind::Double => "x2",
+        Kind::Shrink => "><",
+    }
+}
+
+pub fn color(kind: Kind) -> Color {
+    match kind {
+        Kind::Fast => Color::Red,
+        Kind::Slow => Color::Blue,
+        Kind::Double => Color::Yellow,
+        Kind::Shrink => Color::Magenta,
+    }
+}
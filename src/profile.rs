@@ -0,0 +1,85 @@
+use crate::campaign::Medal;
+use crate::hud;
+use crate::save;
+use crate::scores::Scores;
+use crate::speedrun::{Goal, Records};
+use crate::stats::Bests;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// per-player progress kept between runs
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub tutorial_done: bool,
+    pub bests: Bests,
+    pub speedrun: Records,
+    /// records of `--speedrun score`, apart from the length ones
+    pub speedrun_points: Records,
+    /// best medal earned on each campaign level
+    pub medals: BTreeMap<String, Medal>,
+    pub coins: u32,
+    /// ids of the shop items bought
+    pub unlocked: Vec<String>,
+}
+
+fn profile_path() -> Option<PathBuf> {
+    save::data_dir().map(|dir| dir.join("profile.json"))
+}
+
+impl Profile {
+    /// load the profile, a missing or unreadable one counts as fresh
+    pub fn load() -> Self {
+        profile_path()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// the speedrun records kept for `goal`
+    pub fn speedrun_records(&mut self, goal: Goal) -> &mut Records {
+        match goal {
+            Goal::Length => &mut self.speedrun,
+            Goal::Score => &mut self.speedrun_points,
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = profile_path().ok_or_else(|| io::Error::other("no data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+}
+
+/// print the bests, coins and medals of `profile` and the high-score table, as `stats` does
+pub fn print_stats(profile: &Profile, scores: &Scores) {
+    let bests = &profile.bests;
+    println!("best score: {}", bests.score);
+    println!("best combo: {}", bests.max_combo);
+    println!(
+        "longest run: {}",
+        hud::clock(Duration::from_secs(bests.survived_secs))
+    );
+    println!("longest distance: {} cells", bests.distance);
+    println!("coins: {}", profile.coins);
+    let medals: Vec<_> = profile
+        .medals
+        .iter()
+        .map(|(level, medal)| format!("{} {}", level, medal.name()))
+        .collect();
+    if medals.is_empty() {
+        println!("medals: none");
+    } else {
+        println!("medals: {}", medals.join(", "));
+    }
+    println!();
+    for line in scores.lines(None) {
+        println!("{}", line.content());
+    }
+}
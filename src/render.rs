@@ -0,0 +1,234 @@
+//! The drawing surface behind `Game::render`.
+//!
+//! A frame is cleared, drawn one board cell and one line of text at a time,
+//! then presented. The terminal backend queues crossterm commands to any
+//! output; another frontend implements [`Renderer`] to show the same game.
+//!
+//! Clearing the terminal for every frame flickers and sends the whole board
+//! again and again, which shows over slow links. [`Incremental`] composes
+//! each frame in memory instead, diffs it column by column against the last
+//! frame kept on a [`Screen`] and prints only what changed in a single write.
+
+use crate::view::View;
+use crate::CELL_SZ;
+use crossterm::style::{ContentStyle, StyledContent};
+use crossterm::{cursor, queue, style, terminal, Result};
+use std::fmt::Display;
+use std::io::Write;
+
+pub trait Renderer {
+    /// blank the whole screen for a new frame
+    fn clear(&mut self) -> Result<()>;
+
+    /// one board cell at `pos`, placed and scaled by `view`; cells the
+    /// camera doesn't show are skipped
+    fn draw_cell(&mut self, view: &View, pos: (u16, u16), glyph: StyledContent<&str>)
+        -> Result<()>;
+
+    /// text from column `x` of screen row `y`
+    fn draw_text<D: Display>(&mut self, x: u16, y: u16, text: StyledContent<D>) -> Result<()>;
+
+    /// show what was drawn since the last `clear`
+    fn present(&mut self) -> Result<()>;
+
+    /// columns of the screen drawn to
+    fn width(&self) -> u16;
+}
+
+/// the crossterm backend, for any terminal output
+impl<W: Write> Renderer for W {
+    fn clear(&mut self) -> Result<()> {
+        queue!(self, terminal::Clear(terminal::ClearType::All))
+    }
+
+    fn draw_cell(
+        &mut self,
+        view: &View,
+        pos: (u16, u16),
+        glyph: StyledContent<&str>,
+    ) -> Result<()> {
+        view.draw(self, pos, glyph)
+    }
+
+    fn draw_text<D: Display>(&mut self, x: u16, y: u16, text: StyledContent<D>) -> Result<()> {
+        queue!(self, cursor::MoveTo(x, y), style::PrintStyledContent(text))
+    }
+
+    fn present(&mut self) -> Result<()> {
+        self.flush()
+    }
+
+    fn width(&self) -> u16 {
+        terminal::size().map_or(u16::MAX, |(width, _)| width)
+    }
+}
+
+/// one terminal column of a composed frame
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Tile {
+    ch: char,
+    style: ContentStyle,
+}
+
+impl Default for Tile {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            style: ContentStyle::new(),
+        }
+    }
+}
+
+/// a whole frame, composed in memory before anything is printed
+struct Grid {
+    size: (u16, u16),
+    tiles: Vec<Tile>, // row after row
+}
+
+impl Grid {
+    fn new(size: (u16, u16)) -> Self {
+        Self {
+            size,
+            tiles: vec![Tile::default(); usize::from(size.0) * usize::from(size.1)],
+        }
+    }
+
+    /// `text` from column `x` of row `y`, cut off at the right edge
+    fn put(&mut self, x: u16, y: u16, text: &str, style: ContentStyle) {
+        if y >= self.size.1 {
+            return;
+        }
+        let row = usize::from(y) * usize::from(self.size.0);
+        for (x, ch) in (x..self.size.0).zip(text.chars()) {
+            self.tiles[row + usize::from(x)] = Tile { ch, style };
+        }
+    }
+}
+
+/// what the terminal shows, kept from frame to frame
+#[derive(Default)]
+pub struct Screen {
+    // the last frame; `None` when the terminal may show something else, so
+    // the next frame is printed in full
+    shown: Option<Grid>,
+    term: Option<(u16, u16)>,
+}
+
+impl Screen {
+    /// the terminal shows something else now, e.g. a menu: print the next frame in full
+    pub fn invalidate(&mut self) {
+        self.shown = None;
+    }
+
+    /// note the size of the terminal; after a resize the next frame is printed in full
+    pub fn fit(&mut self, term: (u16, u16)) {
+        if self.term != Some(term) {
+            self.term = Some(term);
+            self.invalidate();
+        }
+    }
+
+    /// a renderer composing the next frame for `out`
+    pub fn frame<'a, W: Write>(&'a mut self, out: &'a mut W) -> Incremental<'a, W> {
+        let size = self
+            .term
+            .or_else(|| terminal::size().ok())
+            .unwrap_or_default();
+        Incremental {
+            out,
+            screen: self,
+            grid: Grid::new(size),
+        }
+    }
+}
+
+/// composes a frame in an in-memory grid, then prints only the columns that
+/// differ from the last frame on a [`Screen`], all in one write
+pub struct Incremental<'a, W: Write> {
+    out: &'a mut W,
+    screen: &'a mut Screen,
+    grid: Grid,
+}
+
+impl<W: Write> Renderer for Incremental<'_, W> {
+    fn clear(&mut self) -> Result<()> {
+        self.grid = Grid::new(self.grid.size);
+        Ok(())
+    }
+
+    fn draw_cell(
+        &mut self,
+        view: &View,
+        pos: (u16, u16),
+        glyph: StyledContent<&str>,
+    ) -> Result<()> {
+        if !view.is_visible(pos) {
+            return Ok(());
+        }
+        let (x, y0) = view.origin(pos);
+        let text = glyph.content().repeat(usize::from(view.cell.0 / CELL_SZ.0));
+        for y in y0..y0 + view.cell.1 {
+            self.grid.put(x, y, &text, *glyph.style());
+        }
+        Ok(())
+    }
+
+    fn draw_text<D: Display>(&mut self, x: u16, y: u16, text: StyledContent<D>) -> Result<()> {
+        self.grid
+            .put(x, y, &text.content().to_string(), *text.style());
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<()> {
+        let grid = std::mem::replace(&mut self.grid, Grid::new((0, 0)));
+        // the terminal shows something unknown: every column counts as changed,
+        // which overwrites it without the visible wipe of a clear
+        let shown = self
+            .screen
+            .shown
+            .take()
+            .filter(|shown| shown.size == grid.size);
+        let changed = |i: usize| {
+            shown
+                .as_ref()
+                .is_none_or(|shown| shown.tiles[i] != grid.tiles[i])
+        };
+        let mut bytes = Vec::new();
+        let (width, height) = grid.size;
+        for y in 0..height {
+            let row = usize::from(y) * usize::from(width);
+            let mut x = 0;
+            while x < width {
+                if !changed(row + usize::from(x)) {
+                    x += 1;
+                    continue;
+                }
+                // a run of changed columns in the same style is printed at once
+                let start = x;
+                let style = grid.tiles[row + usize::from(x)].style;
+                let mut run = String::new();
+                while x < width && changed(row + usize::from(x)) {
+                    let tile = grid.tiles[row + usize::from(x)];
+                    if tile.style != style {
+                        break;
+                    }
+                    run.push(tile.ch);
+                    x += 1;
+                }
+                queue!(
+                    bytes,
+                    cursor::MoveTo(start, y),
+                    style::PrintStyledContent(StyledContent::new(style, run))
+                )?;
+            }
+        }
+        self.out.write_all(&bytes)?;
+        self.out.flush()?;
+        self.screen.shown = Some(grid);
+        Ok(())
+    }
+
+    fn width(&self) -> u16 {
+        self.grid.size.0
+    }
+}
@@ -0,0 +1,77 @@
+//! Replay files: a finished run written to disk when it ends.
+//!
+//! A file holds the state the run started from, the frontend extras that
+//! touch the board (power-ups and plugins) and the turns as a share string,
+//! so it stays a few kilobytes at most. The engine only depends on the
+//! starting state and the turns, so playing them back gives the same run.
+//! The last run is always kept as `last.replay` in the data directory.
+//!
+//! `rust-snake replay FILE` plays one back: space pauses, the right arrow
+//! steps a tick while paused, `1`, `2` and `4` set the speed and `q` quits.
+
+use crate::save;
+use crate::share::ShareReplay;
+use serde::{Deserialize, Serialize};
+use snake_core::powerup::PowerUps;
+use snake_core::GameState;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize)]
+pub struct ReplayFile {
+    pub mode: String,
+    pub score: u16, // final score of the run
+    pub start: GameState,
+    pub power_ups: bool,
+    pub plugins: Vec<String>,
+    turns: String, // share string
+}
+
+impl ReplayFile {
+    pub fn new(
+        mode: &str,
+        score: u16,
+        start: &GameState,
+        power_ups: bool,
+        plugins: Vec<String>,
+        replay: &ShareReplay,
+    ) -> Self {
+        Self {
+            mode: mode.to_string(),
+            score,
+            start: start.clone(),
+            power_ups,
+            plugins,
+            turns: replay.encode(),
+        }
+    }
+
+    /// the seed, shield and turns of the run
+    pub fn replay(&self) -> Result<ShareReplay, String> {
+        ShareReplay::decode(&self.turns)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let error = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+        let data = fs::read(path).map_err(|e| error(&e))?;
+        let mut file: Self = serde_json::from_slice(&data).map_err(|e| error(&e))?;
+        // files from before power-ups were part of the state only had the flag
+        if file.power_ups && file.start.power_ups.is_none() {
+            file.start.power_ups = Some(PowerUps::default());
+        }
+        Ok(file)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_vec(self)?)
+    }
+}
+
+/// where the replay of the last run is kept
+pub fn last_path() -> Option<PathBuf> {
+    save::data_dir().map(|dir| dir.join("last.replay"))
+}
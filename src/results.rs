@@ -0,0 +1,74 @@
+//! Structured results of finished runs, for tournaments and analysis scripts.
+//!
+//! `--results-json` writes an array with one object per run and `--results-csv`
+//! one row per run. Both files are rewritten after every run, so they stay
+//! complete even if the game is killed later on.
+
+use serde::Serialize;
+use snake_core::bus::Crash;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Serialize)]
+pub struct RunResult {
+    pub seed: u64,
+    pub mode: String,
+    pub score: u16,
+    pub length: usize,
+    pub duration_secs: f64, // game time, not counting pauses
+    pub ticks: u64,
+    /// how the run ended: `wall`, `body`, `hazard`, `boss`, `won` or `quit`
+    pub end: &'static str,
+    pub foods: BTreeMap<&'static str, u32>,
+    pub turns: u32,
+    pub distance: u32,
+    pub max_combo: u32,
+}
+
+pub fn end_name(crash: Option<Crash>, won: bool) -> &'static str {
+    match crash {
+        Some(Crash::Wall) => "wall",
+        Some(Crash::Body) => "body",
+        Some(Crash::Hazard) => "hazard",
+        Some(Crash::Boss) => "boss",
+        Some(Crash::Poison) => "poison",
+        Some(Crash::Rival) => "rival",
+        None if won => "won",
+        None => "quit",
+    }
+}
+
+pub fn write_json(path: &Path, results: &[RunResult]) -> io::Result<()> {
+    fs::write(path, serde_json::to_vec_pretty(results)?)
+}
+
+pub fn write_csv(path: &Path, results: &[RunResult]) -> io::Result<()> {
+    let mut text = String::from(
+        "seed,mode,score,length,duration_secs,ticks,end,foods,turns,distance,max_combo\n",
+    );
+    for r in results {
+        // foods as `kind=count` pairs separated by `;`
+        let foods: Vec<_> = r
+            .foods
+            .iter()
+            .map(|(kind, n)| format!("{}={}", kind, n))
+            .collect();
+        text.push_str(&format!(
+            "{},{},{},{},{:.2},{},{},{},{},{},{}\n",
+            r.seed,
+            r.mode,
+            r.score,
+            r.length,
+            r.duration_secs,
+            r.ticks,
+            r.end,
+            foods.join(";"),
+            r.turns,
+            r.distance,
+            r.max_combo
+        ));
+    }
+    fs::write(path, text)
+}
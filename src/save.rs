@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use snake_core::GameState;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// name of the slot written automatically during play
+pub const AUTOSAVE: &str = "autosave";
+/// name of the slot the save key writes
+pub const QUICKSAVE: &str = "quicksave";
+
+// latest save of the running game, written to disk if the game crashes
+static LAST_STATE: Mutex<Option<SaveFile>> = Mutex::new(None);
+
+pub fn data_dir() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "rust-snake").map(|dirs| dirs.data_dir().to_path_buf())
+}
+
+fn emergency_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("emergency.json"))
+}
+
+/// remember the current game state, `played` into its run, in case of a crash
+pub fn track(mode: &str, state: &GameState, played: Duration) {
+    let file = SaveFile::new("emergency", mode, state, played);
+    *LAST_STATE.lock().unwrap_or_else(|e| e.into_inner()) = Some(file);
+}
+
+/// forget the tracked state once the run ends normally
+pub fn untrack() {
+    *LAST_STATE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// write the tracked state to the emergency save file
+pub fn write_emergency() -> io::Result<()> {
+    let state = LAST_STATE.lock().unwrap_or_else(|e| e.into_inner());
+    if let (Some(snapshot), Some(path)) = (state.as_ref(), emergency_path()) {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_vec(snapshot)?)?;
+    }
+    Ok(())
+}
+
+/// load and remove the emergency save left by an interrupted run, if any
+pub fn take_emergency() -> Option<SaveFile> {
+    let path = emergency_path()?;
+    let data = fs::read(&path).ok()?;
+    let _ = fs::remove_file(&path);
+    serde_json::from_slice(&data).ok()
+}
+
+/// restore the terminal and write an emergency save before the default panic report
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        crate::tty::restore();
+        let _ = write_emergency();
+        default_hook(info);
+    }));
+}
+
+/// a named save slot on disk
+#[derive(Serialize, Deserialize)]
+pub struct SaveFile {
+    pub name: String,
+    pub mode: String,
+    pub saved_at: i64, // unix timestamp in seconds
+    #[serde(default)]
+    pub played_ms: u64, // game time of the run when it was saved
+    pub state: GameState,
+}
+
+impl SaveFile {
+    fn new(name: &str, mode: &str, state: &GameState, played: Duration) -> Self {
+        let saved_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        Self {
+            name: name.to_string(),
+            mode: mode.to_string(),
+            saved_at,
+            played_ms: played.as_millis() as u64,
+            state: state.clone(),
+        }
+    }
+
+    pub fn played(&self) -> Duration {
+        Duration::from_millis(self.played_ms)
+    }
+}
+
+fn slots_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("saves"))
+}
+
+/// slot names become file names, so only a safe subset of characters is allowed
+pub fn is_valid_slot_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 16
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// save `state`, `played` into its run, as slot `name`
+pub fn write_slot(name: &str, mode: &str, state: &GameState, played: Duration) -> io::Result<()> {
+    let dir = slots_dir().ok_or_else(|| io::Error::other("no data directory"))?;
+    fs::create_dir_all(&dir)?;
+    let file = SaveFile::new(name, mode, state, played);
+    fs::write(
+        dir.join(format!("{}.json", name)),
+        serde_json::to_vec(&file)?,
+    )
+}
+
+pub fn remove_slot(name: &str) {
+    if let Some(dir) = slots_dir() {
+        let _ = fs::remove_file(dir.join(format!("{}.json", name)));
+    }
+}
+
+/// the most recent save of a run that isn't over, to continue from
+pub fn latest_slot() -> Option<SaveFile> {
+    list_slots().into_iter().find(|slot| !slot.state.is_over)
+}
+
+/// all readable save slots, most recent first
+pub fn list_slots() -> Vec<SaveFile> {
+    let mut slots: Vec<SaveFile> = slots_dir()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| fs::read(entry.ok()?.path()).ok())
+        .filter_map(|data| serde_json::from_slice(&data).ok())
+        .collect();
+    slots.sort_by_key(|slot| -slot.saved_at);
+    slots
+}
@@ -0,0 +1,173 @@
+//! High-score table: the ten best ranked runs with the player's name and date.
+//!
+//! Kept in `scores.json` in the data directory, next to the profile. A run
+//! that makes the table asks for a name, offering the one entered last.
+
+use crate::input;
+use crate::save;
+use chrono::{Local, TimeZone};
+use crossterm::{
+    cursor,
+    event::{Event, KeyCode, KeyEvent},
+    execute, queue,
+    style::{self, StyledContent, Stylize},
+    terminal, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const TABLE_LEN: usize = 10;
+const NAME_LEN: usize = 12;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub score: u16,
+    pub mode: String,
+    pub date: i64, // unix timestamp in seconds
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scores {
+    /// best first
+    pub entries: Vec<Entry>,
+    /// offered in the name prompt
+    pub last_name: String,
+}
+
+fn scores_path() -> Option<PathBuf> {
+    save::data_dir().map(|dir| dir.join("scores.json"))
+}
+
+impl Scores {
+    /// load the table, a missing or unreadable one counts as empty
+    pub fn load() -> Self {
+        scores_path()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = scores_path().ok_or_else(|| io::Error::other("no data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+
+    /// whether `score` would make it into the table
+    pub fn qualifies(&self, score: u16) -> bool {
+        score > 0
+            && (self.entries.len() < TABLE_LEN
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    /// add a run dated now; returns its place in the table, if it made it
+    pub fn insert(&mut self, name: &str, score: u16, mode: &str) -> Option<usize> {
+        if !self.qualifies(score) {
+            return None;
+        }
+        let date = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        // ties go below the older entries
+        let rank = self.entries.partition_point(|entry| entry.score >= score);
+        self.entries.insert(
+            rank,
+            Entry {
+                name: name.to_string(),
+                score,
+                mode: mode.to_string(),
+                date,
+            },
+        );
+        self.entries.truncate(TABLE_LEN);
+        self.last_name = name.to_string();
+        Some(rank)
+    }
+
+    /// the table as text lines, the entry at `highlight` stands out
+    pub fn lines(&self, highlight: Option<usize>) -> Vec<StyledContent<String>> {
+        let mut lines = vec!["HIGH SCORES".to_string().magenta()];
+        if self.entries.is_empty() {
+            lines.push("no scores yet".to_string().dark_grey());
+        }
+        for (i, entry) in self.entries.iter().enumerate() {
+            let date = Local
+                .timestamp_opt(entry.date, 0)
+                .single()
+                .map(|t| t.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            let line = format!(
+                "{:>2}. {:<12} {:>5}  {:<11} {}",
+                i + 1,
+                entry.name,
+                entry.score,
+                entry.mode,
+                date
+            );
+            lines.push(if highlight == Some(i) {
+                line.yellow().bold()
+            } else {
+                line.white()
+            });
+        }
+        lines
+    }
+}
+
+/// ask for the name to put in the table, starting from `name`
+pub fn name_prompt<T: Write>(buffer: &mut T, score: u16, name: &str) -> Result<String> {
+    let mut name = name.to_string();
+    loop {
+        execute!(buffer, terminal::Clear(terminal::ClearType::All))?;
+        queue!(
+            buffer,
+            cursor::MoveTo(10, 0),
+            style::PrintStyledContent(format!("NEW HIGH SCORE: {}", score).green().bold()),
+            cursor::MoveTo(4, 2),
+            style::PrintStyledContent(format!("Name: {}_", name).yellow()),
+            cursor::MoveTo(4, 4),
+            style::PrintStyledContent("Enter: save  Esc: save as \"anonymous\"".dark_grey())
+        )?;
+        buffer.flush()?;
+        if let Event::Key(KeyEvent { code, .. }) = input::read()? {
+            match code {
+                KeyCode::Char(c) if !c.is_control() && name.chars().count() < NAME_LEN => {
+                    name.push(c)
+                }
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Enter if !name.trim().is_empty() => return Ok(name.trim().to_string()),
+                KeyCode::Esc => return Ok("anonymous".to_string()),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// show the table until a key is pressed
+pub fn scores_screen<T: Write>(buffer: &mut T, scores: &Scores) -> Result<()> {
+    execute!(buffer, terminal::Clear(terminal::ClearType::All))?;
+    for (i, line) in scores.lines(None).into_iter().enumerate() {
+        queue!(
+            buffer,
+            cursor::MoveTo(10, i as u16),
+            style::PrintStyledContent(line)
+        )?;
+    }
+    queue!(
+        buffer,
+        cursor::MoveTo(10, scores.entries.len().max(1) as u16 + 2),
+        style::PrintStyledContent("any key: back".dark_grey())
+    )?;
+    buffer.flush()?;
+    while !matches!(input::read()?, Event::Key(_)) {}
+    Ok(())
+}
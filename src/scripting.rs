@@ -0,0 +1,201 @@
+//! Custom rules written in Rhai.
+//!
+//! A script may define any of these hooks, each called with `this` bound to
+//! the game API:
+//!
+//! - `on_spawn()`: a run has started
+//! - `on_tick()`: after every tick
+//! - `on_eat()`: the snake ate food this tick
+//! - `on_collision()`: the snake crashed this tick
+//!
+//! The API exposes read-only `score`, `length`, `tick`, `head_x`, `head_y`,
+//! `width` and `height`, and the commands `add_score(n)`, `grow(n)`,
+//! `shrink(n)`, `spawn_food()`, `spawn_hazard()`, `end_game()`,
+//! `message(text)`, plus `get(key)`/`set(key, value)` for values kept between
+//! calls. Scripts have no file or network access and are limited in the
+//! number of operations per call.
+//!
+//! ```rhai
+//! fn on_eat() {
+//!     if this.score % 5 == 0 { this.spawn_hazard(); this.message("careful!"); }
+//! }
+//! ```
+
+use rhai::{CallFnOptions, Dynamic, Engine, ImmutableString, Map, Scope, AST};
+use snake_core::GameState;
+use std::path::Path;
+
+const MAX_OPERATIONS: u64 = 100_000; // per hook call
+
+#[derive(Clone)]
+enum Command {
+    AddScore(i64),
+    Grow(i64),
+    Shrink(i64),
+    SpawnFood,
+    SpawnHazard,
+    EndGame,
+    Message(String),
+}
+
+/// what a hook sees of the game; commands are applied once the hook returns
+#[derive(Clone)]
+struct Api {
+    score: i64,
+    length: i64,
+    tick: i64,
+    head: (i64, i64),
+    size: (i64, i64),
+    vars: Map,
+    commands: Vec<Command>,
+}
+
+impl Api {
+    fn new(state: &GameState, vars: Map) -> Self {
+        let head = state.snake.head().pos;
+        Self {
+            score: i64::from(state.score),
+            length: state.snake.body.len() as i64,
+            tick: state.tick as i64,
+            head: (i64::from(head.0), i64::from(head.1)),
+            size: (i64::from(state.size.0), i64::from(state.size.1)),
+            vars,
+            commands: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, command: Command) {
+        self.commands.push(command);
+    }
+}
+
+fn register_api(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Api>("Game")
+        .register_get("score", |api: &mut Api| api.score)
+        .register_get("length", |api: &mut Api| api.length)
+        .register_get("tick", |api: &mut Api| api.tick)
+        .register_get("head_x", |api: &mut Api| api.head.0)
+        .register_get("head_y", |api: &mut Api| api.head.1)
+        .register_get("width", |api: &mut Api| api.size.0)
+        .register_get("height", |api: &mut Api| api.size.1)
+        .register_fn("add_score", |api: &mut Api, n: i64| {
+            api.push(Command::AddScore(n))
+        })
+        .register_fn("grow", |api: &mut Api, n: i64| api.push(Command::Grow(n)))
+        .register_fn("shrink", |api: &mut Api, n: i64| {
+            api.push(Command::Shrink(n))
+        })
+        .register_fn("spawn_food", |api: &mut Api| api.push(Command::SpawnFood))
+        .register_fn("spawn_hazard", |api: &mut Api| {
+            api.push(Command::SpawnHazard)
+        })
+        .register_fn("end_game", |api: &mut Api| api.push(Command::EndGame))
+        .register_fn("message", |api: &mut Api, text: ImmutableString| {
+            api.push(Command::Message(text.to_string()))
+        })
+        .register_fn("get", |api: &mut Api, key: ImmutableString| {
+            api.vars.get(key.as_str()).cloned().unwrap_or(Dynamic::UNIT)
+        })
+        .register_fn(
+            "set",
+            |api: &mut Api, key: ImmutableString, value: Dynamic| {
+                api.vars.insert(key.into(), value);
+            },
+        );
+}
+
+pub struct Scripts {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    vars: Map,
+    /// latest text passed to `message`
+    pub message: Option<String>,
+    /// latest runtime error of a hook
+    pub error: Option<String>,
+}
+
+impl Scripts {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(32);
+        engine.set_max_string_size(1024);
+        engine.set_max_array_size(1024);
+        engine.set_max_map_size(1024);
+        register_api(&mut engine);
+        let error = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+        let ast = engine.compile_file(path.into()).map_err(|e| error(&e))?;
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| error(&e))?;
+        Ok(Self {
+            engine,
+            ast,
+            scope,
+            vars: Map::new(),
+            message: None,
+            error: None,
+        })
+    }
+
+    /// run `hook` if the script defines it and apply the commands it issued
+    pub fn call(&mut self, hook: &str, state: &mut GameState) {
+        if !self.ast.iter_functions().any(|f| f.name == hook) {
+            return;
+        }
+        let mut this = Dynamic::from(Api::new(state, std::mem::take(&mut self.vars)));
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut this);
+        let result = self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut self.scope,
+            &self.ast,
+            hook,
+            (),
+        );
+        if let Err(e) = result {
+            self.error = Some(format!("{}: {}", hook, e));
+        }
+        let api = this.cast::<Api>();
+        self.vars = api.vars;
+        for command in &api.commands {
+            self.apply(command, state);
+        }
+    }
+
+    fn apply(&mut self, command: &Command, state: &mut GameState) {
+        let count = |n: &i64| (*n).clamp(0, i64::from(u16::MAX)) as usize;
+        match command {
+            Command::AddScore(n) => {
+                state.score = (i64::from(state.score) + n).clamp(0, i64::from(u16::MAX)) as u16
+            }
+            Command::Grow(n) => {
+                // repeated tail cells unfold as the snake moves on
+                state.snake.body.stack_tail(count(n));
+            }
+            Command::Shrink(n) => {
+                for _ in 0..count(n) {
+                    if state.snake.body.len() > 1 {
+                        state.snake.body.pop_back();
+                    }
+                }
+            }
+            Command::SpawnFood => {
+                if let Some(cell) = state.random_free_cell() {
+                    state.extra_food.push(cell);
+                }
+            }
+            Command::SpawnHazard => {
+                if let Some(cell) = state.random_free_cell() {
+                    state.hazards.push(cell);
+                }
+            }
+            Command::EndGame => state.is_over = true,
+            Command::Message(text) => self.message = Some(text.clone()),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Scoreboard for the rounds played in one sitting.
+//!
+//! Nothing is stored on disk: the session ends when the game exits. With
+//! `--best-of N` the session is over after N rounds.
+
+use crate::input;
+use crossterm::event::Event;
+use crossterm::style::{self, Stylize};
+use crossterm::{cursor, execute, queue, terminal, Result};
+use std::io::Write;
+
+const LISTED: usize = 30; // rounds listed on the scoreboard, most recent last
+
+/// the outcome of one finished round
+pub struct Round {
+    pub score: u16,
+    pub won: bool,
+}
+
+pub struct Session {
+    /// number of rounds in the session, unlimited if `None`
+    pub best_of: Option<u16>,
+    rounds: Vec<Round>,
+}
+
+impl Session {
+    pub fn new(best_of: Option<u16>) -> Self {
+        Self {
+            best_of,
+            rounds: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, score: u16, won: bool) {
+        self.rounds.push(Round { score, won });
+    }
+
+    pub fn played(&self) -> usize {
+        self.rounds.len()
+    }
+
+    /// whether all rounds of a best-of-N session have been played
+    pub fn is_over(&self) -> bool {
+        self.best_of
+            .is_some_and(|n| self.rounds.len() >= usize::from(n))
+    }
+
+    pub fn wins(&self) -> usize {
+        self.rounds.iter().filter(|round| round.won).count()
+    }
+
+    pub fn best(&self) -> u16 {
+        self.rounds
+            .iter()
+            .map(|round| round.score)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn average(&self) -> f64 {
+        match self.rounds.len() {
+            0 => 0.0,
+            n => self.rounds.iter().map(|r| f64::from(r.score)).sum::<f64>() / n as f64,
+        }
+    }
+
+    /// draw the scoreboard and wait for a key; `last` marks the end of the session
+    pub fn show<T: Write>(&self, buffer: &mut T, last: bool) -> Result<()> {
+        execute!(buffer, terminal::Clear(terminal::ClearType::All))?;
+        let title = match (self.best_of, last) {
+            (Some(n), false) => format!("SESSION: round {} of {}", self.played(), n),
+            (_, false) => format!("SESSION: {} rounds", self.played()),
+            (_, true) => "SESSION OVER".to_string(),
+        };
+        queue!(
+            buffer,
+            cursor::MoveTo(10, 0),
+            style::PrintStyledContent(title.magenta().bold()),
+            cursor::MoveTo(10, 2),
+            style::PrintStyledContent(
+                format!(
+                    "Wins: {}  Best: {}  Average: {:.1}",
+                    self.wins(),
+                    self.best(),
+                    self.average()
+                )
+                .green()
+            )
+        )?;
+        let best = self.best();
+        let first = self.rounds.len().saturating_sub(LISTED);
+        let listed = &self.rounds[first..];
+        for (i, round) in listed.iter().enumerate() {
+            let line = format!(
+                "{:>3}. {:>5}{}",
+                first + i + 1,
+                round.score,
+                if round.won { "  win" } else { "" }
+            );
+            queue!(
+                buffer,
+                cursor::MoveTo(10, 4 + i as u16),
+                style::PrintStyledContent(if round.score == best {
+                    line.yellow().bold()
+                } else {
+                    line.green()
+                })
+            )?;
+        }
+        let hint = if last {
+            "press any key to leave"
+        } else {
+            "press any key to go on"
+        };
+        queue!(
+            buffer,
+            cursor::MoveTo(10, 5 + listed.len() as u16),
+            style::PrintStyledContent(hint.dark_grey())
+        )?;
+        buffer.flush()?;
+        while !matches!(input::read()?, Event::Key(_)) {}
+        Ok(())
+    }
+}
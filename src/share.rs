@@ -0,0 +1,292 @@
+//! Replay share strings: a whole run packed into a line of text short enough
+//! for a chat message.
+//!
+//! A string holds the seed, whether the shield was on and every turn with the
+//! tick it was made at, as URL-safe base64 without padding. Playing it back
+//! on the same level and mode gives the same run, since the engine only
+//! depends on the seed and the turns.
+
+use crate::input;
+use crossterm::{
+    cursor,
+    event::{Event, KeyCode, KeyEvent},
+    execute, queue,
+    style::{self, Stylize},
+    terminal, Result,
+};
+use snake_core::Direction;
+use std::io::Write;
+
+const VERSION: u8 = 1;
+const SHIELD: u8 = 1; // flag bit
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+#[derive(Clone)]
+pub struct ShareReplay {
+    pub seed: u64,
+    pub shield: bool,
+    turns: Vec<(u64, Direction)>, // in the order they were made
+}
+
+impl ShareReplay {
+    pub fn new(seed: u64, shield: bool) -> Self {
+        Self {
+            seed,
+            shield,
+            turns: Vec::new(),
+        }
+    }
+
+    /// note a turn made just before the step from `tick`
+    pub fn record(&mut self, tick: u64, dir: Direction) {
+        self.turns.push((tick, dir));
+    }
+
+    /// forget the turns from `tick` on, after the run was rewound
+    pub fn truncate(&mut self, tick: u64) {
+        self.turns.retain(|&(at, _)| at < tick);
+    }
+
+    pub fn encode(&self) -> String {
+        let mut bytes = vec![VERSION, if self.shield { SHIELD } else { 0 }];
+        bytes.extend(self.seed.to_le_bytes());
+        let mut last = 0;
+        for &(tick, dir) in &self.turns {
+            let dir = DIRECTIONS.iter().position(|&d| d == dir).unwrap_or(0) as u64;
+            push_varint(&mut bytes, (tick - last) << 2 | dir);
+            last = tick;
+        }
+        to_base64(&bytes)
+    }
+
+    pub fn decode(text: &str) -> std::result::Result<Self, String> {
+        let bytes = from_base64(text.trim()).ok_or("not a replay string")?;
+        let (header, mut rest) = bytes
+            .split_at_checked(10)
+            .ok_or("replay string too short")?;
+        if header[0] != VERSION {
+            return Err(format!("unknown replay version {}", header[0]));
+        }
+        let seed = u64::from_le_bytes(header[2..10].try_into().unwrap_or_default());
+        let mut replay = Self::new(seed, header[1] & SHIELD != 0);
+        let mut tick: u64 = 0;
+        while !rest.is_empty() {
+            let value = take_varint(&mut rest).ok_or("replay string cut off")?;
+            tick = tick
+                .checked_add(value >> 2)
+                .ok_or("replay string corrupt")?;
+            replay.record(tick, DIRECTIONS[(value & 3) as usize]);
+        }
+        Ok(replay)
+    }
+}
+
+/// turns of a replay being played back
+pub struct Playback {
+    turns: Vec<(u64, Direction)>,
+    next: usize,
+}
+
+impl Playback {
+    pub fn new(replay: ShareReplay) -> Self {
+        Self {
+            turns: replay.turns,
+            next: 0,
+        }
+    }
+
+    /// the next turn due before the step from `tick`, if any is left
+    pub fn next_at(&mut self, tick: u64) -> Option<Direction> {
+        let &(at, dir) = self.turns.get(self.next)?;
+        if at > tick {
+            return None;
+        }
+        self.next += 1;
+        Some(dir)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next >= self.turns.len()
+    }
+}
+
+fn push_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn take_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte < 0x80 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn to_base64(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    for chunk in bytes.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |bits, (i, &b)| bits | u32::from(b) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            text.push(ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+    text
+}
+
+fn from_base64(text: &str) -> Option<Vec<u8>> {
+    let digits = text
+        .bytes()
+        .map(|c| ALPHABET.iter().position(|&a| a == c).map(|d| d as u32))
+        .collect::<Option<Vec<_>>>()?;
+    let mut bytes = Vec::new();
+    for chunk in digits.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |bits, (i, &d)| bits | d << (18 - 6 * i));
+        for i in 0..chunk.len() - 1 {
+            bytes.push((bits >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// show the share string of a finished run until a key is pressed
+pub fn share_screen<T: Write>(buffer: &mut T, replay: &ShareReplay) -> Result<()> {
+    execute!(buffer, terminal::Clear(terminal::ClearType::All))?;
+    queue!(
+        buffer,
+        cursor::MoveTo(10, 0),
+        style::PrintStyledContent("Share replay".magenta()),
+        cursor::MoveTo(0, 2),
+        style::Print(replay.encode()),
+        style::Print("\r\n\r\n"),
+        style::PrintStyledContent(
+            "Import it from the load screen (F9) on the same level and mode. Any key: back"
+                .dark_grey()
+        )
+    )?;
+    buffer.flush()?;
+    while !matches!(input::read()?, Event::Key(_)) {}
+    Ok(())
+}
+
+/// read a pasted share string; `None` when the screen is left with Esc
+pub fn import_screen<T: Write>(buffer: &mut T) -> Result<Option<ShareReplay>> {
+    let mut text = String::new();
+    let mut error = String::new();
+    loop {
+        execute!(buffer, terminal::Clear(terminal::ClearType::All))?;
+        queue!(
+            buffer,
+            cursor::MoveTo(10, 0),
+            style::PrintStyledContent("Import replay string".magenta()),
+            cursor::MoveTo(0, 2),
+            style::PrintStyledContent(format!("{}_", text).yellow()),
+            style::Print("\r\n\r\n"),
+            style::PrintStyledContent(error.clone().red()),
+            style::Print("\r\n"),
+            style::PrintStyledContent("paste or type it  Enter: play  Esc: back".dark_grey())
+        )?;
+        buffer.flush()?;
+        if let Event::Key(KeyEvent { code, .. }) = input::read()? {
+            match code {
+                KeyCode::Char(c) if c.is_ascii() && ALPHABET.contains(&(c as u8)) => text.push(c),
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Enter if !text.is_empty() => match ShareReplay::decode(&text) {
+                    Ok(replay) => return Ok(Some(replay)),
+                    Err(e) => error = e,
+                },
+                KeyCode::Esc => return Ok(None),
+                _ => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turns(replay: ShareReplay) -> Vec<(u64, Direction)> {
+        let mut playback = Playback::new(replay);
+        let mut turns = Vec::new();
+        for tick in 0..1000 {
+            while let Some(dir) = playback.next_at(tick) {
+                turns.push((tick, dir));
+            }
+        }
+        turns
+    }
+
+    #[test]
+    fn round_trip_keeps_the_seed_shield_and_turns() {
+        let made = [
+            (0, Direction::Up),
+            (0, Direction::Left),
+            (3, Direction::Down),
+            (40, Direction::Right),
+            (999, Direction::Up),
+        ];
+        for (seed, shield) in [(0, false), (7, true), (u64::MAX, false)] {
+            let mut replay = ShareReplay::new(seed, shield);
+            for (tick, dir) in made {
+                replay.record(tick, dir);
+            }
+            let text = replay.encode();
+            assert!(text.bytes().all(|c| ALPHABET.contains(&c)), "{}", text);
+            let decoded = ShareReplay::decode(&format!(" {}\n", text)).unwrap();
+            assert_eq!((decoded.seed, decoded.shield), (seed, shield));
+            assert_eq!(turns(decoded), made);
+        }
+    }
+
+    #[test]
+    fn a_run_without_turns_round_trips() {
+        let decoded = ShareReplay::decode(&ShareReplay::new(42, false).encode()).unwrap();
+        assert_eq!(decoded.seed, 42);
+        assert!(Playback::new(decoded).is_done());
+    }
+
+    #[test]
+    fn bad_strings_are_rejected() {
+        let mut replay = ShareReplay::new(5, false);
+        replay.record(300, Direction::Left);
+        let text = replay.encode();
+        // not base64, a single leftover digit, too short for the header
+        for bad in ["", "not a replay!", "A", "AQAA"] {
+            assert!(ShareReplay::decode(bad).is_err(), "{:?}", bad);
+        }
+        // a turn cut off halfway
+        let mut bytes = from_base64(&text).unwrap();
+        bytes.pop();
+        assert!(ShareReplay::decode(&to_base64(&bytes)).is_err());
+        // an unknown version
+        bytes[0] = VERSION + 1;
+        let error = ShareReplay::decode(&to_base64(&bytes)).err().unwrap();
+        assert!(error.contains("version"), "{}", error);
+    }
+}
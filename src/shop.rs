@@ -0,0 +1,150 @@
+//! Coins earned from runs and the unlock shop they are spent in.
+
+use crate::config::Config;
+use crate::menu::select_from;
+use crate::profile::Profile;
+use crossterm::Result;
+use std::io::Write;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Unlock {
+    Theme(&'static str),
+    Glyphs(&'static str),
+    Mode(&'static str),
+    /// a power-up every run starts with
+    PowerUp(&'static str),
+}
+
+pub struct Item {
+    pub id: &'static str, // stored in the profile once bought
+    pub unlock: Unlock,
+    pub price: u32,
+    pub description: &'static str,
+}
+
+pub const ITEMS: &[Item] = &[
+    Item {
+        id: "theme-neon",
+        unlock: Unlock::Theme("neon"),
+        price: 30,
+        description: "neon color theme",
+    },
+    Item {
+        id: "theme-sunset",
+        unlock: Unlock::Theme("sunset"),
+        price: 30,
+        description: "sunset color theme",
+    },
+    Item {
+        id: "glyphs-ascii",
+        unlock: Unlock::Glyphs("ascii"),
+        price: 20,
+        description: "plain ASCII glyphs",
+    },
+    Item {
+        id: "mode-time-attack",
+        unlock: Unlock::Mode("time-attack"),
+        price: 40,
+        description: "time attack mode (--mode time-attack)",
+    },
+    Item {
+        id: "mode-tron",
+        unlock: Unlock::Mode("tron"),
+        price: 60,
+        description: "tron mode (--mode tron)",
+    },
+    Item {
+        id: "powerup-shield",
+        unlock: Unlock::PowerUp("shield"),
+        price: 100,
+        description: "start every run with a shield that undoes the first crash",
+    },
+];
+
+/// coins awarded for a finished run
+pub fn coins_for(score: u16) -> u32 {
+    u32::from(score)
+}
+
+fn owns(profile: &Profile, item: &Item) -> bool {
+    profile.unlocked.iter().any(|id| id == item.id)
+}
+
+/// whether an unlock `matching` is sold in the shop and not bought yet
+fn is_locked(profile: &Profile, matching: impl Fn(Unlock) -> bool) -> bool {
+    ITEMS
+        .iter()
+        .any(|item| matching(item.unlock) && !owns(profile, item))
+}
+
+/// the theme and glyph set to use, with ones not bought yet replaced by the defaults
+pub fn usable_skin<'a>(profile: &Profile, theme: &'a str, glyphs: &'a str) -> (&'a str, &'a str) {
+    let theme_locked = is_locked(profile, |u| matches!(u, Unlock::Theme(n) if n == theme));
+    let glyphs_locked = is_locked(profile, |u| matches!(u, Unlock::Glyphs(n) if n == glyphs));
+    (
+        if theme_locked { "classic" } else { theme },
+        if glyphs_locked { "blocks" } else { glyphs },
+    )
+}
+
+pub fn is_theme_locked(profile: &Profile, theme: &str) -> bool {
+    is_locked(profile, |u| matches!(u, Unlock::Theme(n) if n == theme))
+}
+
+pub fn is_mode_locked(profile: &Profile, mode: &str) -> bool {
+    is_locked(profile, |u| matches!(u, Unlock::Mode(n) if n == mode))
+}
+
+pub fn has_power_up(profile: &Profile, name: &str) -> bool {
+    ITEMS
+        .iter()
+        .any(|item| matches!(item.unlock, Unlock::PowerUp(n) if n == name) && owns(profile, item))
+}
+
+fn is_equipped(config: &Config, item: &Item) -> bool {
+    match item.unlock {
+        Unlock::Theme(name) => config.game.theme == name,
+        Unlock::Glyphs(name) => config.game.glyphs == name,
+        Unlock::Mode(_) | Unlock::PowerUp(_) => true,
+    }
+}
+
+/// buy items with Enter, or equip ones already bought; both are saved right away
+pub fn shop_screen<T: Write>(buffer: &mut T, profile: &mut Profile) -> Result<()> {
+    let mut config = Config::load();
+    let mut selected = 0;
+    loop {
+        let entries: Vec<_> = ITEMS
+            .iter()
+            .map(|item| {
+                let state = match (owns(profile, item), is_equipped(&config, item)) {
+                    (true, true) => "in use".to_string(),
+                    (true, false) => "owned".to_string(),
+                    (false, _) => format!("{} coins", item.price),
+                };
+                format!("{:<10} {}", state, item.description)
+            })
+            .collect();
+        let title = format!("Unlocks - {} coins", profile.coins);
+        let i = match select_from(buffer, &title, &entries, selected)? {
+            Some(i) => i,
+            None => return Ok(()),
+        };
+        selected = i;
+        let item = &ITEMS[i];
+        if !owns(profile, item) {
+            if profile.coins < item.price {
+                continue;
+            }
+            profile.coins -= item.price;
+            profile.unlocked.push(item.id.to_string());
+            let _ = profile.save();
+        }
+        match item.unlock {
+            Unlock::Theme(name) => config.game.theme = name.to_string(),
+            Unlock::Glyphs(name) => config.game.glyphs = name.to_string(),
+            Unlock::Mode(_) | Unlock::PowerUp(_) => continue,
+        }
+        let _ = config.save();
+    }
+}
@@ -0,0 +1,74 @@
+//! Headless runs for `rust-snake simulate`: a bot plays game after game with
+//! no terminal involved, and the totals are printed at the end.
+//!
+//! Only the engine runs, so plugins and scripts are left out. A game the
+//! bot can't end within `MAX_TICKS` ticks, as in zen mode, counts as
+//! unfinished.
+
+use crate::bot;
+use crate::results;
+use crate::Setup;
+use snake_core::bus::Notice;
+use snake_core::Rng;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const MAX_TICKS: u64 = 100_000;
+
+#[derive(Default)]
+pub struct Totals {
+    games: u32,
+    score: u64, // summed over all games
+    best: u16,
+    max_len: usize,
+    ends: BTreeMap<&'static str, u32>,
+}
+
+/// play `games` games of `setup` with the `strategy` bot; the same `seed`
+/// always gives the same games
+pub fn run(setup: &Setup, strategy: &str, games: u32, seed: u64) -> Totals {
+    let mut rng = Rng::new(seed);
+    let mut totals = Totals::default();
+    for _ in 0..games {
+        let mut state = setup.new_state(rng.next_u64());
+        let mut crash = None;
+        while !state.is_over && state.tick < MAX_TICKS {
+            let dir = bot::decide(strategy, &state, &mut rng);
+            state.step(Some(dir));
+            for notice in state.bus.drain(..) {
+                if let Notice::Crashed(cause) = notice {
+                    crash = Some(cause);
+                }
+            }
+        }
+        let end = match (state.is_over, crash) {
+            (false, _) => "unfinished",
+            (true, None) if !state.won => "time",
+            (true, crash) => results::end_name(crash, state.won),
+        };
+        totals.games += 1;
+        totals.score += u64::from(state.score);
+        totals.best = totals.best.max(state.score);
+        totals.max_len = totals.max_len.max(state.snake.body.len());
+        *totals.ends.entry(end).or_default() += 1;
+    }
+    totals
+}
+
+impl Totals {
+    pub fn report(&self) -> String {
+        let mean = self.score as f64 / f64::from(self.games.max(1));
+        let ends: Vec<_> = self
+            .ends
+            .iter()
+            .map(|(end, count)| format!("{} {}", end, count))
+            .collect();
+        let mut text = String::new();
+        let _ = writeln!(text, "games       {}", self.games);
+        let _ = writeln!(text, "mean score  {:.2}", mean);
+        let _ = writeln!(text, "best score  {}", self.best);
+        let _ = writeln!(text, "max length  {}", self.max_len);
+        let _ = writeln!(text, "ends        {}", ends.join(", "));
+        text
+    }
+}
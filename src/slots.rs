@@ -0,0 +1,108 @@
+//! Save/load screen listing the save slots with a preview of each run.
+
+use crate::input;
+use crate::menu::select;
+use crate::save::{self, SaveFile};
+use crate::share::{self, ShareReplay};
+use chrono::{Local, TimeZone};
+use crossterm::{
+    cursor,
+    event::{Event, KeyCode, KeyEvent},
+    execute, queue,
+    style::{self, Stylize},
+    terminal, Result,
+};
+use snake_core::GameState;
+use std::io::Write;
+use std::time::Duration;
+
+const NEW_SLOT: &str = "<new slot>";
+const IMPORT_REPLAY: &str = "<import replay string>";
+
+/// what the load screen picked
+pub enum Loaded {
+    State(Box<SaveFile>),
+    Replay(ShareReplay),
+}
+
+fn preview(slot: &SaveFile) -> String {
+    let time = Local
+        .timestamp_opt(slot.saved_at, 0)
+        .single()
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    format!(
+        "{:<16} score {:>4}  length {:>4}  {:<8} {}",
+        slot.name,
+        slot.state.score,
+        slot.state.snake.body.len(),
+        slot.mode,
+        time
+    )
+}
+
+/// read the name of a new slot, echoing it as it is typed
+fn read_name<T: Write>(buffer: &mut T) -> Result<Option<String>> {
+    let mut name = String::new();
+    loop {
+        execute!(buffer, terminal::Clear(terminal::ClearType::All))?;
+        queue!(
+            buffer,
+            cursor::MoveTo(10, 0),
+            style::PrintStyledContent("New save slot".magenta()),
+            cursor::MoveTo(4, 2),
+            style::PrintStyledContent(format!("Name: {}_", name).yellow()),
+            cursor::MoveTo(4, 4),
+            style::PrintStyledContent(
+                "letters, digits, - and _  Enter: save  Esc: back".dark_grey()
+            )
+        )?;
+        buffer.flush()?;
+        if let Event::Key(KeyEvent { code, .. }) = input::read()? {
+            match code {
+                KeyCode::Char(c) if save::is_valid_slot_name(&format!("{}{}", name, c)) => {
+                    name.push(c)
+                }
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Enter if !name.is_empty() => return Ok(Some(name)),
+                KeyCode::Esc => return Ok(None),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// save `state`, `played` into its run, into an existing or a new slot
+pub fn save_screen<T: Write>(
+    buffer: &mut T,
+    mode: &str,
+    state: &GameState,
+    played: Duration,
+) -> Result<()> {
+    let slots = save::list_slots();
+    let mut entries: Vec<_> = slots.iter().map(preview).collect();
+    entries.push(NEW_SLOT.to_string());
+    let name = match select(buffer, "Save game", &entries)? {
+        Some(i) if i < slots.len() => slots[i].name.clone(),
+        Some(_) => match read_name(buffer)? {
+            Some(name) => name,
+            None => return Ok(()),
+        },
+        None => return Ok(()),
+    };
+    save::write_slot(&name, mode, state, played)
+}
+
+/// pick a slot to load or import a replay string; `None` if the player backed out
+pub fn load_screen<T: Write>(buffer: &mut T) -> Result<Option<Loaded>> {
+    let mut slots = save::list_slots();
+    let mut entries: Vec<_> = slots.iter().map(preview).collect();
+    entries.push(IMPORT_REPLAY.to_string());
+    Ok(match select(buffer, "Load game", &entries)? {
+        Some(i) if i < slots.len() => Some(Loaded::State(Box::new(slots.swap_remove(i)))),
+        Some(_) => share::import_screen(buffer)?.map(Loaded::Replay),
+        None => None,
+    })
+}
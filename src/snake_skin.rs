@@ -0,0 +1,88 @@
+//! The look of the player's snake: its own head, body and tail glyphs and
+//! a solid, gradient or striped run of colors, set in `[skin]`.
+//!
+//! ```toml
+//! [skin]
+//! head = "@@"
+//! body = "oo"
+//! colors = ["#268bd2", "#2aa198"]
+//! pattern = "gradient"
+//! ```
+
+use crate::colors;
+use crate::config::{SkinConfig, SkinPattern};
+use crate::theme;
+use crate::CELL_SZ;
+use crossterm::style::Color;
+
+#[derive(Clone, Default)]
+pub struct SnakeSkin {
+    head: Option<String>,
+    body: Option<String>,
+    tail: Option<String>,
+    head_color: Option<Color>,
+    colors: Vec<Color>,
+    pattern: SkinPattern,
+}
+
+fn glyph(name: &str, glyph: &Option<String>) -> Result<Option<String>, String> {
+    match glyph {
+        Some(g) if g.chars().count() != usize::from(CELL_SZ.0) => Err(format!(
+            "skin {}: {:?} must be {} characters wide",
+            name, g, CELL_SZ.0
+        )),
+        _ => Ok(glyph.clone()),
+    }
+}
+
+fn color(name: &str) -> Result<Color, String> {
+    theme::parse_color(name).ok_or_else(|| format!("skin: unknown color {:?}", name))
+}
+
+impl SnakeSkin {
+    pub fn new(config: &SkinConfig) -> Result<Self, String> {
+        Ok(Self {
+            head: glyph("head", &config.head)?,
+            body: glyph("body", &config.body)?,
+            tail: glyph("tail", &config.tail)?,
+            head_color: config.head_color.as_deref().map(color).transpose()?,
+            colors: config
+                .colors
+                .iter()
+                .map(|name| color(name))
+                .collect::<Result<_, _>>()?,
+            pattern: config.pattern,
+        })
+    }
+
+    /// the glyph of segment `i` of a snake `len` long, counted from the head,
+    /// or `plain` if the skin doesn't set one
+    pub fn glyph<'a>(&'a self, i: usize, len: usize, plain: &'a str) -> &'a str {
+        let glyph = match i {
+            0 => self.head.as_ref().or(self.body.as_ref()),
+            _ if i + 1 == len && len > 1 => self.tail.as_ref().or(self.body.as_ref()),
+            _ => self.body.as_ref(),
+        };
+        glyph.map_or(plain, String::as_str)
+    }
+
+    /// the color of segment `i` of a snake `len` long, counted from the head,
+    /// or `plain` if the skin doesn't set one
+    pub fn color(&self, i: usize, len: usize, plain: Color) -> Color {
+        if let (0, Some(head)) = (i, self.head_color) {
+            return head;
+        }
+        let colors = &self.colors;
+        match (colors.len(), self.pattern) {
+            (0, _) => plain,
+            (1, _) | (_, SkinPattern::Solid) => colors[0],
+            (_, SkinPattern::Striped) => colors[i % colors.len()],
+            (n, SkinPattern::Gradient) => {
+                // how far along the body, from 0 at the head to n - 1 at the tail
+                let at = (i as f64 / len.saturating_sub(1).max(1) as f64) * (n - 1) as f64;
+                let from = (at.floor() as usize).min(n - 2);
+                colors::blend(colors[from], colors[from + 1], at - from as f64)
+            }
+        }
+    }
+}
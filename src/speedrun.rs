@@ -0,0 +1,270 @@
+//! Speedrun mode: how fast can the snake reach each length milestone, or
+//! the score each points milestone.
+//!
+//! The clock runs to the millisecond and stops while the game waits on a
+//! menu. Every attempt is added as a row to `speedruns.csv` (score runs) or
+//! `speedruns_length.csv` (length runs) in the data directory, with the
+//! split times in milliseconds, so attempts can be compared outside the game.
+
+use crate::save;
+use chrono::Local;
+use crossterm::style::{StyledContent, Stylize};
+use serde::{Deserialize, Serialize};
+use snake_core::GameState;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// snake lengths timed by a split, the run is complete at the last one
+pub const MILESTONES: [usize; 3] = [10, 25, 50];
+/// scores timed by a split in a points run
+pub const SCORE_MILESTONES: [usize; 3] = [10, 25, 50];
+
+/// what a run races to, picked with `--speedrun length|score`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    Length,
+    Score,
+}
+
+impl Goal {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "length" => Some(Self::Length),
+            "score" => Some(Self::Score),
+            _ => None,
+        }
+    }
+
+    pub fn milestones(self) -> &'static [usize] {
+        match self {
+            Self::Length => &MILESTONES,
+            Self::Score => &SCORE_MILESTONES,
+        }
+    }
+
+    /// how far `state` has come towards the milestones
+    fn progress(self, state: &GameState) -> usize {
+        match self {
+            Self::Length => state.snake.body.len(),
+            Self::Score => usize::from(state.score),
+        }
+    }
+
+    fn label(self, milestone: usize) -> String {
+        match self {
+            Self::Length => milestone.to_string(),
+            Self::Score => format!("{}pts", milestone),
+        }
+    }
+}
+
+/// personal records, in milliseconds, kept in the profile
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Records {
+    /// split times of the fastest complete run
+    pub best: Vec<u64>,
+    /// fastest time ever for each segment between milestones
+    pub golds: Vec<u64>,
+}
+
+pub fn format_time(time: Duration) -> String {
+    let ms = time.as_millis();
+    format!("{}:{:02}.{:03}", ms / 60_000, ms / 1000 % 60, ms % 1000)
+}
+
+fn millis(time: Duration) -> u64 {
+    time.as_millis() as u64
+}
+
+pub struct Speedrun {
+    started: Instant,
+    paused_at: Option<Instant>,
+    paused_for: Duration,
+    pub splits: Vec<Duration>,
+    pub goal: Goal,
+    records: Records, // as they were when the run started
+}
+
+impl Speedrun {
+    pub fn new(goal: Goal, records: Records) -> Self {
+        Self {
+            started: Instant::now(),
+            paused_at: None,
+            paused_for: Duration::ZERO,
+            splits: Vec::new(),
+            goal,
+            records,
+        }
+    }
+
+    /// stop the clock while the game waits on a menu or an idle pause
+    pub fn pause(&mut self) {
+        self.paused_at.get_or_insert_with(Instant::now);
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(at) = self.paused_at.take() {
+            self.paused_for += at.elapsed();
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        let now = self.paused_at.unwrap_or_else(Instant::now);
+        now.duration_since(self.started) - self.paused_for
+    }
+
+    /// take a split for every milestone `state` has reached
+    pub fn update(&mut self, state: &GameState) {
+        let milestones = self.goal.milestones();
+        let progress = self.goal.progress(state);
+        while self.splits.len() < milestones.len() && progress >= milestones[self.splits.len()] {
+            self.splits.push(self.elapsed());
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.splits.len() == self.goal.milestones().len()
+    }
+
+    fn segment(&self, i: usize) -> Duration {
+        self.splits[i] - i.checked_sub(1).map_or(Duration::ZERO, |j| self.splits[j])
+    }
+
+    /// timer and splits, each colored against the personal best:
+    /// gold for a new best segment, green when ahead, red when behind
+    pub fn hud(&self) -> Vec<StyledContent<String>> {
+        let mut parts = vec![format!("SPEEDRUN {}", format_time(self.elapsed())).cyan()];
+        for (i, &milestone) in self.goal.milestones().iter().enumerate() {
+            let label = self.goal.label(milestone);
+            let best = self
+                .records
+                .best
+                .get(i)
+                .map(|&ms| Duration::from_millis(ms));
+            let part = match (self.splits.get(i), best) {
+                (Some(&split), Some(best)) => {
+                    let delta = if split <= best {
+                        format!("-{}", format_time(best - split))
+                    } else {
+                        format!("+{}", format_time(split - best))
+                    };
+                    let text = format!("{}: {} ({})", label, format_time(split), delta);
+                    let gold = self.records.golds.get(i);
+                    if gold.is_some_and(|&g| millis(self.segment(i)) < g) {
+                        text.yellow()
+                    } else if split <= best {
+                        text.green()
+                    } else {
+                        text.red()
+                    }
+                }
+                (Some(&split), None) => format!("{}: {}", label, format_time(split)).white(),
+                (None, Some(best)) => format!("{}: {}", label, format_time(best)).dark_grey(),
+                (None, None) => format!("{}: --", label).dark_grey(),
+            };
+            parts.push(part);
+        }
+        parts
+    }
+
+    /// lines for the end-of-run summary, each flagged if it is a new record
+    pub fn summary(&self) -> Vec<(String, bool)> {
+        let mut lines = Vec::new();
+        for (i, split) in self.splits.iter().enumerate() {
+            let segment = millis(self.segment(i));
+            let gold = self.records.golds.get(i).is_none_or(|&g| segment < g);
+            let text = format!(
+                "Split {}: {}  segment {}",
+                self.goal.label(self.goal.milestones()[i]),
+                format_time(*split),
+                format_time(self.segment(i))
+            );
+            lines.push(if gold {
+                (format!("{}  GOLD", text), true)
+            } else {
+                (text, false)
+            });
+        }
+        if self.is_complete() {
+            let total = self.splits[self.splits.len() - 1];
+            lines.push(match self.records.best.last() {
+                Some(&best) if millis(total) >= best => (
+                    format!(
+                        "Final time: {}  (best {})",
+                        format_time(total),
+                        format_time(Duration::from_millis(best))
+                    ),
+                    false,
+                ),
+                _ => (
+                    format!("Final time: {}  NEW BEST", format_time(total)),
+                    true,
+                ),
+            });
+        }
+        lines
+    }
+
+    /// save new golds, and the splits as personal best if the run was complete and faster
+    pub fn save_records(&self, records: &mut Records) {
+        for i in 0..self.splits.len() {
+            let segment = millis(self.segment(i));
+            match records.golds.get_mut(i) {
+                Some(gold) => *gold = (*gold).min(segment),
+                None => records.golds.push(segment),
+            }
+        }
+        let total = self.splits.last().map(|&t| millis(t));
+        let best = records.best.last().copied();
+        if self.is_complete() && (best.is_none() || total < best) {
+            records.best = self.splits.iter().map(|&t| millis(t)).collect();
+        }
+    }
+
+    /// add the attempt to the log, with the time and `seed` it was played with
+    pub fn log(&self, seed: u64) -> io::Result<()> {
+        let path = log_path(self.goal).ok_or_else(|| io::Error::other("no data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let new = !path.exists();
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if new {
+            let splits: Vec<_> = self
+                .goal
+                .milestones()
+                .iter()
+                .map(|m| format!("split_{}", m))
+                .collect();
+            writeln!(file, "date,seed,{},complete", splits.join(","))?;
+        }
+        // milestones not reached are left empty
+        let splits: Vec<_> = (0..self.goal.milestones().len())
+            .map(|i| {
+                self.splits
+                    .get(i)
+                    .map_or(String::new(), |&t| millis(t).to_string())
+            })
+            .collect();
+        writeln!(
+            file,
+            "{},{},{},{}",
+            Local::now().format("%Y-%m-%d %H:%M:%S"),
+            seed,
+            splits.join(","),
+            self.is_complete()
+        )
+    }
+}
+
+/// where every attempt at `goal` is logged
+fn log_path(goal: Goal) -> Option<PathBuf> {
+    let name = match goal {
+        Goal::Length => "speedruns_length.csv",
+        Goal::Score => "speedruns.csv",
+    };
+    save::data_dir().map(|dir| dir.join(name))
+}
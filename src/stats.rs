@@ -0,0 +1,76 @@
+//! Stats of a single run, collected from the game's notice bus.
+
+use serde::{Deserialize, Serialize};
+use snake_core::bus::{Crash, Notice};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const COMBO_TICKS: u64 = 20; // foods eaten at most this many ticks apart form a combo
+
+#[derive(Default)]
+pub struct RunStats {
+    pub foods: BTreeMap<&'static str, u32>,
+    pub max_combo: u32,
+    combo: u32,
+    last_meal: Option<u64>,
+    pub turns: u32,
+    pub distance: u32, // cells moved
+    pub ticks: u64,
+    pub crash: Option<Crash>,
+}
+
+impl RunStats {
+    pub fn record(&mut self, notice: Notice, tick: u64) {
+        self.ticks = tick;
+        match notice {
+            Notice::Ate(kind) => {
+                *self.foods.entry(kind).or_default() += 1;
+                self.combo = match self.last_meal {
+                    Some(last) if tick - last <= COMBO_TICKS => self.combo + 1,
+                    _ => 1,
+                };
+                self.max_combo = self.max_combo.max(self.combo);
+                self.last_meal = Some(tick);
+            }
+            Notice::Turned(_) => self.turns += 1,
+            Notice::Moved => self.distance += 1,
+            Notice::Crashed(crash) => self.crash = Some(crash),
+            Notice::PowerUp(_) | Notice::Smashed | Notice::Poisoned | Notice::Eliminated => (),
+        }
+    }
+
+    /// ticks since the last food, or since the start if none was eaten yet
+    pub fn ticks_since_food(&self) -> u64 {
+        self.ticks.saturating_sub(self.last_meal.unwrap_or(0))
+    }
+
+    /// average speed in cells per second over `survived`, the game time of the run
+    pub fn speed(&self, survived: Duration) -> f64 {
+        let secs = survived.as_secs_f64();
+        if secs > 0.0 {
+            f64::from(self.distance) / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// personal bests over all ranked runs
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bests {
+    pub score: u16,
+    pub max_combo: u32,
+    pub survived_secs: u64,
+    pub distance: u32,
+}
+
+impl Bests {
+    /// raise the bests beaten by a finished run that lasted `survived` in game time
+    pub fn update(&mut self, score: u16, stats: &RunStats, survived: Duration) {
+        self.score = self.score.max(score);
+        self.max_combo = self.max_combo.max(stats.max_combo);
+        self.survived_secs = self.survived_secs.max(survived.as_secs());
+        self.distance = self.distance.max(stats.distance);
+    }
+}
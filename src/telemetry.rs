@@ -0,0 +1,86 @@
+//! Strictly opt-in, anonymous usage stats.
+//!
+//! Nothing identifying is recorded: only the mode played, a coarse score
+//! bucket and the session length. Sessions are queued on disk and posted in
+//! batches to the configured endpoint.
+
+use crate::config::TelemetryConfig;
+use crate::save;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const BATCH_SIZE: usize = 5; // sessions queued before a batch is sent
+const TIMEOUT: u64 = 3; // upload timeout in seconds
+
+#[derive(Serialize, Deserialize)]
+pub struct Session {
+    mode: String,
+    score_bucket: String,
+    session_secs: u64,
+}
+
+impl Session {
+    pub fn new(mode: &str, score: u16, length: Duration) -> Self {
+        Self {
+            mode: mode.to_string(),
+            score_bucket: score_bucket(score).to_string(),
+            session_secs: length.as_secs(),
+        }
+    }
+}
+
+fn score_bucket(score: u16) -> &'static str {
+    match score {
+        0..=9 => "0-9",
+        10..=24 => "10-24",
+        25..=49 => "25-49",
+        50..=99 => "50-99",
+        _ => "100+",
+    }
+}
+
+fn queue_path() -> Option<PathBuf> {
+    save::data_dir().map(|dir| dir.join("telemetry.json"))
+}
+
+fn load_queue() -> Vec<Session> {
+    queue_path()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+fn store_queue(queue: &[Session]) {
+    if let Some(path) = queue_path() {
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(data) = serde_json::to_vec(queue) {
+            let _ = fs::write(path, data);
+        }
+    }
+}
+
+/// queue a finished session and send the batch once it is full
+///
+/// Does nothing unless the player opted in. Failed uploads stay queued.
+pub fn record(config: &TelemetryConfig, session: Session) {
+    let endpoint = match &config.endpoint {
+        Some(endpoint) if config.enabled == Some(true) => endpoint,
+        _ => return,
+    };
+    let mut queue = load_queue();
+    queue.push(session);
+    if queue.len() >= BATCH_SIZE {
+        let sent = ureq::post(endpoint)
+            .timeout(Duration::from_secs(TIMEOUT))
+            .send_json(serde_json::json!({ "sessions": queue }))
+            .is_ok();
+        if sent {
+            queue.clear();
+        }
+    }
+    store_queue(&queue);
+}
@@ -0,0 +1,192 @@
+//! Color themes: the palette the board and the title are drawn in.
+//!
+//! The built-in themes are classic, solarized, monochrome, neon, sunset and
+//! colorblind; mods add more (see `content`). Colors are crossterm color names, or
+//! `#rrggbb` for any other color.
+
+use crate::Tile;
+use crossterm::style::Color;
+use serde::{de::Error, Deserialize, Deserializer};
+
+/// a crossterm color name or `#rrggbb`
+pub fn parse_color(name: &str) -> Option<Color> {
+    match name.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 => {
+            let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+            Some(Color::Rgb {
+                r: channel(0)?,
+                g: channel(2)?,
+                b: channel(4)?,
+            })
+        }
+        Some(_) => None,
+        None => Color::try_from(name).ok(),
+    }
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    parse_color(&name).ok_or_else(|| D::Error::custom(format!("unknown color {:?}", name)))
+}
+
+fn poison_color() -> Color {
+    Color::Green
+}
+
+fn rival_color() -> Color {
+    Color::Cyan
+}
+
+fn text_color() -> Color {
+    Color::Magenta
+}
+
+/// colors of the board tiles
+#[derive(Clone, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    #[serde(deserialize_with = "color")]
+    snake: Color,
+    #[serde(deserialize_with = "color")]
+    food: Color,
+    #[serde(deserialize_with = "color")]
+    wall: Color,
+    #[serde(deserialize_with = "color")]
+    hazard: Color,
+    #[serde(deserialize_with = "color")]
+    bonus: Color,
+    #[serde(deserialize_with = "color", default = "poison_color")]
+    poison: Color,
+    /// the second snake in a two-player game
+    #[serde(deserialize_with = "color", default = "rival_color")]
+    rival: Color,
+    /// every tile while the game is paused
+    #[serde(deserialize_with = "color")]
+    dimmed: Color,
+    /// the title above the board
+    #[serde(deserialize_with = "color", default = "text_color")]
+    pub text: Color,
+}
+
+/// the themes every build comes with, classic first
+pub fn builtin() -> Vec<Theme> {
+    vec![
+        Theme::classic(),
+        Theme::solarized(),
+        Theme::monochrome(),
+        Theme::neon(),
+        Theme::sunset(),
+        Theme::colorblind(),
+    ]
+}
+
+impl Theme {
+    pub fn classic() -> Self {
+        Self {
+            name: "classic".to_string(),
+            snake: Color::Blue,
+            food: Color::Red,
+            wall: Color::White,
+            hazard: Color::Magenta,
+            bonus: Color::Yellow,
+            poison: Color::Green,
+            rival: Color::Cyan,
+            dimmed: Color::DarkGrey,
+            text: Color::Magenta,
+        }
+    }
+
+    /// Ethan Schoonover's solarized accents on its grey tones
+    fn solarized() -> Self {
+        let rgb = |r, g, b| Color::Rgb { r, g, b };
+        Self {
+            name: "solarized".to_string(),
+            snake: rgb(0x26, 0x8b, 0xd2),
+            food: rgb(0xdc, 0x32, 0x2f),
+            wall: rgb(0x93, 0xa1, 0xa1),
+            hazard: rgb(0xd3, 0x36, 0x82),
+            bonus: rgb(0xb5, 0x89, 0x00),
+            poison: rgb(0x85, 0x99, 0x00),
+            rival: rgb(0x2a, 0xa1, 0x98),
+            dimmed: rgb(0x58, 0x6e, 0x75),
+            text: rgb(0x6c, 0x71, 0xc4),
+        }
+    }
+
+    /// shades of grey only, for terminals and eyes that do without color
+    fn monochrome() -> Self {
+        Self {
+            name: "monochrome".to_string(),
+            snake: Color::Grey,
+            food: Color::White,
+            wall: Color::DarkGrey,
+            hazard: Color::White,
+            bonus: Color::White,
+            poison: Color::Grey,
+            rival: Color::White,
+            dimmed: Color::DarkGrey,
+            text: Color::White,
+        }
+    }
+
+    fn neon() -> Self {
+        Self {
+            name: "neon".to_string(),
+            snake: Color::Cyan,
+            food: Color::Magenta,
+            wall: Color::DarkBlue,
+            hazard: Color::Red,
+            bonus: Color::Yellow,
+            poison: Color::Green,
+            rival: Color::White,
+            dimmed: Color::DarkGrey,
+            text: Color::Cyan,
+        }
+    }
+
+    fn sunset() -> Self {
+        Self {
+            name: "sunset".to_string(),
+            snake: Color::Yellow,
+            food: Color::Red,
+            wall: Color::DarkMagenta,
+            hazard: Color::DarkRed,
+            bonus: Color::White,
+            poison: Color::DarkGreen,
+            rival: Color::Cyan,
+            dimmed: Color::DarkGrey,
+            text: Color::Yellow,
+        }
+    }
+
+    /// Okabe and Ito's palette, told apart with any kind of color blindness;
+    /// meant to go with the `shapes` glyphs, so color is never all there is
+    fn colorblind() -> Self {
+        let rgb = |r, g, b| Color::Rgb { r, g, b };
+        Self {
+            name: "colorblind".to_string(),
+            snake: rgb(0x56, 0xb4, 0xe9),
+            food: rgb(0xe6, 0x9f, 0x00),
+            wall: rgb(0xbb, 0xbb, 0xbb),
+            hazard: rgb(0xd5, 0x5e, 0x00),
+            bonus: rgb(0xf0, 0xe4, 0x42),
+            poison: rgb(0x00, 0x9e, 0x73),
+            rival: rgb(0xcc, 0x79, 0xa7),
+            dimmed: Color::DarkGrey,
+            text: rgb(0xf0, 0xe4, 0x42),
+        }
+    }
+
+    pub fn color(&self, tile: Tile, dimmed: bool) -> Color {
+        match tile {
+            _ if dimmed => self.dimmed,
+            Tile::Snake => self.snake,
+            Tile::Food => self.food,
+            Tile::Wall => self.wall,
+            Tile::Hazard => self.hazard,
+            Tile::Bonus => self.bonus,
+            Tile::Poison => self.poison,
+            Tile::Rival => self.rival,
+        }
+    }
+}
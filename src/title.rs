@@ -0,0 +1,66 @@
+//! Title screen shown before the first run: continue a saved run, play,
+//! change settings, look at the high scores or quit. Left alone for a while,
+//! it plays a demo game.
+
+use crate::menu::{select_idle, Outcome};
+use crate::options;
+use crate::save::{self, SaveFile};
+use crate::scores::{self, Scores};
+use crossterm::Result;
+use std::io::Write;
+use std::time::Duration;
+
+const ENTRIES: [&str; 4] = ["Play", "Settings", "High Scores", "Quit"];
+const CONTINUE: &str = "Continue"; // offered first while there is a run to continue
+const DEMO_AFTER: Duration = Duration::from_secs(20); // idle time before the demo starts
+
+/// what the player chose on the title screen
+pub enum Choice {
+    Play,
+    /// carry on with the most recent save of an unfinished run
+    Continue(Box<SaveFile>),
+    Quit,
+}
+
+/// run the title menu headed `title`, playing `demo` whenever it idles.
+/// `themes` are offered in the settings
+pub fn title_screen<T: Write>(
+    buffer: &mut T,
+    title: &str,
+    themes: &[String],
+    mut demo: impl FnMut(&mut T) -> Result<()>,
+) -> Result<Choice> {
+    let mut latest = save::latest_slot();
+    let mut entries: Vec<_> = ENTRIES.iter().map(|entry| entry.to_string()).collect();
+    if latest.is_some() {
+        entries.insert(0, CONTINUE.to_string());
+    }
+    let mut selected = 0;
+    loop {
+        let picked = match select_idle(buffer, title, &entries, selected, DEMO_AFTER)? {
+            Outcome::Picked(i) => i,
+            Outcome::Idle => {
+                demo(buffer)?;
+                continue;
+            }
+            Outcome::Back => return Ok(Choice::Quit),
+        };
+        selected = picked;
+        match entries[picked].as_str() {
+            CONTINUE => {
+                return Ok(latest
+                    .take()
+                    .map_or(Choice::Play, |slot| Choice::Continue(Box::new(slot))))
+            }
+            "Play" => return Ok(Choice::Play),
+            // saved right away, the caller loads the config again
+            "Settings" => {
+                options::options_screen(buffer, themes)?;
+            }
+            "High Scores" => {
+                scores::scores_screen(buffer, &Scores::load())?;
+            }
+            _ => return Ok(Choice::Quit),
+        }
+    }
+}
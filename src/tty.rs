@@ -0,0 +1,38 @@
+//! The terminal while the game runs: raw mode, the alternate screen and a
+//! hidden cursor for as long as a [`TerminalGuard`] lives.
+//!
+//! The shell gets its terminal back however the game ends: when the guard is
+//! dropped, from the panic hook and before a command line error exits.
+
+use crossterm::{cursor, execute, terminal, Result};
+use std::io::stdout;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// whether the terminal is set up for the game and needs restoring
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+pub struct TerminalGuard(());
+
+impl TerminalGuard {
+    /// enable raw mode, switch to the alternate screen and hide the cursor
+    pub fn enter() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        ACTIVE.store(true, Ordering::SeqCst);
+        execute!(stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self(()))
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// undo `TerminalGuard::enter`, if the terminal is still set up for the game
+pub fn restore() {
+    if ACTIVE.swap(false, Ordering::SeqCst) {
+        let _ = execute!(stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
@@ -0,0 +1,136 @@
+//! Guided first game that introduces the controls and rules step by step.
+
+use snake_core::powerup::{Kind, PowerUps};
+use snake_core::{Cell, GameState};
+
+enum Goal {
+    Turns(u32),
+    Score(u16),
+    Ticks(u32),
+    PowerUp(Kind),
+}
+
+struct Step {
+    prompt: &'static str,
+    food: Option<(u16, u16)>, // scripted food placement when the step starts
+    goal: Goal,
+}
+
+const STEPS: &[Step] = &[
+    Step {
+        prompt: "Steer with the arrow keys, WASD or hjkl. Make 3 turns.",
+        food: Some((1, 1)),
+        goal: Goal::Turns(3),
+    },
+    Step {
+        prompt: "Eat the red food to grow and score a point.",
+        food: Some((15, 8)),
+        goal: Goal::Score(1),
+    },
+    Step {
+        prompt: "Well done! Eat one more.",
+        food: Some((22, 24)),
+        goal: Goal::Score(1),
+    },
+    Step {
+        prompt: "Walls and your own body are deadly. Stay alive for 40 ticks.",
+        food: None,
+        goal: Goal::Ticks(40),
+    },
+    Step {
+        prompt: "Power-ups work for a while: >> speeds up, << slows down, x2 doubles points. Eat the x2.",
+        food: None,
+        goal: Goal::PowerUp(Kind::Double),
+    },
+];
+
+pub struct Tutorial {
+    step: usize,
+    start_score: u16,
+    turns: u32,
+    ticks: u32,
+}
+
+impl Tutorial {
+    pub fn new(state: &mut GameState) -> Self {
+        let mut tutorial = Self {
+            step: 0,
+            start_score: 0,
+            turns: 0,
+            ticks: 0,
+        };
+        tutorial.enter(state);
+        tutorial
+    }
+
+    /// start the current step: reset its counters and place its food
+    fn enter(&mut self, state: &mut GameState) {
+        self.start_score = state.score;
+        self.turns = 0;
+        self.ticks = 0;
+        let step = match STEPS.get(self.step) {
+            Some(step) => step,
+            None => return,
+        };
+        if let Some((x, y)) = step.food {
+            state.food = vec![Cell::new(x, y)];
+            if state.snake.check_overlap_food(&state.food[0]) {
+                state.move_food(0);
+            }
+        }
+        // the power-up lies where the food of the earlier steps was, and stays
+        if let Goal::PowerUp(kind) = step.goal {
+            let mut cell = Cell::new(15, 8);
+            if state.snake.check_overlap_food(&cell) {
+                cell = state.random_free_cell().unwrap_or(cell);
+            }
+            let power_ups = state.power_ups.get_or_insert_with(PowerUps::default);
+            power_ups.place(cell, kind, u64::MAX);
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.step >= STEPS.len()
+    }
+
+    pub fn prompt(&self) -> String {
+        match STEPS.get(self.step) {
+            Some(step) => format!(
+                "Tutorial {}/{}: {}",
+                self.step + 1,
+                STEPS.len(),
+                step.prompt
+            ),
+            None => "Tutorial complete!".to_string(),
+        }
+    }
+
+    pub fn on_turn(&mut self) {
+        self.turns += 1;
+    }
+
+    /// check the goal of the current step; a crash restarts the step
+    pub fn after_tick(&mut self, state: &mut GameState) {
+        if state.is_over {
+            let score = state.score;
+            *state = GameState::new(state.size, state.rng.next_u64());
+            state.score = score;
+            self.enter(state);
+            return;
+        }
+        self.ticks += 1;
+        let done = match STEPS.get(self.step).map(|step| &step.goal) {
+            Some(Goal::Turns(n)) => self.turns >= *n,
+            Some(Goal::Score(n)) => state.score.saturating_sub(self.start_score) >= *n,
+            Some(Goal::Ticks(n)) => self.ticks >= *n,
+            Some(Goal::PowerUp(kind)) => {
+                state.power_ups.as_ref().is_some_and(|p| p.is_active(*kind))
+            }
+            None => false,
+        };
+        if done {
+            self.step += 1;
+            self.enter(state);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Self-update for binaries installed from the release page.
+//!
+//! The latest release is looked up on GitHub, the asset matching this
+//! platform is downloaded and checked against its published `.sha256` file,
+//! then swapped in place of the running executable.
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+const RELEASE_FEED: &str = "https://api.github.com/repos/kiscad/rust-snake/releases/latest";
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+fn asset_name() -> String {
+    format!(
+        "rust-snake-{}-{}{}",
+        env::consts::ARCH,
+        env::consts::OS,
+        env::consts::EXE_SUFFIX
+    )
+}
+
+/// parse `v1.2.3` / `1.2.3` into comparable numbers
+fn parse_version(tag: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = tag
+        .trim_start_matches('v')
+        .split('.')
+        .map(|p| p.parse().ok());
+    Some((parts.next()??, parts.next()??, parts.next()??))
+}
+
+fn download(url: &str) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    ureq::get(url)
+        .call()
+        .map_err(io::Error::other)?
+        .into_reader()
+        .read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// replace the executable at `exe` with `binary`, keeping the old one until the swap succeeded
+fn swap_in(exe: &Path, binary: &[u8]) -> io::Result<()> {
+    let new = exe.with_extension("new");
+    let old = exe.with_extension("old");
+    fs::write(&new, binary)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&new, fs::Permissions::from_mode(0o755))?;
+    }
+    fs::rename(exe, &old)?;
+    if let Err(e) = fs::rename(&new, exe) {
+        fs::rename(&old, exe)?;
+        return Err(e);
+    }
+    // a running executable can't be deleted on windows; it is cleaned up next time
+    let _ = fs::remove_file(&old);
+    Ok(())
+}
+
+pub fn run() -> io::Result<()> {
+    let exe = env::current_exe()?;
+    if exe.components().any(|c| c.as_os_str() == ".cargo") {
+        println!("rust-snake was installed with cargo; run `cargo install rust-snake` to update.");
+        return Ok(());
+    }
+    let _ = fs::remove_file(exe.with_extension("old"));
+
+    let release: Release = ureq::get(RELEASE_FEED)
+        .call()
+        .map_err(io::Error::other)?
+        .into_json()?;
+    let current = env!("CARGO_PKG_VERSION");
+    // a tag like `v1.0.0-rc1` isn't a release to compare against
+    let latest = parse_version(&release.tag_name).ok_or_else(|| {
+        io::Error::other(format!(
+            "can't read the version of the latest release, {}",
+            release.tag_name
+        ))
+    })?;
+    if Some(latest) <= parse_version(current) {
+        println!("rust-snake {} is up to date.", current);
+        return Ok(());
+    }
+
+    let name = asset_name();
+    let find = |name: &str| {
+        release
+            .assets
+            .iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| io::Error::other(format!("release has no asset {}", name)))
+    };
+    let binary_asset = find(&name)?;
+    let checksum_asset = find(&format!("{}.sha256", name))?;
+
+    println!("Downloading {} {}...", name, release.tag_name);
+    let binary = download(&binary_asset.browser_download_url)?;
+    let checksum = String::from_utf8_lossy(&download(&checksum_asset.browser_download_url)?)
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let digest: String = Sha256::digest(&binary)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if digest != checksum {
+        return Err(io::Error::other("checksum mismatch, update aborted"));
+    }
+
+    swap_in(&exe, &binary)?;
+    println!("Updated rust-snake {} -> {}.", current, release.tag_name);
+    Ok(())
+}
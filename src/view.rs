@@ -0,0 +1,203 @@
+//! Mapping from board cells to terminal positions.
+//!
+//! Every board cell covers a block of `cell` terminal columns and rows. The
+//! default is one glyph (two columns) by one row; larger blocks repeat the
+//! glyph so the board stays readable on big screens and projectors.
+//!
+//! Boards too big for the terminal are shown through a camera following the
+//! snake's head. It only scrolls once the head leaves the middle of the
+//! screen, and arrows on the edges point to food out of sight.
+//!
+//! A terminal too small for even a few cells of the board holds the game
+//! until it is resized.
+//!
+//! On boards split into rooms by doorways the camera shows one room at a
+//! time and snaps to the next one as the head passes through a doorway.
+
+use crate::CELL_SZ;
+use crossterm::style::{self, StyledContent};
+use crossterm::{cursor, queue, Result};
+use snake_core::level::Room;
+use std::io::Write;
+
+const HUD_ROWS: u16 = 3; // terminal rows kept free below the board
+const MIN_VISIBLE: (u16, u16) = (12, 6); // board cells the camera shows at least
+
+#[derive(Clone, Copy)]
+pub struct View {
+    /// terminal columns and rows per board cell
+    pub cell: (u16, u16),
+    // first board cell in the top left corner of the screen
+    offset: (u16, u16),
+    // board cells that fit on the screen
+    visible: (u16, u16),
+    // the room being shown on boards with doorways
+    room: Option<Room>,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self {
+            cell: CELL_SZ,
+            offset: (0, 0),
+            visible: (u16::MAX, u16::MAX),
+            room: None,
+        }
+    }
+}
+
+/// the new offset along one axis keeping `head` out of the outer quarters
+fn scroll(offset: u16, head: u16, visible: u16, size: u16) -> u16 {
+    if size <= visible {
+        return 0;
+    }
+    let margin = visible / 4;
+    let offset = if head < offset + margin {
+        head.saturating_sub(margin)
+    } else if head + margin >= offset + visible {
+        head + margin + 1 - visible
+    } else {
+        offset
+    };
+    offset.min(size - visible)
+}
+
+impl View {
+    /// a view drawing each cell as a `width` x `height` block of characters
+    pub fn scaled((width, height): (u16, u16)) -> std::result::Result<Self, String> {
+        if width == 0 || height == 0 || width % CELL_SZ.0 != 0 {
+            return Err(format!(
+                "scale {}x{}: the width must be a multiple of {} and the height at least 1",
+                width, height, CELL_SZ.0
+            ));
+        }
+        Ok(Self {
+            cell: (width, height),
+            ..Self::default()
+        })
+    }
+
+    /// the size of the largest board shown whole on a `term` sized terminal
+    pub fn fit(&self, term: (u16, u16)) -> (u16, u16) {
+        (
+            (term.0 / self.cell.0).saturating_sub(1),
+            (term.1.saturating_sub(HUD_ROWS) / self.cell.1).saturating_sub(1),
+        )
+    }
+
+    /// the smallest terminal showing enough of a board of `size` cells to play on
+    pub fn min_term(&self, size: (u16, u16)) -> (u16, u16) {
+        (
+            (size.0.min(MIN_VISIBLE.0) + 1) * self.cell.0,
+            (size.1.min(MIN_VISIBLE.1) + 1) * self.cell.1 + HUD_ROWS,
+        )
+    }
+
+    /// fit the view to a `term` sized terminal and move the camera after `head`
+    pub fn follow(&mut self, head: (u16, u16), size: (u16, u16), rooms: &[Room], term: (u16, u16)) {
+        // a head in a doorway is in both rooms, so stay in the current one
+        if !self.room.is_some_and(|room| room.contains(head)) {
+            self.room = rooms.iter().find(|room| room.contains(head)).copied();
+        }
+        let area = self.room.unwrap_or(Room { pos: (0, 0), size });
+        self.visible = (
+            (term.0 / self.cell.0)
+                .saturating_sub(1)
+                .clamp(1, area.size.0),
+            (term.1.saturating_sub(HUD_ROWS) / self.cell.1)
+                .saturating_sub(1)
+                .clamp(1, area.size.1),
+        );
+        let axis = |offset: u16, head: u16, start: u16, visible: u16, len: u16| {
+            let offset = offset.clamp(start, start + len - visible);
+            start + scroll(offset - start, head - start, visible, len)
+        };
+        self.offset = (
+            axis(
+                self.offset.0,
+                head.0,
+                area.pos.0,
+                self.visible.0,
+                area.size.0,
+            ),
+            axis(
+                self.offset.1,
+                head.1,
+                area.pos.1,
+                self.visible.1,
+                area.size.1,
+            ),
+        );
+    }
+
+    pub fn is_visible(&self, pos: (u16, u16)) -> bool {
+        (self.offset.0..self.offset.0.saturating_add(self.visible.0)).contains(&pos.0)
+            && (self.offset.1..self.offset.1.saturating_add(self.visible.1)).contains(&pos.1)
+    }
+
+    /// top left terminal position of the board cell at `pos`, which must be visible
+    pub fn origin(&self, pos: (u16, u16)) -> (u16, u16) {
+        (
+            (pos.0 - self.offset.0 + 1) * self.cell.0,
+            (pos.1 - self.offset.1 + 1) * self.cell.1,
+        )
+    }
+
+    /// terminal columns and rows taken by the visible part of a board of `size`
+    /// cells, margin included
+    pub fn extent(&self, size: (u16, u16)) -> (u16, u16) {
+        (
+            (size.0.min(self.visible.0) + 1) * self.cell.0,
+            (size.1.min(self.visible.1) + 1) * self.cell.1,
+        )
+    }
+
+    /// the edge cell and arrow pointing to `pos` if it is off the screen
+    pub fn edge_toward(&self, pos: (u16, u16)) -> Option<((u16, u16), &'static str)> {
+        if self.is_visible(pos) {
+            return None;
+        }
+        let side = |p: u16, offset: u16, visible: u16| {
+            if p < offset {
+                (offset, -1)
+            } else if p >= offset + visible {
+                (offset + visible - 1, 1)
+            } else {
+                (p, 0)
+            }
+        };
+        let (x, dx) = side(pos.0, self.offset.0, self.visible.0);
+        let (y, dy) = side(pos.1, self.offset.1, self.visible.1);
+        let arrow = match (dx, dy) {
+            (-1, -1) => "◤ ",
+            (1, -1) => " ◥",
+            (-1, 1) => "◣ ",
+            (1, 1) => " ◢",
+            (-1, _) => "◀ ",
+            (1, _) => " ▶",
+            (_, -1) => "▲ ",
+            _ => "▼ ",
+        };
+        Some(((x, y), arrow))
+    }
+
+    /// fill the block of the cell at `pos` with a one-cell wide `glyph`, if visible
+    pub fn draw<T: Write>(
+        &self,
+        buffer: &mut T,
+        pos: (u16, u16),
+        glyph: StyledContent<&str>,
+    ) -> Result<()> {
+        if !self.is_visible(pos) {
+            return Ok(());
+        }
+        let (x0, y0) = self.origin(pos);
+        for y in y0..y0 + self.cell.1 {
+            queue!(buffer, cursor::MoveTo(x0, y))?;
+            for _ in 0..self.cell.0 / CELL_SZ.0 {
+                queue!(buffer, style::PrintStyledContent(glyph))?;
+            }
+        }
+        Ok(())
+    }
+}